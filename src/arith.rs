@@ -0,0 +1,136 @@
+use crate::executor::ExecResult;
+
+/// What Int `+ - * /` do when the result does not fit in an i64, and what
+/// `<< >>` do when a left shift loses bits or the shift amount is outside `0..64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// Fault with `Integer overflow` (the default).
+    #[default]
+    Checked,
+    /// Two's complement wrap-around; shift amounts are taken modulo 64.
+    Wrapping,
+    /// Clamp to `i64::MIN` / `i64::MAX`. A left shift that loses bits clamps
+    /// by the sign of the operand, `>>` by 64 or more leaves 0 or -1, and a
+    /// negative shift amount shifts the other way.
+    Saturating,
+}
+
+impl std::str::FromStr for OverflowMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "checked" => Ok(Self::Checked),
+            "wrap" | "wrapping" => Ok(Self::Wrapping),
+            "saturate" | "saturating" => Ok(Self::Saturating),
+            _ => Err(format!("unknown overflow mode '{}' (expected checked, wrap or saturate)", s)),
+        }
+    }
+}
+
+/// `a << n` when `n` is in `0..64` and no bit, including the sign, is shifted out.
+fn exact_shl(a: i64, n: i64) -> Option<i64> {
+    let n = u32::try_from(n).ok().filter(|n| *n < 64)?;
+    let shifted = a << n;
+    (shifted >> n == a).then_some(shifted)
+}
+
+fn saturating_shl(a: i64, n: i64) -> i64 {
+    if n < 0 {
+        return saturating_shr(a, n.checked_neg().unwrap_or(i64::MAX));
+    }
+    exact_shl(a, n).unwrap_or(match a.signum() {
+        0 => 0,
+        1 => i64::MAX,
+        _ => i64::MIN,
+    })
+}
+
+fn saturating_shr(a: i64, n: i64) -> i64 {
+    if n < 0 {
+        return saturating_shl(a, n.checked_neg().unwrap_or(i64::MAX));
+    }
+    a >> n.min(63)
+}
+
+/// `a op b` for `op` in `+ - * /`, or `<` / `>` for `<<` / `>>`. None when
+/// the division is by zero or, in `Checked` mode, the result overflows, a left
+/// shift loses bits or the shift amount is out of range.
+pub fn int_op(mode: OverflowMode, op: char, a: i64, b: i64) -> Option<i64> {
+    if op == '/' && b == 0 {
+        return None;
+    }
+    match (mode, op) {
+        (OverflowMode::Checked, '+') => a.checked_add(b),
+        (OverflowMode::Checked, '-') => a.checked_sub(b),
+        (OverflowMode::Checked, '*') => a.checked_mul(b),
+        (OverflowMode::Checked, '/') => a.checked_div(b),
+        (OverflowMode::Checked, '<') => exact_shl(a, b),
+        (OverflowMode::Checked, '>') => u32::try_from(b).ok().and_then(|n| a.checked_shr(n)),
+        (OverflowMode::Wrapping, '+') => Some(a.wrapping_add(b)),
+        (OverflowMode::Wrapping, '-') => Some(a.wrapping_sub(b)),
+        (OverflowMode::Wrapping, '*') => Some(a.wrapping_mul(b)),
+        (OverflowMode::Wrapping, '/') => Some(a.wrapping_div(b)),
+        (OverflowMode::Wrapping, '<') => Some(a.wrapping_shl(b as u32)),
+        (OverflowMode::Wrapping, '>') => Some(a.wrapping_shr(b as u32)),
+        (OverflowMode::Saturating, '+') => Some(a.saturating_add(b)),
+        (OverflowMode::Saturating, '-') => Some(a.saturating_sub(b)),
+        (OverflowMode::Saturating, '*') => Some(a.saturating_mul(b)),
+        (OverflowMode::Saturating, '/') => Some(a.saturating_div(b)),
+        (OverflowMode::Saturating, '<') => Some(saturating_shl(a, b)),
+        (OverflowMode::Saturating, '>') => Some(saturating_shr(a, b)),
+        _ => None,
+    }
+}
+
+pub fn overflow_message(op: char, a: i64, b: i64) -> String {
+    let symbol = match op {
+        '<' => "<<".to_string(),
+        '>' => ">>".to_string(),
+        _ => op.to_string(),
+    };
+    format!("Integer overflow: {} {} {}", a, symbol, b)
+}
+
+pub fn overflow_fault(op: char, a: i64, b: i64) -> ExecResult {
+    let node = match op {
+        '+' => "Node::Add",
+        '-' => "Node::Sub",
+        '*' => "Node::Mul",
+        '<' => "Node::BitShiftLeft",
+        '>' => "Node::BitShiftRight",
+        _ => "Node::Div",
+    };
+    ExecResult::Fault { msg: overflow_message(op, a, b), node: node.into() }
+}
+
+/// Arithmetic emitted by the AOT codegen. Ints are checked and panic on
+/// overflow, the compiled counterpart of the interpreter's fault; Floats
+/// behave like the plain operators.
+pub trait AotArith: Sized {
+    fn add(self, rhs: Self) -> Self;
+    fn sub(self, rhs: Self) -> Self;
+    fn mul(self, rhs: Self) -> Self;
+    fn div(self, rhs: Self) -> Self;
+}
+
+fn aot_int(op: char, a: i64, b: i64) -> i64 {
+    if op == '/' && b == 0 {
+        panic!("Div by zero");
+    }
+    int_op(OverflowMode::Checked, op, a, b).unwrap_or_else(|| panic!("{}", overflow_message(op, a, b)))
+}
+
+impl AotArith for i64 {
+    fn add(self, rhs: Self) -> Self { aot_int('+', self, rhs) }
+    fn sub(self, rhs: Self) -> Self { aot_int('-', self, rhs) }
+    fn mul(self, rhs: Self) -> Self { aot_int('*', self, rhs) }
+    fn div(self, rhs: Self) -> Self { aot_int('/', self, rhs) }
+}
+
+impl AotArith for f64 {
+    fn add(self, rhs: Self) -> Self { self + rhs }
+    fn sub(self, rhs: Self) -> Self { self - rhs }
+    fn mul(self, rhs: Self) -> Self { self * rhs }
+    fn div(self, rhs: Self) -> Self { self / rhs }
+}
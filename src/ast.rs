@@ -37,10 +37,15 @@ pub enum Node {
     MapGet(Box<Node>, Box<Node>),            // Target Map, Key
     MapSet(Box<Node>, Box<Node>, Box<Node>), // Target Map, Key, Value
     MapHasKey(Box<Node>, Box<Node>),         // Target Map, Key
+    MapKeys(Box<Node>),                      // Target Map -> Array of keys
+    MapValues(Box<Node>),                    // Target Map -> Array of values (key order)
+    MapEntries(Box<Node>),                   // Target Map -> Array of [key, value]
+    MapRemove(Box<Node>, Box<Node>),         // Target Map, Key -> removed value or Void
+    MapLen(Box<Node>),                       // Target Map -> Int
     Index(Box<Node>, Box<Node>),             // General index (Expression based)
     Concat(Box<Node>, Box<Node>),
 
-    ObjectLiteral(std::collections::HashMap<String, Node>),
+    ObjectLiteral(indexmap::IndexMap<String, Node>),
     PropertyGet(Box<Node>, String), // Target Object, Property Name
     PropertySet(Box<Node>, String, Box<Node>), // Target Object, Property Name, Value
 
@@ -205,9 +210,134 @@ pub enum Node {
     While(Box<Node>, Box<Node>),
     Block(Vec<Node>),
     Return(Box<Node>),
+    Yield, // Suspends the running coroutine until the next frame
     Import(String),
     AddWorldAABB { min: Box<Node>, max: Box<Node> },
     CheckCollision { a_min: Box<Node>, a_max: Box<Node>, b_min: Box<Node>, b_max: Box<Node> },
+
+    // Source Mapping: statement wrapper emitted by Parser::with_locations (debugger / tooling)
+    Located { line: usize, col: usize, stmt: Box<Node> },
+}
+
+impl Node {
+    /// Variant name, used by tooling (profiler, tracer) to group nodes.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Node::IntLiteral { .. } => "IntLiteral",
+            Node::FloatLiteral { .. } => "FloatLiteral",
+            Node::BoolLiteral { .. } => "BoolLiteral",
+            Node::StringLiteral { .. } => "StringLiteral",
+            Node::Identifier { .. } => "Identifier",
+            Node::Assign { .. } => "Assign",
+            Node::Add { .. } => "Add",
+            Node::Sub { .. } => "Sub",
+            Node::Mul { .. } => "Mul",
+            Node::Div { .. } => "Div",
+            Node::Sin { .. } => "Sin",
+            Node::Cos { .. } => "Cos",
+            Node::Mat4Mul { .. } => "Mat4Mul",
+            Node::Time => "Time",
+            Node::GlobalTime => "GlobalTime",
+            Node::Abs { .. } => "Abs",
+            Node::Eq { .. } => "Eq",
+            Node::Lt { .. } => "Lt",
+            Node::Gt { .. } => "Gt",
+            Node::ArrayCreate { .. } => "ArrayCreate",
+            Node::ArrayGet { .. } => "ArrayGet",
+            Node::ArraySet { .. } => "ArraySet",
+            Node::ArrayPush { .. } => "ArrayPush",
+            Node::ArrayLen { .. } => "ArrayLen",
+            Node::MapCreate => "MapCreate",
+            Node::MapGet { .. } => "MapGet",
+            Node::MapSet { .. } => "MapSet",
+            Node::MapHasKey { .. } => "MapHasKey",
+            Node::MapKeys { .. } => "MapKeys",
+            Node::MapValues { .. } => "MapValues",
+            Node::MapEntries { .. } => "MapEntries",
+            Node::MapRemove { .. } => "MapRemove",
+            Node::MapLen { .. } => "MapLen",
+            Node::Index { .. } => "Index",
+            Node::Concat { .. } => "Concat",
+            Node::ObjectLiteral { .. } => "ObjectLiteral",
+            Node::PropertyGet { .. } => "PropertyGet",
+            Node::PropertySet { .. } => "PropertySet",
+            Node::BitAnd { .. } => "BitAnd",
+            Node::BitShiftLeft { .. } => "BitShiftLeft",
+            Node::BitShiftRight { .. } => "BitShiftRight",
+            Node::FnDef { .. } => "FnDef",
+            Node::Call { .. } => "Call",
+            Node::FileRead { .. } => "FileRead",
+            Node::FileWrite { .. } => "FileWrite",
+            Node::Print { .. } => "Print",
+            Node::FSRead { .. } => "FSRead",
+            Node::FSWrite { .. } => "FSWrite",
+            Node::Store { .. } => "Store",
+            Node::Load { .. } => "Load",
+            Node::DrawRect { .. } => "DrawRect",
+            Node::UIFixed { .. } => "UIFixed",
+            Node::UIFillParent => "UIFillParent",
+            Node::RenderCanvas { .. } => "RenderCanvas",
+            Node::Transform2D { .. } => "Transform2D",
+            Node::Sprite2D { .. } => "Sprite2D",
+            Node::Camera3D { .. } => "Camera3D",
+            Node::Mesh3D { .. } => "Mesh3D",
+            Node::PointLight3D { .. } => "PointLight3D",
+            Node::Material3D { .. } => "Material3D",
+            Node::MeshInstance3D { .. } => "MeshInstance3D",
+            Node::FPSCamera { .. } => "FPSCamera",
+            Node::MouseGrab { .. } => "MouseGrab",
+            Node::RaycastSimple => "RaycastSimple",
+            Node::WeaponViewModel { .. } => "WeaponViewModel",
+            Node::Fetch { .. } => "Fetch",
+            Node::Extract { .. } => "Extract",
+            Node::EvalJSONNative { .. } => "EvalJSONNative",
+            Node::ToString { .. } => "ToString",
+            Node::NativeCall { .. } => "NativeCall",
+            Node::ExternCall { .. } => "ExternCall",
+            Node::InitWindow { .. } => "InitWindow",
+            Node::InitGraphics => "InitGraphics",
+            Node::LoadShader { .. } => "LoadShader",
+            Node::RenderMesh { .. } => "RenderMesh",
+            Node::PollEvents { .. } => "PollEvents",
+            Node::InitAudio => "InitAudio",
+            Node::PlayNote { .. } => "PlayNote",
+            Node::StopNote { .. } => "StopNote",
+            Node::LoadMesh { .. } => "LoadMesh",
+            Node::LoadTexture { .. } => "LoadTexture",
+            Node::PlayAudioFile { .. } => "PlayAudioFile",
+            Node::RenderAsset { .. } => "RenderAsset",
+            Node::LoadFont { .. } => "LoadFont",
+            Node::DrawText { .. } => "DrawText",
+            Node::GetLastKeypress => "GetLastKeypress",
+            Node::UIWindow { .. } => "UIWindow",
+            Node::UILabel { .. } => "UILabel",
+            Node::UIButton { .. } => "UIButton",
+            Node::UITextInput { .. } => "UITextInput",
+            Node::UISetStyle { .. } => "UISetStyle",
+            Node::UIHorizontal { .. } => "UIHorizontal",
+            Node::UIFullscreen { .. } => "UIFullscreen",
+            Node::UIGrid { .. } => "UIGrid",
+            Node::UIScrollArea { .. } => "UIScrollArea",
+            Node::InitCamera { .. } => "InitCamera",
+            Node::DrawVoxelGrid { .. } => "DrawVoxelGrid",
+            Node::LoadTextureAtlas { .. } => "LoadTextureAtlas",
+            Node::LoadSample { .. } => "LoadSample",
+            Node::PlaySample { .. } => "PlaySample",
+            Node::InitVoxelMap => "InitVoxelMap",
+            Node::SetVoxel { .. } => "SetVoxel",
+            Node::EnableInteraction { .. } => "EnableInteraction",
+            Node::EnablePhysics { .. } => "EnablePhysics",
+            Node::If { .. } => "If",
+            Node::While { .. } => "While",
+            Node::Block { .. } => "Block",
+            Node::Return { .. } => "Return",
+            Node::Yield => "Yield",
+            Node::Import { .. } => "Import",
+            Node::AddWorldAABB { .. } => "AddWorldAABB",
+            Node::CheckCollision { .. } => "CheckCollision",
+            Node::Located { .. } => "Located",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -7,6 +7,8 @@ pub struct FetchTask {
     pub method: String,
     pub url: String,
     pub callback_node: Box<Node>,
+    /// The engine's `AgentPermissions::allowed_domains`; redirects must stay inside it.
+    pub allowed_domains: Vec<String>,
 }
 
 /// The result returned from the background worker to the main WGPU thread.
@@ -34,35 +36,19 @@ impl AsyncBridge {
             let agent = ureq::AgentBuilder::new()
                 .timeout(std::time::Duration::from_secs(10))
                 .build();
+            // Requests limited by `allowed_domains` follow redirects by hand so
+            // every hop is checked against the allowlist.
+            let checked_agent = ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(10))
+                .redirects(0)
+                .build();
 
             // Processing loop: wait for tasks from the main thread
             while let Ok(task) = rx_task.recv() {
-                let payload = match task.method.to_uppercase().as_str() {
-                    "GET" => match agent.get(&task.url).call() {
-                        Ok(response) => match response.into_string() {
-                            Ok(body) => Ok(body),
-                            Err(e) => Err(format!("Failed to read GET response: {}", e)),
-                        },
-                        Err(ureq::Error::Status(code, response)) => Err(format!(
-                            "HTTP {} Error: {}",
-                            code,
-                            response.into_string().unwrap_or_default()
-                        )),
-                        Err(e) => Err(format!("GET Request failed: {}", e)),
-                    },
-                    "POST" => match agent.post(&task.url).call() {
-                        Ok(response) => match response.into_string() {
-                            Ok(body) => Ok(body),
-                            Err(e) => Err(format!("Failed to read POST response: {}", e)),
-                        },
-                        Err(ureq::Error::Status(code, response)) => Err(format!(
-                            "HTTP {} Error: {}",
-                            code,
-                            response.into_string().unwrap_or_default()
-                        )),
-                        Err(e) => Err(format!("POST Request failed: {}", e)),
-                    },
-                    _ => Err(format!("Unsupported HTTP method: {}", task.method)),
+                let payload = if task.allowed_domains.is_empty() {
+                    fetch(&agent, &task.method, &task.url).map(|(_, _, body)| body)
+                } else {
+                    fetch_checked(&checked_agent, &task)
                 };
 
                 // Send the payload back to the main thread's Receiver
@@ -80,11 +66,12 @@ impl AsyncBridge {
     }
 
     /// Dispatch a request to the background thread without blocking.
-    pub fn dispatch_fetch(&self, method: String, url: String, callback_node: Box<Node>) {
+    pub fn dispatch_fetch(&self, method: String, url: String, callback_node: Box<Node>, allowed_domains: Vec<String>) {
         let _ = self.tx_task.send(FetchTask {
             method,
             url,
             callback_node,
+            allowed_domains,
         });
     }
 
@@ -94,3 +81,52 @@ impl AsyncBridge {
         self.rx_payload.try_recv().ok()
     }
 }
+
+/// Most redirects `fetch_checked` follows.
+const MAX_REDIRECTS: usize = 5;
+
+/// Performs one request. Ok carries the status, `Location` header and body of
+/// any non-error response, including redirects the agent did not follow.
+fn fetch(agent: &ureq::Agent, method: &str, url: &str) -> Result<(u16, Option<String>, String), String> {
+    let method = method.to_uppercase();
+    let request = match method.as_str() {
+        "GET" => agent.get(url),
+        "POST" => agent.post(url),
+        _ => return Err(format!("Unsupported HTTP method: {}", method)),
+    };
+    match request.call() {
+        Ok(response) => {
+            let status = response.status();
+            let location = response.header("Location").map(str::to_string);
+            match response.into_string() {
+                Ok(body) => Ok((status, location, body)),
+                Err(e) => Err(format!("Failed to read {} response: {}", method, e)),
+            }
+        }
+        Err(ureq::Error::Status(code, response)) => Err(format!(
+            "HTTP {} Error: {}",
+            code,
+            response.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(format!("{} Request failed: {}", method, e)),
+    }
+}
+
+/// Like `fetch` with redirects, refusing any hop whose host is not allowed.
+fn fetch_checked(agent: &ureq::Agent, task: &FetchTask) -> Result<String, String> {
+    let (mut method, mut url) = (task.method.to_uppercase(), task.url.clone());
+    for _ in 0..=MAX_REDIRECTS {
+        crate::executor::check_domains(&task.allowed_domains, &url)?;
+        let (status, location) = match fetch(agent, &method, &url)? {
+            (status, Some(location), _) if (300..400).contains(&status) => (status, location),
+            (_, _, body) => return Ok(body),
+        };
+        let base = url::Url::parse(&url).map_err(|e| format!("{} Request failed: {}", method, e))?;
+        url = base.join(&location).map_err(|e| format!("Invalid redirect to '{}': {}", location, e))?.to_string();
+        // 307 and 308 keep the method; the other redirects continue with GET.
+        if !matches!(status, 307 | 308) {
+            method = "GET".into();
+        }
+    }
+    Err(format!("More than {} redirects", MAX_REDIRECTS))
+}
@@ -252,9 +252,10 @@ fn main() {
             Box::new(Node::Block(vec![
                 Node::Assign(
                     "len".to_string(),
-                    Box::new(Node::ArrayLen(Box::new(Node::Identifier(
-                        "str".to_string(),
-                    )))),
+                    Box::new(Node::NativeCall(
+                        "String.Len".to_string(),
+                        vec![Node::Identifier("str".to_string())],
+                    )),
                 ),
                 Node::If(
                     Box::new(Node::Lt(
@@ -0,0 +1,14 @@
+// Debug Adapter Protocol server for KnotenCore scripts.
+// Point a DAP-capable editor at this binary; it speaks the protocol over stdio.
+
+fn main() {
+    // Same 8MB stack as run_knc, so deeply recursive scripts behave identically under the debugger.
+    let builder = std::thread::Builder::new().stack_size(8 * 1024 * 1024);
+    let handler = builder
+        .spawn(|| {
+            let input = Box::new(std::io::BufReader::new(std::io::stdin()));
+            knoten_core::dap::serve(input, Box::new(std::io::stdout()));
+        })
+        .expect("Failed to spawn KnotenCore debug adapter thread");
+    handler.join().unwrap();
+}
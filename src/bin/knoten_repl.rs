@@ -0,0 +1,46 @@
+// Interactive shell for KnotenCore. One engine lives for the whole session,
+// so variables and functions defined on earlier lines stay available.
+
+use std::io::{BufRead, Write};
+
+fn main() {
+    // Same 8MB stack as run_knc, so deeply recursive definitions behave identically.
+    let builder = std::thread::Builder::new().stack_size(8 * 1024 * 1024);
+    let handler = builder.spawn(run).expect("Failed to spawn KnotenCore REPL thread");
+    handler.join().unwrap();
+}
+
+fn run() {
+    let mut repl = knoten_core::repl::Repl::new();
+    // Sandboxed like run_knc: file system access has to be granted explicitly.
+    repl.engine.permissions.allow_fs_read = false;
+    repl.engine.permissions.allow_fs_write = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--allow-read" => repl.engine.permissions.allow_fs_read = true,
+            "--allow-write" => repl.engine.permissions.allow_fs_write = true,
+            "--allow-network" => repl.engine.permissions.allow_network = true,
+            _ => {
+                eprintln!("Usage: knoten_repl [--allow-read] [--allow-write] [--allow-network]");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    println!("KnotenCore REPL. Type :help for commands, :quit to exit.");
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{}", if repl.is_continuation() { "   ...> " } else { "knoten> " });
+        let _ = std::io::stdout().flush();
+        let Some(Ok(line)) = lines.next() else { break };
+        if !repl.is_continuation() && matches!(line.trim(), ":quit" | ":q") {
+            break;
+        }
+        if let Some(out) = repl.feed(&line)
+            && !out.is_empty()
+        {
+            println!("{}", out);
+        }
+    }
+}
@@ -39,6 +39,15 @@ fn run() {
     let mut is_check = false;
     let mut no_opt = false;
     let mut transpile = false;
+    let mut debug = false;
+    let mut profile = false;
+    let mut watch = false;
+    let mut trace_path: Option<String> = None;
+    let mut trace_functions: Vec<String> = Vec::new();
+    let mut restore_path: Option<String> = None;
+    let mut snapshot_path: Option<String> = None;
+    let mut list_natives = false;
+    let mut json_output = false;
     let mut file_path = String::new();
 
     for arg in args.iter().skip(1) {
@@ -48,17 +57,73 @@ fn run() {
             no_opt = true;
         } else if arg == "--transpile" {
             transpile = true;
+        } else if arg == "--debug" {
+            debug = true;
+        } else if arg == "--watch" {
+            watch = true;
+        } else if arg == "--profile" {
+            profile = true;
+        } else if let Some(path) = arg.strip_prefix("--trace=") {
+            trace_path = Some(path.to_string());
+        } else if let Some(path) = arg.strip_prefix("--restore=") {
+            restore_path = Some(path.to_string());
+        } else if let Some(path) = arg.strip_prefix("--snapshot=") {
+            snapshot_path = Some(path.to_string());
+        } else if let Some(names) = arg.strip_prefix("--trace-fn=") {
+            trace_functions.extend(names.split(',').filter(|n| !n.is_empty()).map(String::from));
+        } else if let Some(mode) = arg.strip_prefix("--overflow=") {
+            engine.overflow_mode = mode.parse().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        } else if arg == "--list-natives" {
+            list_natives = true;
+        } else if arg == "--json" {
+            json_output = true;
         } else if arg == "--allow-read" {
             engine.permissions.allow_fs_read = true;
         } else if arg == "--allow-write" {
             engine.permissions.allow_fs_write = true;
+        } else if let Some(dirs) = arg.strip_prefix("--allow-read=") {
+            // Grants read access limited to the listed directories; repeatable.
+            for dir in dirs.split(',').filter(|d| !d.is_empty()) {
+                engine.permissions.grant_read(dir).unwrap_or_else(|e| {
+                    eprintln!("Error: --allow-read: {}", e);
+                    std::process::exit(1);
+                });
+            }
+        } else if let Some(dirs) = arg.strip_prefix("--allow-write=") {
+            // Grants write access limited to the listed directories; repeatable.
+            for dir in dirs.split(',').filter(|d| !d.is_empty()) {
+                engine.permissions.grant_write(dir).unwrap_or_else(|e| {
+                    eprintln!("Error: --allow-write: {}", e);
+                    std::process::exit(1);
+                });
+            }
         } else if arg == "--allow-network" {
             engine.permissions.allow_network = true;
+        } else if let Some(domains) = arg.strip_prefix("--allow-domain=") {
+            // Grants network access limited to the listed hosts; repeatable.
+            engine.permissions.allow_network = true;
+            engine.permissions.allowed_domains.extend(domains.split(',').filter(|d| !d.is_empty()).map(String::from));
         } else {
             file_path = arg.clone();
         }
     }
 
+    if list_natives {
+        let natives = engine.native_catalogue();
+        if json_output {
+            println!("{}", serde_json::to_string_pretty(natives).expect("Native catalogue is serializable"));
+        } else {
+            for native in natives {
+                let permission = native.permission.as_deref().map(|p| format!(" [{}]", p)).unwrap_or_default();
+                println!("{}{}\n    {}", native.signature(), permission, native.doc);
+            }
+        }
+        return;
+    }
+
     // Check if we are bundled (Sprint 11) - Respects permissions set above
     if let Some(bundled_json) = option_env!("KNOTEN_BUNDLE") {
         println!("Running embedded KnotenCore bundle...");
@@ -70,7 +135,8 @@ fn run() {
     }
 
     if file_path.is_empty() {
-        eprintln!("Usage: run_knc [--check] [--no-opt] [--transpile] [--allow-read] [--allow-write] [--allow-network] <path_to.nod>");
+        eprintln!("Usage: run_knc [--check] [--no-opt] [--transpile] [--debug] [--watch] [--profile] [--trace=<out.jsonl>] [--trace-fn=<fn,...>] [--restore=<snap.json>] [--snapshot=<snap.json>] [--overflow=checked|wrap|saturate] [--allow-read[=<dir>,...]] [--allow-write[=<dir>,...]] [--allow-network] [--allow-domain=<host|*.host>,...] <path_to.nod>");
+        eprintln!("       run_knc --list-natives [--json]");
         eprintln!("       run_knc build <path_to.nod>");
         std::process::exit(1);
     }
@@ -79,12 +145,12 @@ fn run() {
     println!("Loading KnotenCore Script: {}", file_path);
 
     let json_string = fs::read_to_string(&file_path).expect("Failed to read file");
-    let mut ast = if file_path.ends_with(".knoten") {
-        let mut parser = knoten_core::parser::Parser::new(&json_string);
-        parser.parse()
-    } else {
-        serde_json::from_str(&json_string).expect("Failed to parse KnotenCore AST")
-    };
+    // Debug sessions and traces need statement locations for lines and stepping.
+    let locations = debug || trace_path.is_some();
+    let mut ast = knoten_core::parser::parse_script(&file_path, &json_string, locations).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
 
     let mut typer = knoten_core::optimizer::TypeChecker::new();
     let _ = typer.check(&ast);
@@ -96,7 +162,27 @@ fn run() {
         std::process::exit(1);
     }
 
-    if !no_opt {
+    // Malformed nodes and unknown NativeCall / ExternCall names are rejected before anything runs.
+    let mut validator = knoten_core::validator::Validator::with_natives(engine.native_catalogue());
+    if let Err(errors) = validator.validate(&ast) {
+        eprintln!("\nValidation Failed:");
+        for err in errors {
+            eprintln!(" - {}", err);
+        }
+        std::process::exit(1);
+    }
+
+    if debug {
+        if !file_path.ends_with(".knoten") {
+            eprintln!("[debug] JSON ASTs carry no source lines: only function breakpoints are available.");
+        }
+        println!("[debug] Type 'h' at the prompt for commands.");
+        let frontend = knoten_core::debugger::ConsoleFrontend::stdio(&json_string);
+        engine.debugger = Some(Box::new(knoten_core::debugger::Debugger::new(Box::new(frontend), true)));
+    }
+
+    // Optimisation is skipped while debugging so stepping follows the source as written.
+    if !no_opt && !debug {
         let before_nodes = knoten_core::optimizer::count_nodes(&ast);
         ast = knoten_core::optimizer::optimize(ast);
         let after_nodes = knoten_core::optimizer::count_nodes(&ast);
@@ -107,21 +193,8 @@ fn run() {
     }
 
     if is_check {
-        use knoten_core::validator::Validator;
-        let mut validator = Validator::new();
-        match validator.validate(&ast) {
-            Ok(_) => {
-                println!("\nSyntax OK");
-                std::process::exit(0);
-            }
-            Err(errors) => {
-                eprintln!("\nValidation Failed:");
-                for err in errors {
-                    eprintln!(" - {}", err);
-                }
-                std::process::exit(1);
-            }
-        }
+        println!("\nSyntax OK");
+        std::process::exit(0);
     }
 
     // ── Main Thread Loop & Proxy Setup ─────────────────────────────
@@ -138,6 +211,34 @@ fn run() {
     let proxy = event_loop.create_proxy();
     knoten_core::natives::registry::set_render_channel(proxy);
 
+    // Reports are written to the working directory as <stem>.folded / <stem>.profile.json.
+    let profile_prefix = Path::new(&file_path).file_stem().and_then(|s| s.to_str()).unwrap_or("knoten").to_string();
+    if profile {
+        engine.profiler = Some(Box::new(knoten_core::profiler::Profiler::new()));
+    }
+    if let Some(path) = &restore_path {
+        if let Err(e) = engine.load_snapshot(path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        println!("Restored engine state from {}", path);
+    }
+    if watch && let Some(tx) = engine.action_tx.clone() {
+        // Function bodies are swapped in between loop iterations; see hot_reload::spawn_watcher.
+        knoten_core::hot_reload::spawn_watcher(file_path.clone(), tx, !no_opt && !debug, engine.native_catalogue().to_vec(), std::time::Duration::from_millis(300));
+        println!("[watch] Watching {} for changes", file_path);
+    }
+    if let Some(path) = &trace_path {
+        match knoten_core::tracer::Tracer::to_file(path) {
+            Ok(tracer) if trace_functions.is_empty() => engine.tracer = Some(Box::new(tracer)),
+            Ok(tracer) => engine.tracer = Some(Box::new(tracer.with_functions(trace_functions))),
+            Err(e) => {
+                eprintln!("Cannot create trace file '{}': {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     let ast_arc = Arc::new(ast);
     let ast_for_thread = ast_arc.clone();
     let mut thread_engine = engine; // Move the engine with set permissions
@@ -147,6 +248,21 @@ fn run() {
         .spawn(move || {
             let result = thread_engine.execute(&ast_for_thread);
             println!("\nExecution Finished.\nResult: {}", result);
+            if let Some(path) = &snapshot_path {
+                match thread_engine.save_snapshot(path) {
+                    Ok(()) => println!("Snapshot written: {}", path),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            if let Some(tracer) = thread_engine.tracer.as_mut() {
+                tracer.flush();
+            }
+            if let Some(mut profiler) = thread_engine.profiler.take() {
+                match profiler.write_reports(&profile_prefix) {
+                    Ok((folded, summary)) => println!("Profile written: {} and {}", folded, summary),
+                    Err(e) => eprintln!("Failed to write profile: {}", e),
+                }
+            }
             knoten_core::natives::registry::exit_event_loop();
         })
         .expect("Failed to spawn executor thread");
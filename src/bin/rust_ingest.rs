@@ -29,7 +29,7 @@ fn parse_rust_file(file_content: &str, module_name: &str) -> Node {
                 in_struct = false;
                 // Generate a constructor function for this struct
                 let mut arg_names = Vec::new();
-                let mut obj_map = std::collections::HashMap::new();
+                let mut obj_map = indexmap::IndexMap::new();
 
                 for field in &current_struct_fields {
                     arg_names.push(field.clone());
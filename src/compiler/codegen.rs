@@ -88,6 +88,7 @@ impl Codegen {
                 }
                 out
             }
+            Node::Located { stmt, .. } => self.generate(stmt, is_root),
             Node::Print(expr) => {
                 let inner = self.generate(expr, false);
                 format!("println!(\"{{}}\", {})", inner)
@@ -154,22 +155,22 @@ impl Codegen {
             Node::StringLiteral(v) => format!("String::from(\"{}\")", v),
             Node::Identifier(name) => name.clone(),
             Node::Add(l, r) => format!(
-                "({} + {})",
+                "knoten_core::arith::AotArith::add({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Sub(l, r) => format!(
-                "({} - {})",
+                "knoten_core::arith::AotArith::sub({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Mul(l, r) => format!(
-                "({} * {})",
+                "knoten_core::arith::AotArith::mul({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Div(l, r) => format!(
-                "({} / {})",
+                "knoten_core::arith::AotArith::div({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
@@ -237,7 +238,7 @@ impl Codegen {
             Node::ArrayLen(arr) => {
                 format!("{}.len() as i64", self.generate(arr, false))
             }
-            Node::MapCreate => "std::collections::HashMap::new()".to_string(),
+            Node::MapCreate => "knoten_core::compiler::AotMap::new()".to_string(),
             Node::MapGet(map, key) => {
                 format!(
                     "{}.get(&{}).cloned().unwrap()",
@@ -270,6 +271,30 @@ impl Codegen {
                     self.generate(key, false)
                 )
             }
+            Node::MapKeys(map) => {
+                format!(
+                    "{}.keys().cloned().collect::<Vec<String>>()",
+                    self.generate(map, false)
+                )
+            }
+            Node::MapValues(map) | Node::MapEntries(map) => {
+                let item = if matches!(node, Node::MapValues(_)) { "|(_, v)| v.clone()" } else { "|(k, v)| (k.clone(), v.clone())" };
+                format!(
+                    "{}.iter().map({}).collect::<Vec<_>>()",
+                    self.generate(map, false),
+                    item
+                )
+            }
+            Node::MapRemove(map, key) => {
+                format!(
+                    "{}.shift_remove(&{})",
+                    self.generate(map, false),
+                    self.generate(key, false)
+                )
+            }
+            Node::MapLen(map) => {
+                format!("{}.len() as i64", self.generate(map, false))
+            }
             Node::If(cond, then_b, else_b) => {
                 let cond_str = self.generate(cond, false);
                 let then_str = self.generate(then_b, false);
@@ -1 +1,5 @@
 pub mod codegen;
+
+/// Map type of AOT-compiled programs. Keys keep their insertion order, like
+/// the interpreter's objects (`executor::ObjectMap`).
+pub type AotMap<K, V> = indexmap::IndexMap<K, V>;
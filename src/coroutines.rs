@@ -0,0 +1,334 @@
+//! Coroutines: script functions that suspend at `yield` and continue on the
+//! next frame.
+//!
+//! The evaluator is recursive, so a coroutine cannot keep its Rust stack
+//! between frames. Instead its body is run by `co_exec`, which understands
+//! the statements a coroutine can suspend inside (`Block`, `If`, `While`) and
+//! records where it stopped as a path of statement indices. The next step
+//! walks back down that path. Everything else, including calls to other
+//! functions, is evaluated normally and cannot yield.
+
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType, StackFrame};
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::{arg, check_arity, describe, fault};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+const AWAIT: &str = "Coroutine.Await";
+/// Outcomes kept per engine; the oldest ids are forgotten first.
+const MAX_FINISHED: usize = 1024;
+
+/// A started coroutine that has not finished yet.
+pub struct Coroutine {
+    pub function: String,
+    body: Arc<Node>,
+    locals: HashMap<String, RelType>,
+    /// Where the last step stopped; None before the first step.
+    resume: Option<Vec<usize>>,
+}
+
+/// How a coroutine ended.
+#[derive(Clone)]
+pub enum Outcome {
+    Done(RelType),
+    Cancelled,
+    Faulted(String),
+}
+
+/// The coroutines of one engine. Ids are never reused; the last
+/// `MAX_FINISHED` outcomes are kept so `Coroutine.Status` and
+/// `Coroutine.Await` keep working after the end.
+#[derive(Default)]
+pub struct Coroutines {
+    next_id: i64,
+    live: BTreeMap<i64, Coroutine>,
+    finished: BTreeMap<i64, Outcome>,
+    /// The coroutine being stepped; it is out of `live` meanwhile.
+    running: Option<i64>,
+    cancel_running: bool,
+}
+
+impl Coroutines {
+    /// Number of coroutines that have not finished.
+    pub fn pending(&self) -> usize {
+        self.live.len() + usize::from(self.running.is_some())
+    }
+
+    fn finish(&mut self, id: i64, outcome: Outcome) {
+        self.finished.insert(id, outcome);
+        while self.finished.len() > MAX_FINISHED {
+            self.finished.pop_first();
+        }
+    }
+}
+
+/// Result of running part of a coroutine body.
+enum Co {
+    Done(ExecResult),
+    /// Suspended; the path back to the suspension point, innermost index first.
+    Suspended(Vec<usize>),
+}
+
+/// The `Coroutine.Await(id)` call of a statement `Coroutine.Await(id);` or
+/// `x = Coroutine.Await(id);`, the only places an await may suspend.
+fn await_statement(node: &Node) -> Option<(&[Node], Option<&str>)> {
+    match node {
+        Node::NativeCall(name, args) if name == AWAIT => Some((args, None)),
+        Node::Assign(target, value) => match value.as_ref() {
+            Node::NativeCall(name, args) if name == AWAIT => Some((args, Some(target))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Catalogue entries of the `Coroutine.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Coroutine.Start", &["function: Function", "args?: Array"], "Int", None, "Starts a function that may `yield`; it first runs at the next pump. Returns the coroutine id."),
+        ("", "Coroutine.Cancel", &["id: Int"], "Bool", None, "Stops a coroutine; false if it had already finished."),
+        ("", "Coroutine.Status", &["id: Int"], "String", None, "suspended, done, cancelled or faulted."),
+        ("", "Coroutine.Await", &["id: Int"], "Any", None, "Return value of a finished coroutine. As a statement in a coroutine, waits until it finishes."),
+        ("", "Coroutine.Pending", &[], "Int", None, "Number of coroutines that have not finished."),
+    ]
+}
+
+impl ExecutionEngine {
+    /// `Coroutine.*` natives.
+    pub(crate) fn coroutine_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Coroutine.Start" => self.coroutine_start(name, args),
+            "Coroutine.Cancel" => check_arity(name, args, 1).and_then(|_| arg::<i64>(name, args, 0)).map(|id| {
+                let co = &mut self.coroutines;
+                let cancelled = if co.running == Some(id) {
+                    co.cancel_running = true;
+                    true
+                } else {
+                    co.live.remove(&id).is_some()
+                };
+                if cancelled {
+                    co.finish(id, Outcome::Cancelled);
+                }
+                ExecResult::Value(RelType::Bool(cancelled))
+            }),
+            "Coroutine.Status" => check_arity(name, args, 1).and_then(|_| arg::<i64>(name, args, 0)).and_then(|id| {
+                let status = match self.coroutine_outcome(name, id)? {
+                    None => "suspended",
+                    Some(Outcome::Done(_)) => "done",
+                    Some(Outcome::Cancelled) => "cancelled",
+                    Some(Outcome::Faulted(_)) => "faulted",
+                };
+                Ok(ExecResult::Value(RelType::Str(status.into())))
+            }),
+            AWAIT => check_arity(name, args, 1).and_then(|_| arg::<i64>(name, args, 0)).and_then(|id| match self.coroutine_outcome(name, id)? {
+                Some(outcome) => self.await_result(id, outcome),
+                None => Err(fault(name, format!("coroutine {} has not finished; only a coroutine can wait for it", id))),
+            }),
+            "Coroutine.Pending" => {
+                check_arity(name, args, 0).map(|_| ExecResult::Value(RelType::Int(self.coroutines.pending() as i64)))
+            }
+            _ => return None,
+        };
+        Some(res.unwrap_or_else(|fault| fault))
+    }
+
+    fn coroutine_start(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        if !(1..=2).contains(&args.len()) {
+            return Err(fault(name, format!("expected 1 or 2 args, got {}", args.len())));
+        }
+        let (function, params, body) = match &args[0] {
+            RelType::FnDef(fname, params, body) => (fname.clone(), params.clone(), body.as_ref().clone()),
+            RelType::Str(fname) => match self.get_var(fname) {
+                Some(RelType::FnDef(_, params, body)) => (fname.clone(), params, *body),
+                _ => return Err(fault(name, format!("'{}' is not a function", fname))),
+            },
+            other => return Err(fault(name, format!("function must be a Function, got {}", describe(other)))),
+        };
+        let call_args: Vec<RelType> = if args.len() == 2 { arg(name, args, 1)? } else { Vec::new() };
+        if call_args.len() != params.len() {
+            return Err(fault(name, format!("'{}' expects {} args, got {}", function, params.len(), call_args.len())));
+        }
+        let co = &mut self.coroutines;
+        co.next_id += 1;
+        let coroutine = Coroutine {
+            function,
+            body: Arc::new(body),
+            locals: params.into_iter().zip(call_args).collect(),
+            resume: None,
+        };
+        co.live.insert(co.next_id, coroutine);
+        Ok(ExecResult::Value(RelType::Int(co.next_id)))
+    }
+
+    /// None while the coroutine is still running or suspended.
+    fn coroutine_outcome(&self, native: &str, id: i64) -> Result<Option<Outcome>, ExecResult> {
+        let co = &self.coroutines;
+        if let Some(outcome) = co.finished.get(&id) {
+            return Ok(Some(outcome.clone()));
+        }
+        if co.live.contains_key(&id) || co.running == Some(id) {
+            return Ok(None);
+        }
+        Err(fault(native, format!("no coroutine with id {}", id)))
+    }
+
+    fn await_result(&self, id: i64, outcome: Outcome) -> Result<ExecResult, ExecResult> {
+        match outcome {
+            Outcome::Done(v) => Ok(ExecResult::Value(v)),
+            Outcome::Cancelled => Err(fault(AWAIT, format!("coroutine {} was cancelled", id))),
+            Outcome::Faulted(msg) => Err(fault(AWAIT, format!("coroutine {} faulted: {}", id, msg))),
+        }
+    }
+
+    /// Resumes every coroutine started before this call, in start order, until
+    /// its next `yield`, an unfinished `Coroutine.Await` or its end. A fault
+    /// ends the coroutine and is reported on stderr. Called from the per-frame
+    /// pump, `poll_async_bridge`.
+    pub fn poll_coroutines(&mut self) {
+        if self.coroutines.running.is_some() {
+            return;
+        }
+        let ids: Vec<i64> = self.coroutines.live.keys().copied().collect();
+        for id in ids {
+            // An earlier coroutine may have cancelled this one.
+            let Some(mut coroutine) = self.coroutines.live.remove(&id) else { continue };
+            self.coroutines.running = Some(id);
+            self.call_stack.push(StackFrame { locals: std::mem::take(&mut coroutine.locals), function: coroutine.function.clone(), line: 0 });
+            let body = coroutine.body.clone();
+            let resume = coroutine.resume.take();
+            let res = self.co_exec(&body, resume.as_deref());
+            coroutine.locals = self.call_stack.pop().map(|frame| frame.locals).unwrap_or_default();
+            let co = &mut self.coroutines;
+            co.running = None;
+            if std::mem::take(&mut co.cancel_running) {
+                continue;
+            }
+            let outcome = match res {
+                Co::Suspended(mut path) => {
+                    path.reverse();
+                    coroutine.resume = Some(path);
+                    co.live.insert(id, coroutine);
+                    continue;
+                }
+                Co::Done(ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v)) => Outcome::Done(v),
+                Co::Done(ExecResult::Fault { msg, node }) => {
+                    eprintln!("[coroutine] {} ({}) failed: {} (at {})", id, coroutine.function, msg, node);
+                    Outcome::Faulted(msg)
+                }
+            };
+            co.finish(id, outcome);
+        }
+    }
+
+    /// Runs `node`, or continues it from `at`, a path produced by an earlier
+    /// suspension. `Some(&[])` means the suspending statement itself: a
+    /// `yield` is then finished, an await is checked again.
+    fn co_exec(&mut self, node: &Node, at: Option<&[usize]>) -> Co {
+        if let Some((args, target)) = await_statement(node) {
+            return self.co_await(args, target);
+        }
+        match node {
+            Node::Yield => match at {
+                Some(_) => Co::Done(ExecResult::Value(RelType::Void)),
+                None => Co::Suspended(Vec::new()),
+            },
+            Node::Located { line, stmt, .. } => {
+                if let Some(frame) = self.call_stack.last_mut() { frame.line = *line; }
+                self.co_exec(stmt, at)
+            }
+            Node::Block(nodes) => {
+                let (start, mut inner) = match at {
+                    Some([i, rest @ ..]) => (*i, Some(rest)),
+                    _ => (0, None),
+                };
+                let mut last_val = RelType::Void;
+                let len = nodes.len();
+                for (i, n) in nodes.iter().enumerate().skip(start) {
+                    match self.co_exec(n, inner.take()) {
+                        Co::Done(ExecResult::Value(v)) if i < len - 1 => self.release_handles(&v),
+                        Co::Done(ExecResult::Value(v)) => last_val = v,
+                        Co::Done(other) => return Co::Done(other),
+                        Co::Suspended(mut path) => {
+                            path.push(i);
+                            return Co::Suspended(path);
+                        }
+                    }
+                }
+                Co::Done(ExecResult::Value(last_val))
+            }
+            Node::If(cond, then_b, else_b) => {
+                let (branch, rest) = match at {
+                    Some([b, rest @ ..]) => (*b, Some(rest)),
+                    _ => match self.evaluate_inner(cond) {
+                        ExecResult::Value(RelType::Bool(true)) => (0, None),
+                        ExecResult::Value(RelType::Bool(false)) => (1, None),
+                        ExecResult::Value(_) => return Co::Done(ExecResult::Fault { msg: "If condition must be boolean".into(), node: "Node::If".into() }),
+                        err => return Co::Done(err),
+                    },
+                };
+                match (branch, else_b) {
+                    (0, _) => self.co_branch(0, then_b, rest),
+                    (_, Some(eb)) => self.co_branch(1, eb, rest),
+                    (_, None) => Co::Done(ExecResult::Value(RelType::Void)),
+                }
+            }
+            Node::While(cond, body) => {
+                if let Some([_, rest @ ..]) = at {
+                    match self.co_branch(0, body, Some(rest)) {
+                        Co::Done(ExecResult::Value(v)) => self.release_handles(&v),
+                        other => return other,
+                    }
+                }
+                loop {
+                    match self.evaluate_inner(cond) {
+                        ExecResult::Value(RelType::Bool(true)) => {}
+                        ExecResult::Value(_) => return Co::Done(ExecResult::Value(RelType::Void)),
+                        err => return Co::Done(err),
+                    }
+                    self.poll_actions();
+                    match self.co_branch(0, body, None) {
+                        Co::Done(ExecResult::Value(v)) => self.release_handles(&v),
+                        other => return other,
+                    }
+                }
+            }
+            other => Co::Done(self.evaluate_inner(other)),
+        }
+    }
+
+    fn co_branch(&mut self, index: usize, node: &Node, at: Option<&[usize]>) -> Co {
+        match self.co_exec(node, at) {
+            Co::Suspended(mut path) => {
+                path.push(index);
+                Co::Suspended(path)
+            }
+            done => done,
+        }
+    }
+
+    /// Suspends until the awaited coroutine has finished, then yields its
+    /// result (assigned to `target` if given).
+    fn co_await(&mut self, args: &[Node], target: Option<&str>) -> Co {
+        let mut values = Vec::with_capacity(args.len());
+        for a in args {
+            match self.evaluate_inner(a) {
+                ExecResult::Value(v) => values.push(v),
+                err => return Co::Done(err),
+            }
+        }
+        let outcome = check_arity(AWAIT, &values, 1)
+            .and_then(|_| arg::<i64>(AWAIT, &values, 0))
+            .and_then(|id| self.coroutine_outcome(AWAIT, id).map(|o| (id, o)));
+        let res = match outcome {
+            Ok((id, None)) if self.coroutines.running == Some(id) => fault(AWAIT, "a coroutine cannot await itself".into()),
+            Ok((_, None)) => return Co::Suspended(Vec::new()),
+            Ok((id, Some(outcome))) => self.await_result(id, outcome).unwrap_or_else(|fault| fault),
+            Err(fault) => fault,
+        };
+        if let (Some(target), ExecResult::Value(v)) = (target, &res) {
+            self.set_var(target.to_string(), v.clone());
+        }
+        Co::Done(res)
+    }
+}
@@ -0,0 +1,541 @@
+//! Debug Adapter Protocol server (used by the `knoten_dap` binary).
+//!
+//! Speaks DAP over a byte stream (stdio in practice) and drives the engine's
+//! `Debugger` hook. A single thread (id 1) is exposed; its stack frames are the
+//! engine's `call_stack`, innermost first.
+
+use crate::ast::Node;
+use crate::debugger::{Breakpoints, DebugCommand, DebugFrontend, Debugger, StopEvent, StopReason};
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, VecDeque};
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+const THREAD_ID: i64 = 1;
+/// Variable references at or above this value name a structured value.
+const HANDLE_BASE: i64 = 1 << 20;
+const GLOBALS_REF: i64 = 1 << 30;
+
+/// Reads one `Content-Length` framed message. Returns `None` on EOF or a malformed frame.
+pub fn read_message(input: &mut dyn BufRead) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("Content-Length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0u8; length?];
+    input.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+/// Writes one `Content-Length` framed message.
+pub fn write_message(output: &mut dyn Write, msg: &Value) -> std::io::Result<()> {
+    let body = msg.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Connection state shared between the server loop and the frontend that runs
+/// inside the engine while a script executes.
+struct Client {
+    output: Box<dyn Write + Send>,
+    seq: i64,
+    requests: Receiver<Value>,
+    /// Requests received while running that must wait for the next stop.
+    pending: VecDeque<Value>,
+    stdout: Option<Receiver<String>>,
+    breakpoints: Breakpoints,
+    program: String,
+    /// Lines holding a statement; breakpoints are moved onto the next one.
+    statement_lines: BTreeSet<usize>,
+    disconnected: bool,
+}
+
+impl Client {
+    fn send(&mut self, mut msg: Value) {
+        self.seq += 1;
+        msg["seq"] = json!(self.seq);
+        let _ = write_message(&mut self.output, &msg);
+    }
+
+    fn respond(&mut self, req: &Value, body: Value) {
+        self.send(json!({
+            "type": "response", "request_seq": req["seq"], "success": true,
+            "command": req["command"], "body": body,
+        }));
+    }
+
+    fn fail(&mut self, req: &Value, message: &str) {
+        self.send(json!({
+            "type": "response", "request_seq": req["seq"], "success": false,
+            "command": req["command"], "message": message,
+        }));
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    /// Forwards captured `Print` output as `output` events.
+    fn flush_stdout(&mut self) {
+        let lines: Vec<String> = match &self.stdout {
+            Some(rx) => rx.try_iter().collect(),
+            None => return,
+        };
+        for line in lines {
+            self.event("output", json!({ "category": "stdout", "output": format!("{}\n", line) }));
+        }
+    }
+
+    fn next_request(&mut self) -> Option<Value> {
+        match self.pending.pop_front() {
+            Some(req) => Some(req),
+            None => self.requests.recv().ok(),
+        }
+    }
+
+    fn source(&self) -> Value {
+        let name = std::path::Path::new(&self.program).file_name().and_then(|n| n.to_str()).unwrap_or("");
+        json!({ "name": name, "path": self.program })
+    }
+
+    /// Requests that are answered the same way whether or not the script is running.
+    /// Returns false if `req` is not one of them.
+    fn handle_common(&mut self, req: &Value, breakpoints: &mut Breakpoints) -> bool {
+        let args = &req["arguments"];
+        match req["command"].as_str().unwrap_or("") {
+            "threads" => {
+                self.respond(req, json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }));
+            }
+            "setBreakpoints" => {
+                let requested: Vec<i64> = match args["breakpoints"].as_array() {
+                    Some(bps) => bps.iter().filter_map(|b| b["line"].as_i64()).collect(),
+                    None => args["lines"].as_array().map(|l| l.iter().filter_map(Value::as_i64).collect()).unwrap_or_default(),
+                };
+                let same_file = self.program.is_empty() || args["source"]["path"].as_str().is_none_or(|p| same_path(p, &self.program));
+                breakpoints.lines.clear();
+                let mut result = Vec::new();
+                for line in requested {
+                    let resolved = if !same_file {
+                        None
+                    } else if self.statement_lines.is_empty() {
+                        Some(line.max(0) as usize)
+                    } else {
+                        self.statement_lines.range(line.max(0) as usize..).next().copied()
+                    };
+                    match resolved {
+                        Some(l) => {
+                            breakpoints.lines.insert(l);
+                            result.push(json!({ "verified": true, "line": l }));
+                        }
+                        None => result.push(json!({ "verified": false, "line": line, "message": "No statement at or after this line" })),
+                    }
+                }
+                self.respond(req, json!({ "breakpoints": result }));
+            }
+            "setFunctionBreakpoints" => {
+                breakpoints.functions = args["breakpoints"]
+                    .as_array()
+                    .map(|b| b.iter().filter_map(|f| f["name"].as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+                let result: Vec<Value> = breakpoints.functions.iter().map(|_| json!({ "verified": true })).collect();
+                self.respond(req, json!({ "breakpoints": result }));
+            }
+            "setExceptionBreakpoints" => self.respond(req, json!({ "breakpoints": [] })),
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    match (dunce::canonicalize(a), dunce::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Collects the lines of every `Node::Located` statement in `ast`.
+fn statement_lines(ast: &Node) -> BTreeSet<usize> {
+    fn walk(v: &Value, lines: &mut BTreeSet<usize>) {
+        match v {
+            Value::Object(map) => {
+                if let Some(line) = map.get("Located").and_then(|l| l["line"].as_u64()) {
+                    lines.insert(line as usize);
+                }
+                map.values().for_each(|v| walk(v, lines));
+            }
+            Value::Array(items) => items.iter().for_each(|v| walk(v, lines)),
+            _ => {}
+        }
+    }
+    let mut lines = BTreeSet::new();
+    if let Ok(v) = serde_json::to_value(ast) {
+        walk(&v, &mut lines);
+    }
+    lines
+}
+
+fn load_program(path: &str) -> Result<Node, String> {
+    let src = std::fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path, e))?;
+    crate::parser::parse_script(path, &src, true)
+}
+
+fn type_name(val: &RelType) -> &'static str {
+    match val {
+        RelType::Int(_) => "Int",
+        RelType::Float(_) => "Float",
+        RelType::Bool(_) => "Bool",
+        RelType::Str(_) => "String",
+        RelType::Array(_) => "Array",
+        RelType::Object(_) => "Object",
+        RelType::Handle(_) => "Handle",
+        RelType::FnDef(..) => "Function",
+        RelType::Call(..) => "Call",
+        RelType::Void => "Void",
+    }
+}
+
+/// The frontend installed on the engine while a script runs under the adapter.
+struct DapFrontend {
+    client: Arc<Mutex<Client>>,
+    /// Structured values shown in the current stop, addressed by `HANDLE_BASE + index`.
+    handles: Vec<RelType>,
+    col: usize,
+}
+
+impl DapFrontend {
+    fn variable(&mut self, name: String, val: RelType) -> Value {
+        let value = match &val {
+            RelType::Str(s) => format!("{:?}", s),
+            RelType::Array(a) => format!("Array({})", a.len()),
+            RelType::Object(m) => format!("Object({})", m.len()),
+            other => other.to_string(),
+        };
+        let reference = self.reference(val.clone());
+        json!({ "name": name, "value": value, "type": type_name(&val), "variablesReference": reference })
+    }
+
+    fn reference(&mut self, val: RelType) -> i64 {
+        if matches!(val, RelType::Array(_) | RelType::Object(_)) {
+            self.handles.push(val);
+            HANDLE_BASE + self.handles.len() as i64 - 1
+        } else {
+            0
+        }
+    }
+
+    fn variables(&mut self, engine: &ExecutionEngine, reference: i64) -> Vec<Value> {
+        let entries: Vec<(String, RelType)> = if reference == GLOBALS_REF {
+            engine.global_variables()
+        } else if reference >= HANDLE_BASE {
+            match self.handles.get((reference - HANDLE_BASE) as usize) {
+                Some(RelType::Array(items)) => items.iter().enumerate().map(|(i, v)| (i.to_string(), v.clone())).collect(),
+                Some(RelType::Object(map)) => {
+                    let mut fields: Vec<(String, RelType)> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                    fields.sort_by(|a, b| a.0.cmp(&b.0));
+                    fields
+                }
+                _ => Vec::new(),
+            }
+        } else {
+            // Frame references are frame ids (call stack index + 1); locals only.
+            let mut locals: Vec<(String, RelType)> = engine
+                .call_stack
+                .get((reference - 1).max(0) as usize)
+                .map(|f| f.locals.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_default();
+            locals.sort_by(|a, b| a.0.cmp(&b.0));
+            locals
+        };
+        entries.into_iter().map(|(name, val)| self.variable(name, val)).collect()
+    }
+
+    /// Answers a request while stopped. Returns the command that resumes execution.
+    fn handle_stopped(&mut self, engine: &mut ExecutionEngine, breakpoints: &mut Breakpoints, req: &Value) -> Option<DebugCommand> {
+        let client = self.client.clone();
+        let mut client = client.lock().unwrap();
+        if client.handle_common(req, breakpoints) {
+            return None;
+        }
+        let args = &req["arguments"];
+        let command = match req["command"].as_str().unwrap_or("") {
+            "stackTrace" => {
+                let source = client.source();
+                let depth = engine.call_stack.len();
+                let frames: Vec<Value> = engine
+                    .call_stack
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(i, f)| {
+                        let column = if i + 1 == depth { self.col } else { 1 };
+                        json!({ "id": i + 1, "name": f.function, "line": f.line, "column": column, "source": source })
+                    })
+                    .collect();
+                client.respond(req, json!({ "stackFrames": frames, "totalFrames": depth }));
+                None
+            }
+            "scopes" => {
+                let frame_id = args["frameId"].as_i64().unwrap_or(engine.call_stack.len() as i64);
+                client.respond(
+                    req,
+                    json!({ "scopes": [
+                        { "name": "Locals", "presentationHint": "locals", "variablesReference": frame_id, "expensive": false },
+                        { "name": "Globals", "variablesReference": GLOBALS_REF, "expensive": false },
+                    ] }),
+                );
+                None
+            }
+            "variables" => {
+                let vars = self.variables(engine, args["variablesReference"].as_i64().unwrap_or(0));
+                client.respond(req, json!({ "variables": vars }));
+                None
+            }
+            "evaluate" => {
+                let expr = args["expression"].as_str().unwrap_or("");
+                let frame = args["frameId"].as_i64().map(|id| (id - 1).max(0) as usize).unwrap_or(engine.call_stack.len() - 1);
+                let result = crate::parser::parse_checked(expr, false).map(|node| engine.evaluate_in_frame(&node, frame));
+                client.flush_stdout();
+                match result {
+                    Ok(ExecResult::Value(v)) | Ok(ExecResult::ReturnBlockInfo(v)) => {
+                        let var = self.variable(String::new(), v);
+                        client.respond(req, json!({ "result": var["value"], "type": var["type"], "variablesReference": var["variablesReference"] }));
+                    }
+                    Ok(fault) => client.fail(req, &fault.to_string()),
+                    Err(e) => client.fail(req, &format!("Parse error: {}", e)),
+                }
+                None
+            }
+            "continue" => {
+                client.respond(req, json!({ "allThreadsContinued": true }));
+                Some(DebugCommand::Continue)
+            }
+            "next" => Some(DebugCommand::StepOver),
+            "stepIn" => Some(DebugCommand::StepIn),
+            "stepOut" => Some(DebugCommand::StepOut),
+            "pause" => {
+                client.respond(req, json!({}));
+                None
+            }
+            "disconnect" | "terminate" => {
+                client.respond(req, json!({}));
+                client.disconnected = req["command"] == "disconnect";
+                Some(DebugCommand::Terminate)
+            }
+            other => {
+                client.fail(req, &format!("Unsupported request '{}'", other));
+                None
+            }
+        };
+        if matches!(command, Some(DebugCommand::StepOver | DebugCommand::StepIn | DebugCommand::StepOut)) {
+            client.respond(req, json!({}));
+        }
+        command
+    }
+}
+
+impl DebugFrontend for DapFrontend {
+    fn paused(&mut self, engine: &mut ExecutionEngine, breakpoints: &mut Breakpoints, stop: &StopEvent) -> DebugCommand {
+        self.handles.clear();
+        self.col = stop.col;
+        let (reason, text) = match &stop.reason {
+            StopReason::Entry => ("entry", None),
+            StopReason::Breakpoint => ("breakpoint", None),
+            StopReason::FunctionBreakpoint(name) => ("function breakpoint", Some(name.clone())),
+            StopReason::Step => ("step", None),
+            StopReason::Pause => ("pause", None),
+        };
+        {
+            let mut client = self.client.lock().unwrap();
+            client.flush_stdout();
+            client.event("stopped", json!({ "reason": reason, "text": text, "threadId": THREAD_ID, "allThreadsStopped": true }));
+        }
+        loop {
+            let req = self.client.lock().unwrap().next_request();
+            let Some(req) = req else {
+                // Client went away.
+                self.client.lock().unwrap().disconnected = true;
+                return DebugCommand::Terminate;
+            };
+            if let Some(command) = self.handle_stopped(engine, breakpoints, &req) {
+                return command;
+            }
+        }
+    }
+
+    fn poll(&mut self, breakpoints: &mut Breakpoints) -> Option<DebugCommand> {
+        let mut client = self.client.lock().unwrap();
+        client.flush_stdout();
+        let incoming: Vec<Value> = client.requests.try_iter().collect();
+        client.pending.extend(incoming);
+        // Requests are answered in order: stop at the first one that needs a stopped thread.
+        while let Some(req) = client.pending.front().cloned() {
+            match req["command"].as_str().unwrap_or("") {
+                "pause" => {
+                    client.pending.pop_front();
+                    client.respond(&req, json!({}));
+                    return Some(DebugCommand::Pause);
+                }
+                "disconnect" | "terminate" => {
+                    client.pending.pop_front();
+                    client.respond(&req, json!({}));
+                    client.disconnected = req["command"] == "disconnect";
+                    return Some(DebugCommand::Terminate);
+                }
+                _ => {
+                    if !client.handle_common(&req, breakpoints) {
+                        break;
+                    }
+                    client.pending.pop_front();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Runs a debug session until the client disconnects or closes the stream.
+///
+/// Supported launch arguments: `program` (a `.knoten` file or JSON AST),
+/// `stopOnEntry`, `noDebug`, and `allowRead` / `allowWrite` / `allowNetwork`
+/// (all off by default, as with `run_knc`).
+pub fn serve(mut input: Box<dyn BufRead + Send>, output: Box<dyn Write + Send>) {
+    let (tx, rx): (Sender<Value>, Receiver<Value>) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        while let Some(msg) = read_message(&mut input) {
+            if tx.send(msg).is_err() {
+                break;
+            }
+        }
+    });
+
+    let client = Arc::new(Mutex::new(Client {
+        output,
+        seq: 0,
+        requests: rx,
+        pending: VecDeque::new(),
+        stdout: None,
+        breakpoints: Breakpoints::default(),
+        program: String::new(),
+        statement_lines: BTreeSet::new(),
+        disconnected: false,
+    }));
+
+    // ── Configuration phase ──────────────────────────────────────────
+    let mut launch: Option<(Node, Value)> = None;
+    let mut configured = false;
+    while launch.is_none() || !configured {
+        let mut c = client.lock().unwrap();
+        let Some(req) = c.next_request() else { return };
+        let mut breakpoints = std::mem::take(&mut c.breakpoints);
+        let handled = c.handle_common(&req, &mut breakpoints);
+        c.breakpoints = breakpoints;
+        if handled {
+            continue;
+        }
+        match req["command"].as_str().unwrap_or("") {
+            "initialize" => {
+                c.respond(
+                    &req,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsFunctionBreakpoints": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsTerminateRequest": true,
+                    }),
+                );
+                c.event("initialized", json!({}));
+            }
+            "launch" => {
+                let args = req["arguments"].clone();
+                let program = args["program"].as_str().unwrap_or("").to_string();
+                match load_program(&program) {
+                    Ok(ast) => {
+                        c.statement_lines = statement_lines(&ast);
+                        c.program = program;
+                        // Re-resolve breakpoints set before the program was known.
+                        let lines: Vec<usize> = c.breakpoints.lines.iter().copied().collect();
+                        let resolved: BTreeSet<usize> = lines
+                            .into_iter()
+                            .filter_map(|l| c.statement_lines.range(l..).next().copied())
+                            .collect();
+                        c.breakpoints.lines = resolved;
+                        c.respond(&req, json!({}));
+                        launch = Some((ast, args));
+                    }
+                    Err(e) => c.fail(&req, &e),
+                }
+            }
+            "configurationDone" => {
+                c.respond(&req, json!({}));
+                configured = true;
+            }
+            "disconnect" | "terminate" => {
+                c.respond(&req, json!({}));
+                return;
+            }
+            other => c.fail(&req, &format!("Request '{}' is not valid before launch", other)),
+        }
+    }
+    let (ast, args) = launch.expect("configuration loop exits only after launch");
+
+    // ── Execution ────────────────────────────────────────────────────
+    let mut engine = ExecutionEngine::new();
+    engine.permissions.allow_fs_read = args["allowRead"].as_bool().unwrap_or(false);
+    engine.permissions.allow_fs_write = args["allowWrite"].as_bool().unwrap_or(false);
+    engine.permissions.allow_network = args["allowNetwork"].as_bool().unwrap_or(false);
+    let (stdout_tx, stdout_rx) = std::sync::mpsc::channel();
+    engine.stdout_tx = Some(stdout_tx);
+    client.lock().unwrap().stdout = Some(stdout_rx);
+
+    if !args["noDebug"].as_bool().unwrap_or(false) {
+        let frontend = DapFrontend { client: client.clone(), handles: Vec::new(), col: 0 };
+        let mut debugger = Debugger::new(Box::new(frontend), args["stopOnEntry"].as_bool().unwrap_or(false));
+        debugger.breakpoints = std::mem::take(&mut client.lock().unwrap().breakpoints);
+        engine.debugger = Some(Box::new(debugger));
+    }
+    let result = engine.execute(&ast);
+
+    let mut c = client.lock().unwrap();
+    c.flush_stdout();
+    let exit_code = match &result {
+        ExecResult::Fault { .. } => {
+            if !c.disconnected {
+                c.event("output", json!({ "category": "stderr", "output": format!("{}\n", result) }));
+            }
+            1
+        }
+        _ => 0,
+    };
+    c.event("exited", json!({ "exitCode": exit_code }));
+    c.event("terminated", json!({}));
+    if c.disconnected {
+        return;
+    }
+
+    // ── Wait for the client to hang up ───────────────────────────────
+    while let Some(req) = c.next_request() {
+        match req["command"].as_str().unwrap_or("") {
+            "disconnect" => {
+                c.respond(&req, json!({}));
+                return;
+            }
+            "threads" => c.respond(&req, json!({ "threads": [] })),
+            _ => c.fail(&req, "The script has finished"),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+
+/// Line and function breakpoints. Lines refer to `Node::Located` positions
+/// produced by `Parser::with_locations`.
+#[derive(Default, Clone, Debug)]
+pub struct Breakpoints {
+    pub lines: BTreeSet<usize>,
+    pub functions: BTreeSet<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopReason {
+    Entry,
+    Breakpoint,
+    FunctionBreakpoint(String),
+    Step,
+    Pause,
+}
+
+/// Where and why execution paused. `depth` is the call stack height.
+#[derive(Clone, Debug)]
+pub struct StopEvent {
+    pub reason: StopReason,
+    pub line: usize,
+    pub col: usize,
+    pub depth: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DebugCommand {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+    /// Stop at the next statement, reported as `StopReason::Pause`.
+    Pause,
+    Terminate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StepMode {
+    Run,
+    In,
+    Over(usize),
+    Out(usize),
+}
+
+/// The user-facing side of a debug session. `paused` is called with the engine
+/// halted at a statement and blocks until the user resumes execution. The
+/// engine can be inspected and evaluated against freely while paused.
+pub trait DebugFrontend: Send {
+    fn paused(&mut self, engine: &mut ExecutionEngine, breakpoints: &mut Breakpoints, stop: &StopEvent) -> DebugCommand;
+
+    /// Called before every statement while the script runs, so a frontend can
+    /// update breakpoints or interrupt execution. Only `Pause` and `Terminate`
+    /// are acted upon.
+    fn poll(&mut self, _breakpoints: &mut Breakpoints) -> Option<DebugCommand> {
+        None
+    }
+}
+
+pub struct Debugger {
+    pub breakpoints: Breakpoints,
+    step: StepMode,
+    pending_reason: Option<StopReason>,
+    terminated: bool,
+    frontend: Box<dyn DebugFrontend>,
+}
+
+impl Debugger {
+    pub fn new(frontend: Box<dyn DebugFrontend>, stop_on_entry: bool) -> Self {
+        Self {
+            breakpoints: Breakpoints::default(),
+            step: if stop_on_entry { StepMode::In } else { StepMode::Run },
+            pending_reason: if stop_on_entry { Some(StopReason::Entry) } else { None },
+            terminated: false,
+            frontend,
+        }
+    }
+
+    fn on_statement(&mut self, engine: &mut ExecutionEngine, line: usize, col: usize) -> Option<ExecResult> {
+        if self.terminated {
+            return Some(terminated_fault());
+        }
+        match self.frontend.poll(&mut self.breakpoints) {
+            Some(DebugCommand::Pause) => {
+                self.step = StepMode::In;
+                self.pending_reason = Some(StopReason::Pause);
+            }
+            Some(DebugCommand::Terminate) => {
+                self.terminated = true;
+                return Some(terminated_fault());
+            }
+            _ => {}
+        }
+        let depth = engine.call_stack.len();
+        let stepped = match self.step {
+            StepMode::Run => false,
+            StepMode::In => true,
+            StepMode::Over(d) => depth <= d,
+            StepMode::Out(d) => depth < d,
+        };
+        let reason = if stepped {
+            self.pending_reason.take().unwrap_or(StopReason::Step)
+        } else if self.breakpoints.lines.contains(&line) {
+            StopReason::Breakpoint
+        } else {
+            return None;
+        };
+
+        let stop = StopEvent { reason, line, col, depth };
+        let command = self.frontend.paused(engine, &mut self.breakpoints, &stop);
+        self.step = match command {
+            DebugCommand::Continue => StepMode::Run,
+            DebugCommand::StepIn | DebugCommand::Pause => StepMode::In,
+            DebugCommand::StepOver => StepMode::Over(depth),
+            DebugCommand::StepOut => StepMode::Out(depth),
+            DebugCommand::Terminate => {
+                self.terminated = true;
+                return Some(terminated_fault());
+            }
+        };
+        None
+    }
+
+    fn on_function_entry(&mut self, name: &str) {
+        // Pause on the first statement of the body so the stop has a source line.
+        if self.breakpoints.functions.contains(name) {
+            self.step = StepMode::In;
+            self.pending_reason = Some(StopReason::FunctionBreakpoint(name.to_string()));
+        }
+    }
+}
+
+fn terminated_fault() -> ExecResult {
+    ExecResult::Fault { msg: "Debugger: session terminated".into(), node: "Debugger".into() }
+}
+
+impl ExecutionEngine {
+    pub(crate) fn debug_statement(&mut self, line: usize, col: usize) -> Option<ExecResult> {
+        // Detach the debugger while the frontend runs, so expressions it evaluates
+        // against the paused engine cannot re-enter the hook.
+        let mut debugger = self.debugger.take()?;
+        let res = debugger.on_statement(self, line, col);
+        self.debugger = Some(debugger);
+        res
+    }
+
+    pub(crate) fn debug_function_entry(&mut self, name: &str) {
+        if let Some(debugger) = self.debugger.as_mut() {
+            debugger.on_function_entry(name);
+        }
+    }
+
+    /// Variables visible in `frame` (0 = outermost), sorted by name.
+    /// Frame 0 also shows the globals held in `memory`.
+    pub fn frame_variables(&self, frame: usize) -> Vec<(String, RelType)> {
+        let mut vars: Vec<(String, RelType)> = Vec::new();
+        if let Some(f) = self.call_stack.get(frame) {
+            vars.extend(f.locals.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        if frame == 0 {
+            vars.extend(self.memory.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        vars
+    }
+
+    /// Global variables held in `memory`, sorted by name.
+    pub fn global_variables(&self) -> Vec<(String, RelType)> {
+        let mut vars: Vec<(String, RelType)> = self.memory.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        vars
+    }
+
+    /// Evaluates `node` as if `frame` were the innermost call frame. Frames above
+    /// it are set aside for the duration of the evaluation and restored afterwards.
+    pub fn evaluate_in_frame(&mut self, node: &Node, frame: usize) -> ExecResult {
+        if frame >= self.call_stack.len() {
+            return ExecResult::Fault { msg: format!("Frame {} does not exist", frame), node: "Debugger".into() };
+        }
+        let upper = self.call_stack.split_off(frame + 1);
+        let res = self.evaluate(node);
+        self.call_stack.extend(upper);
+        res
+    }
+}
+
+/// Line-oriented command prompt used by `run_knc --debug`.
+pub struct ConsoleFrontend {
+    input: Box<dyn BufRead + Send>,
+    output: Box<dyn Write + Send>,
+    source: Vec<String>,
+}
+
+impl ConsoleFrontend {
+    pub fn new(input: Box<dyn BufRead + Send>, output: Box<dyn Write + Send>, source: &str) -> Self {
+        Self { input, output, source: source.lines().map(String::from).collect() }
+    }
+
+    pub fn stdio(source: &str) -> Self {
+        Self::new(Box::new(std::io::BufReader::new(std::io::stdin())), Box::new(std::io::stdout()), source)
+    }
+
+    fn print_help(&mut self) {
+        let _ = writeln!(
+            self.output,
+            "  c              continue\n  s              step into\n  n              step over\n  o              step out\n  \
+             b <line|fn>    set breakpoint\n  d <line|fn>    delete breakpoint\n  bl             list breakpoints\n  \
+             bt             backtrace\n  v [frame]      show variables\n  p <expr>       evaluate expression\n  q              terminate"
+        );
+    }
+
+    fn print_stop(&mut self, stop: &StopEvent) {
+        let reason = match &stop.reason {
+            StopReason::Entry => "entry".to_string(),
+            StopReason::Breakpoint => "breakpoint".to_string(),
+            StopReason::FunctionBreakpoint(name) => format!("function breakpoint '{}'", name),
+            StopReason::Step => "step".to_string(),
+            StopReason::Pause => "pause".to_string(),
+        };
+        let text = self.source.get(stop.line.wrapping_sub(1)).map(|s| s.trim()).unwrap_or("");
+        let _ = writeln!(self.output, "[debug] paused ({}) at line {}: {}", reason, stop.line, text);
+    }
+}
+
+impl DebugFrontend for ConsoleFrontend {
+    fn paused(&mut self, engine: &mut ExecutionEngine, breakpoints: &mut Breakpoints, stop: &StopEvent) -> DebugCommand {
+        self.print_stop(stop);
+        loop {
+            let _ = write!(self.output, "(knc-dbg) ");
+            let _ = self.output.flush();
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => return DebugCommand::Terminate,
+                Ok(_) => {}
+            }
+            let line = line.trim();
+            let (cmd, arg) = match line.split_once(' ') {
+                Some((c, a)) => (c, a.trim()),
+                None => (line, ""),
+            };
+            match cmd {
+                "c" | "continue" => return DebugCommand::Continue,
+                "s" | "step" => return DebugCommand::StepIn,
+                "n" | "next" => return DebugCommand::StepOver,
+                "o" | "out" => return DebugCommand::StepOut,
+                "q" | "quit" => return DebugCommand::Terminate,
+                "b" | "break" if !arg.is_empty() => {
+                    match arg.parse::<usize>() {
+                        Ok(l) => { breakpoints.lines.insert(l); }
+                        Err(_) => { breakpoints.functions.insert(arg.to_string()); }
+                    }
+                    let _ = writeln!(self.output, "breakpoint set: {}", arg);
+                }
+                "d" | "delete" if !arg.is_empty() => {
+                    let removed = match arg.parse::<usize>() {
+                        Ok(l) => breakpoints.lines.remove(&l),
+                        Err(_) => breakpoints.functions.remove(arg),
+                    };
+                    let _ = writeln!(self.output, "{}", if removed { "breakpoint deleted" } else { "no such breakpoint" });
+                }
+                "bl" => {
+                    for l in &breakpoints.lines { let _ = writeln!(self.output, "  line {}", l); }
+                    for f in &breakpoints.functions { let _ = writeln!(self.output, "  fn {}", f); }
+                }
+                "bt" | "backtrace" => {
+                    for (i, frame) in engine.call_stack.iter().enumerate().rev() {
+                        let _ = writeln!(self.output, "  #{} {} (line {})", i, frame.function, frame.line);
+                    }
+                }
+                "v" | "vars" => {
+                    let frame = arg.parse::<usize>().unwrap_or(engine.call_stack.len().saturating_sub(1));
+                    for (name, val) in engine.frame_variables(frame) {
+                        let _ = writeln!(self.output, "  {} = {}", name, val);
+                    }
+                }
+                "p" | "print" if !arg.is_empty() => {
+                    let out = match crate::parser::parse_checked(arg, false) {
+                        Ok(node) => engine.evaluate(&node).to_string(),
+                        Err(e) => format!("Parse error: {}", e),
+                    };
+                    let _ = writeln!(self.output, "{}", out);
+                }
+                "" => {}
+                _ => self.print_help(),
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use std::time::Duration;
+
+/// Seeded randomness and a host-driven virtual clock.
+///
+/// While an engine is deterministic, `Node::Time` / `Node::GlobalTime`,
+/// `Math.Random`, `Math.Perlin2D`, `registry_now`, `registry_elapsed_ms` and
+/// the clock-reading `DateTime` natives read from here instead of the OS, so two runs with the same seed and the
+/// same sequence of `advance_time` calls produce identical results.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Determinism {
+    pub seed: u64,
+    rng_state: u64,
+    /// Virtual time since startup. Only `ExecutionEngine::advance_time` moves it.
+    pub clock: Duration,
+    /// Wall-clock time, in ms since the Unix epoch, that `DateTime.Now` reports
+    /// while the virtual clock is at zero.
+    #[serde(default)]
+    pub epoch_ms: i64,
+}
+
+impl Determinism {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, rng_state: seed, clock: Duration::ZERO, epoch_ms: 0 }
+    }
+
+    /// SplitMix64. Implemented here rather than taken from `rand` so sequences
+    /// stay identical across dependency upgrades.
+    pub fn next_u64(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Seed handed to `noise::Perlin`.
+    pub fn perlin_seed(&self) -> u32 {
+        (self.seed ^ (self.seed >> 32)) as u32
+    }
+}
+
+impl ExecutionEngine {
+    /// Switches the engine to deterministic mode with a fresh RNG and the
+    /// virtual clock at zero.
+    pub fn enable_deterministic(&mut self, seed: u64) {
+        self.determinism = Some(Determinism::new(seed));
+    }
+
+    /// Moves the virtual clock forward. Has no effect outside deterministic mode.
+    pub fn advance_time(&mut self, dt: Duration) {
+        if let Some(det) = self.determinism.as_mut() {
+            det.clock += dt;
+        }
+    }
+
+    /// Time since startup: virtual in deterministic mode, wall-clock otherwise.
+    pub fn now(&self) -> Duration {
+        match &self.determinism {
+            Some(det) => det.clock,
+            None => self.startup_time.elapsed(),
+        }
+    }
+
+    /// Answers time- and randomness-dependent natives from the deterministic
+    /// state. Returns None outside deterministic mode or for any other native.
+    pub(crate) fn deterministic_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let det = self.determinism.as_mut()?;
+        let now_ms = det.clock.as_millis() as i64;
+        match name {
+            "Math.Random" => Some(ExecResult::Value(RelType::Float(det.next_f64()))),
+            "Math.Perlin2D" => Some(crate::natives::math::perlin2d(args, det.perlin_seed())),
+            name if crate::natives::datetime::is_clock_native(name) => {
+                let clock = crate::natives::datetime::Clock { now_ms: det.epoch_ms.saturating_add(now_ms), local_is_utc: true };
+                crate::natives::datetime::datetime_native(name, args, clock)
+            }
+            "registry_now" => {
+                let id = crate::natives::registry::registry_now_virtual(now_ms);
+                Some(ExecResult::Value(RelType::Handle(crate::executor::NativeHandle(id))))
+            }
+            "registry_elapsed_ms" => match args {
+                [RelType::Handle(crate::executor::NativeHandle(id))] => {
+                    Some(ExecResult::Value(RelType::Int(crate::natives::registry::registry_elapsed_ms_virtual(*id, now_ms))))
+                }
+                _ => Some(ExecResult::Fault {
+                    msg: "[FFI] registry_elapsed_ms expects 1 Handle arg".to_string(),
+                    node: "Native::Bridge::registry_elapsed_ms".into(),
+                }),
+            },
+            _ => None,
+        }
+    }
+}
@@ -16,6 +16,9 @@ pub fn emit_dsl(node: &Node, indent: usize) -> String {
         Node::BoolLiteral(v) => v.to_string(),
         Node::StringLiteral(v) => format!("\"{}\"", v),
 
+        // Source locations carry no syntax of their own
+        Node::Located { stmt, .. } => emit_dsl(stmt, indent),
+
         // Memory
         Node::Identifier(name) => name.clone(),
         Node::Assign(name, val) => format!("{} = {}", name, emit_dsl(val, indent)),
@@ -80,6 +83,7 @@ pub fn emit_dsl(node: &Node, indent: usize) -> String {
             )
         }
         Node::Return(val) => format!("return {}", emit_dsl(val, indent)),
+        Node::Yield => "yield".to_string(),
 
         // Arrays & Objects
         Node::ArrayCreate(args) => {
@@ -171,10 +175,13 @@ fn extract_args(node: &Node) -> Vec<&Node> {
             v
         }
         Node::Concat(a, b) => vec![&**a, &**b],
+        Node::MapHasKey(a, b) | Node::MapRemove(a, b) => vec![&**a, &**b],
+        Node::MapKeys(a) | Node::MapValues(a) | Node::MapEntries(a) | Node::MapLen(a) => vec![&**a],
         Node::FileRead(a) => vec![&**a],
         Node::FileWrite(a, b) => vec![&**a, &**b],
         Node::FSRead(a) => vec![&**a],
         Node::FSWrite(a, b) => vec![&**a, &**b],
+        Node::Extract { source, path } => vec![&**source, &**path],
         Node::Fetch {
             method: _,
             url: _,
@@ -187,5 +194,5 @@ fn extract_args(node: &Node) -> Vec<&Node> {
 
 fn emit_node_name(node: &Node) -> String {
     let s = format!("{:?}", node);
-    s.split('(').next().unwrap_or(&s).to_string()
+    s.split(['(', ' ']).next().unwrap_or(&s).to_string()
 }
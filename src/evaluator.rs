@@ -1,4 +1,4 @@
-use crate::executor::{ExecutionEngine, RelType, ExecResult, StackFrame};
+use crate::executor::{ExecutionEngine, ObjectMap, RelType, ExecResult, StackFrame};
 use crate::ast::Node;
 use std::collections::HashMap;
 
@@ -14,6 +14,43 @@ impl ExecutionEngine {
     }
 
     pub fn evaluate_inner(&mut self, node: &Node) -> ExecResult {
+        let Some(profiler) = self.profiler.as_mut() else { return self.evaluate_node(node) };
+        profiler.enter_node(node.kind());
+        let res = self.evaluate_node(node);
+        if let Some(profiler) = self.profiler.as_mut() { profiler.exit_node(); }
+        res
+    }
+
+    /// Calls the script function `name` with already evaluated arguments.
+    /// Used by hosts (see `Action::CallFunction`) to run script code from Rust.
+    pub fn call_function(&mut self, name: &str, args: Vec<RelType>) -> ExecResult {
+        match self.get_var(name) {
+            Some(RelType::FnDef(_, params, body)) => {
+                if params.len() != args.len() { return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() } }
+                self.invoke_function(name, &params, &body, args)
+            }
+            Some(_) => ExecResult::Fault { msg: format!("'{}' is not a function", name), node: "Node::Call".into() },
+            None => ExecResult::Fault { msg: format!("Function '{}' not found", name), node: "Node::Call".into() },
+        }
+    }
+
+    pub(crate) fn invoke_function(&mut self, name: &str, params: &[String], body: &Node, args: Vec<RelType>) -> ExecResult {
+        let locals: HashMap<String, RelType> = params.iter().cloned().zip(args).collect();
+        self.call_stack.push(StackFrame { locals, function: name.to_string(), line: 0 });
+        if self.debugger.is_some() { self.debug_function_entry(name); }
+        if let Some(profiler) = self.profiler.as_mut() { profiler.enter_frame(name, false); }
+        let res = self.evaluate_inner(body);
+        if let Some(profiler) = self.profiler.as_mut() { profiler.exit_frame(); }
+        if let Some(frame) = self.call_stack.pop() {
+            for (_, val) in frame.locals { self.release_handles(&val); }
+        }
+        match res {
+            ExecResult::ReturnBlockInfo(v) => ExecResult::Value(v),
+            other => other,
+        }
+    }
+
+    fn evaluate_node(&mut self, node: &Node) -> ExecResult {
         match node {
             // Literals
             Node::IntLiteral(v) => ExecResult::Value(RelType::Int(*v)),
@@ -64,7 +101,7 @@ impl ExecutionEngine {
             Node::Eq(l, r) => self.do_compare(l, "==", r),
             Node::Lt(l, r) => self.do_compare(l, "<", r),
             Node::Gt(l, r) => self.do_compare(l, ">", r),
-            Node::Time | Node::GlobalTime => ExecResult::Value(RelType::Float(self.startup_time.elapsed().as_secs_f64())),
+            Node::Time | Node::GlobalTime => ExecResult::Value(RelType::Float(self.now().as_secs_f64())),
             Node::Mat4Mul(l, r) => {
                 let lv = match self.evaluate_inner(l) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Mat4Mul expects array".into(), node: "Node::Mat4Mul".into() } };
                 let rv = match self.evaluate_inner(r) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Mat4Mul expects array".into(), node: "Node::Mat4Mul".into() } };
@@ -131,7 +168,7 @@ impl ExecutionEngine {
             }
 
             // Data Structures: Maps & Objects
-            Node::MapCreate => ExecResult::Value(RelType::Object(HashMap::new())),
+            Node::MapCreate => ExecResult::Value(RelType::Object(ObjectMap::new())),
             Node::MapGet(map_expr, key_expr) => {
                 let m = match self.evaluate_inner(map_expr) { ExecResult::Value(RelType::Object(v)) => v, _ => return ExecResult::Fault { msg: "Target is not a map/object".into(), node: "Node::MapGet".into() } };
                 let k = match self.evaluate_inner(key_expr) { ExecResult::Value(RelType::Str(v)) => v, _ => return ExecResult::Fault { msg: "Key is not a string".into(), node: "Node::MapGet".into() } };
@@ -156,8 +193,31 @@ impl ExecutionEngine {
                 let k = match self.evaluate_inner(key_expr) { ExecResult::Value(RelType::Str(v)) => v, _ => return ExecResult::Fault { msg: "Key is not a string".into(), node: "Node::MapHasKey".into() } };
                 ExecResult::Value(RelType::Bool(m.contains_key(&k)))
             }
+            Node::MapKeys(map_expr) | Node::MapValues(map_expr) | Node::MapEntries(map_expr) => {
+                let m = match self.evaluate_inner(map_expr) { ExecResult::Value(RelType::Object(v)) => v, _ => return ExecResult::Fault { msg: "Target is not a map/object".into(), node: format!("Node::{}", node.kind()) } };
+                // Insertion order, matching how the object displays.
+                let items = m.into_iter().map(|(k, v)| match node {
+                    Node::MapKeys(_) => RelType::Str(k),
+                    Node::MapValues(_) => v,
+                    _ => RelType::Array(vec![RelType::Str(k), v]),
+                });
+                ExecResult::Value(RelType::Array(items.collect()))
+            }
+            Node::MapLen(map_expr) => {
+                let m = match self.evaluate_inner(map_expr) { ExecResult::Value(RelType::Object(v)) => v, _ => return ExecResult::Fault { msg: "Target is not a map/object".into(), node: "Node::MapLen".into() } };
+                ExecResult::Value(RelType::Int(m.len() as i64))
+            }
+            Node::MapRemove(map_expr, key_expr) => {
+                if let Node::Identifier(name) = &**map_expr {
+                    let k = match self.evaluate_inner(key_expr) { ExecResult::Value(RelType::Str(v)) => v, _ => return ExecResult::Fault { msg: "Key is not a string".into(), node: "Node::MapRemove".into() } };
+                    match self.mutate_map_remove(name, &k) {
+                        Ok(old) => ExecResult::Value(old.unwrap_or(RelType::Void)),
+                        Err(err) => err,
+                    }
+                } else { ExecResult::Fault { msg: "MapRemove only supported on identifiers currently".into(), node: "Node::MapRemove".into() } }
+            }
             Node::ObjectLiteral(map) => {
-                let mut res = HashMap::with_capacity(map.len());
+                let mut res = ObjectMap::with_capacity(map.len());
                 for (k, v_node) in map {
                     match self.evaluate_inner(v_node) {
                         ExecResult::Value(v) => { res.insert(k.clone(), v); }
@@ -219,13 +279,19 @@ impl ExecutionEngine {
             }
             Node::BitShiftLeft(l, r) => {
                 match (self.evaluate_inner(l), self.evaluate_inner(r)) {
-                    (ExecResult::Value(RelType::Int(a)), ExecResult::Value(RelType::Int(b))) => ExecResult::Value(RelType::Int(a << b)),
+                    (ExecResult::Value(RelType::Int(a)), ExecResult::Value(RelType::Int(b))) => match crate::arith::int_op(self.overflow_mode, '<', a, b) {
+                        Some(v) => ExecResult::Value(RelType::Int(v)),
+                        None => crate::arith::overflow_fault('<', a, b),
+                    },
                     _ => ExecResult::Fault { msg: "Bitwise SHL expects integers".into(), node: "Node::BitShiftLeft".into() },
                 }
             }
             Node::BitShiftRight(l, r) => {
                 match (self.evaluate_inner(l), self.evaluate_inner(r)) {
-                    (ExecResult::Value(RelType::Int(a)), ExecResult::Value(RelType::Int(b))) => ExecResult::Value(RelType::Int(a >> b)),
+                    (ExecResult::Value(RelType::Int(a)), ExecResult::Value(RelType::Int(b))) => match crate::arith::int_op(self.overflow_mode, '>', a, b) {
+                        Some(v) => ExecResult::Value(RelType::Int(v)),
+                        None => crate::arith::overflow_fault('>', a, b),
+                    },
                     _ => ExecResult::Fault { msg: "Bitwise SHR expects integers".into(), node: "Node::BitShiftRight".into() },
                 }
             }
@@ -243,6 +309,7 @@ impl ExecutionEngine {
             }
             Node::While(cond, body) => {
                 while let ExecResult::Value(RelType::Bool(true)) = self.evaluate_inner(cond) {
+                    self.poll_actions();
                     match self.evaluate_inner(body) {
                         ExecResult::Value(v) => self.release_handles(&v),
                         ExecResult::ReturnBlockInfo(v) => return ExecResult::ReturnBlockInfo(v),
@@ -255,7 +322,9 @@ impl ExecutionEngine {
                 let mut last_val = RelType::Void;
                 let len = nodes.len();
                 for (i, n) in nodes.iter().enumerate() {
-                    match self.evaluate_inner(n) {
+                    let res = self.evaluate_inner(n);
+                    if self.tracer.is_some() { self.trace_statement(n, &res); }
+                    match res {
                         ExecResult::Value(v) => {
                             if i < len - 1 { self.release_handles(&v); }
                             else { last_val = v; }
@@ -270,6 +339,11 @@ impl ExecutionEngine {
                 let v = match self.evaluate_inner(&*expr) { ExecResult::Value(v) => v, err => return err };
                 ExecResult::ReturnBlockInfo(v)
             }
+            // Statement-level yields are handled by the coroutine scheduler.
+            Node::Yield => ExecResult::Fault {
+                msg: "yield is only allowed as a statement in a function started with Coroutine.Start".into(),
+                node: "Node::Yield".into(),
+            },
 
             // Functions
             Node::FnDef(name, params, body) => {
@@ -281,27 +355,28 @@ impl ExecutionEngine {
                 match func {
                     RelType::FnDef(_, params, body) => {
                         if params.len() != args.len() { return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() } }
-                        let mut locals = HashMap::with_capacity(params.len());
-                        for (p, a) in params.iter().zip(args.iter()) {
+                        let mut values = Vec::with_capacity(args.len());
+                        for a in args {
                             match self.evaluate_inner(a) {
-                                ExecResult::Value(v) => { locals.insert(p.clone(), v); }
+                                ExecResult::Value(v) => values.push(v),
                                 err => return err,
                             }
                         }
-                        self.call_stack.push(StackFrame { locals });
-                        let res = self.evaluate_inner(&body);
-                        if let Some(frame) = self.call_stack.pop() {
-                            for (_, val) in frame.locals { self.release_handles(&val); }
-                        }
-                        match res {
-                            ExecResult::ReturnBlockInfo(v) => ExecResult::Value(v),
-                            other => other,
-                        }
+                        self.invoke_function(name, &params, &body, values)
                     }
                     _ => ExecResult::Fault { msg: format!("'{}' is not a function", name), node: "Node::Call".into() },
                 }
             }
 
+            // Source Mapping
+            Node::Located { line, col, stmt } => {
+                if let Some(frame) = self.call_stack.last_mut() { frame.line = *line; }
+                if self.debugger.is_some() && let Some(fault) = self.debug_statement(*line, *col) {
+                    return fault;
+                }
+                self.evaluate_inner(stmt)
+            }
+
             // Special Physics
             Node::CheckCollision { a_min, a_max, b_min, b_max } => {
                 let am = match self.evaluate_inner(a_min) { ExecResult::Value(v) => v, err => return err };
@@ -351,25 +426,30 @@ impl ExecutionEngine {
     pub fn do_math(&mut self, left: &Node, op: char, right: &Node) -> ExecResult {
         let lv = match self.evaluate_inner(left) { ExecResult::Value(v) => v, err => return err };
         let rv = match self.evaluate_inner(right) { ExecResult::Value(v) => v, err => return err };
+        if let (RelType::Int(a), RelType::Int(b)) = (&lv, &rv) {
+            if op == '/' && *b == 0 {
+                return ExecResult::Fault { msg: "Div by zero".into(), node: "Node::MathDiv".into() };
+            }
+            return match crate::arith::int_op(self.overflow_mode, op, *a, *b) {
+                Some(v) => ExecResult::Value(RelType::Int(v)),
+                None => crate::arith::overflow_fault(op, *a, *b),
+            };
+        }
         let res = match op {
             '+' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a + b),
                 (RelType::Float(a), RelType::Float(b)) => RelType::Float(a + b),
                 (RelType::Str(a), RelType::Str(b)) => RelType::Str(a + &b),
                 _ => return ExecResult::Fault { msg: "Invalid types for +".into(), node: "Node::Add".into() },
             },
             '-' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a - b),
                 (RelType::Float(a), RelType::Float(b)) => RelType::Float(a - b),
                 _ => return ExecResult::Fault { msg: "Invalid types for -".into(), node: "Node::Sub".into() },
             },
             '*' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a * b),
                 (RelType::Float(a), RelType::Float(b)) => RelType::Float(a * b),
                 _ => return ExecResult::Fault { msg: "Invalid types for *".into(), node: "Node::Mul".into() },
             },
             '/' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => { if b == 0 { return ExecResult::Fault { msg: "Div by zero".into(), node: "Node::MathDiv".into() } } RelType::Int(a / b) },
                 (RelType::Float(a), RelType::Float(b)) => RelType::Float(a / b),
                 _ => return ExecResult::Fault { msg: "Invalid types for /".into(), node: "Node::Div".into() },
             },
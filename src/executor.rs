@@ -21,6 +21,10 @@ impl Drop for NativeHandle {
     }
 }
 
+/// Object storage. Keys keep their insertion order, so Display, JSON output and
+/// key iteration are the same on every run.
+pub type ObjectMap = indexmap::IndexMap<String, RelType>;
+
 #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum RelType {
     Int(i64),
@@ -28,7 +32,7 @@ pub enum RelType {
     Bool(bool),
     Str(String),
     Array(Vec<RelType>),
-    Object(HashMap<String, RelType>),
+    Object(ObjectMap),
     Handle(NativeHandle),
     FnDef(String, Vec<String>, Box<Node>),
     Call(String, Vec<Node>),
@@ -41,11 +45,116 @@ pub struct AgentPermissions {
     pub allowed_domains: Vec<String>,
     pub allow_fs_read: bool,
     pub allow_fs_write: bool,
+    /// Canonical directories reads are limited to; empty means the whole
+    /// working directory.
+    pub read_dirs: Vec<PathBuf>,
+    /// Canonical directories writes are limited to; empty means the whole
+    /// working directory.
+    pub write_dirs: Vec<PathBuf>,
 }
 
 impl Default for AgentPermissions {
     fn default() -> Self {
-        Self { allow_network: false, allowed_domains: Vec::new(), allow_fs_read: false, allow_fs_write: false }
+        Self {
+            allow_network: false,
+            allowed_domains: Vec::new(),
+            allow_fs_read: false,
+            allow_fs_write: false,
+            read_dirs: Vec::new(),
+            write_dirs: Vec::new(),
+        }
+    }
+}
+
+impl AgentPermissions {
+    /// Whether a script may fetch `url`: network access must be on and the
+    /// host must pass `check_domains`.
+    pub fn check_url(&self, url: &str) -> Result<(), String> {
+        if !self.allow_network {
+            return Err("Permission Denied: allow_network is false. Use --allow-network flag.".into());
+        }
+        check_domains(&self.allowed_domains, url)
+    }
+
+    /// Turns on reads and limits them to `dir` (plus any earlier grants).
+    pub fn grant_read(&mut self, dir: &str) -> Result<(), String> {
+        let dir = canonical_grant(dir)?;
+        self.allow_fs_read = true;
+        self.read_dirs.push(dir);
+        Ok(())
+    }
+
+    /// Turns on writes and limits them to `dir` (plus any earlier grants).
+    pub fn grant_write(&mut self, dir: &str) -> Result<(), String> {
+        let dir = canonical_grant(dir)?;
+        self.allow_fs_write = true;
+        self.write_dirs.push(dir);
+        Ok(())
+    }
+
+    /// Resolves an existing file a script wants to read and checks it
+    /// against `read_dirs`. Does not check `allow_fs_read`.
+    pub fn check_read_path(&self, path: &str) -> Result<PathBuf, String> {
+        let resolved = ExecutionEngine::validate_fs_path(path)?;
+        within(&self.read_dirs, resolved, path, "read")
+    }
+
+    /// Like `check_read_path` for paths that may not exist, such as the
+    /// argument of `IO.FileExists`.
+    pub fn check_lookup_path(&self, path: &str) -> Result<PathBuf, String> {
+        let resolved = ExecutionEngine::validate_fs_path_write(path)?;
+        within(&self.read_dirs, resolved, path, "read")
+    }
+
+    /// Resolves a file a script wants to create or change and checks it
+    /// against `write_dirs`. Does not check `allow_fs_write`.
+    pub fn check_write_path(&self, path: &str) -> Result<PathBuf, String> {
+        let resolved = ExecutionEngine::validate_fs_path_write(path)?;
+        within(&self.write_dirs, resolved, path, "write")
+    }
+}
+
+/// Canonicalizes a directory grant, which must lie in the working directory.
+fn canonical_grant(dir: &str) -> Result<PathBuf, String> {
+    let canonical = ExecutionEngine::validate_fs_path(dir)?;
+    if !canonical.is_dir() {
+        return Err(format!("'{}' is not a directory", dir));
+    }
+    Ok(canonical)
+}
+
+fn within(dirs: &[PathBuf], resolved: PathBuf, path: &str, access: &str) -> Result<PathBuf, String> {
+    if dirs.is_empty() || dirs.iter().any(|dir| resolved.starts_with(dir)) {
+        Ok(resolved)
+    } else {
+        Err(format!("Permission Denied: '{}' is outside the allowed {} directories", path, access))
+    }
+}
+
+/// Matches the host of `url` against an allowlist. Entries are host names
+/// (`example.org`) or wildcards (`*.example.org`, any subdomain but not
+/// example.org itself). An empty list allows every host.
+pub fn check_domains(allowed: &[String], url: &str) -> Result<(), String> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let parsed = url::Url::parse(url).map_err(|e| format!("Permission Denied: cannot check the host of '{}': {}", url, e))?;
+    let host = match parsed.host() {
+        Some(url::Host::Domain(domain)) => domain.trim_end_matches('.').to_ascii_lowercase(),
+        Some(ip) => ip.to_string(),
+        None => return Err(format!("Permission Denied: '{}' has no host", url)),
+    };
+    let matches = |entry: &String| {
+        let entry = entry.trim_end_matches('.').to_ascii_lowercase();
+        match entry.strip_prefix("*.") {
+            Some(parent) => host.strip_suffix(parent).is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+            None => host == entry,
+        }
+    };
+    if allowed.iter().any(matches) {
+        Ok(())
+    } else {
+        Err(format!("Permission Denied: host '{}' is not in allowed_domains", host))
     }
 }
 
@@ -84,8 +193,11 @@ impl Default for VoiceState {
 
 // Sprint 85: MeshBuffers removed — mesh/GPU resources are managed exclusively in window.rs (KnotenApp)
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct StackFrame {
     pub locals: HashMap<String, RelType>,
+    pub function: String,
+    pub line: usize,
 }
 
 #[repr(C)]
@@ -110,7 +222,7 @@ pub struct InstanceData {
     pub material_pbr: [f32; 4],
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 pub struct PointLightData {
     pub x: f32, pub y: f32, pub z: f32,
     pub r: f32, pub g: f32, pub b: f32,
@@ -138,6 +250,8 @@ pub struct ExecutionEngine {
     pub memory: HashMap<String, RelType>,
     pub startup_time: std::time::Instant,
     pub native_modules: Vec<Box<dyn NativeModule>>,
+    /// Descriptions of every callable native (built-ins plus `register`ed closures).
+    pub natives: Vec<crate::natives::catalogue::NativeInfo>,
     pub bridge: Box<dyn BridgeModule>,
     // ── Camera / FPS state (read by executor nodes) ───────────────────
     pub camera_active: bool,
@@ -175,12 +289,28 @@ pub struct ExecutionEngine {
     pub samples: HashMap<i64, std::sync::Arc<[u8]>>,
     // ── Async / Permissions / Actions ────────────────────────────────
     pub async_bridge: Option<crate::async_bridge::AsyncBridge>,
+    pub timers: crate::timers::Timers,
+    pub coroutines: crate::coroutines::Coroutines,
+    pub workers: crate::workers::Workers,
+    /// Set while `poll_async_bridge` runs, so callbacks that reach a frame
+    /// boundary themselves do not pump again.
+    pumping: bool,
     pub action_tx: Option<std::sync::mpsc::Sender<Action>>,
     pub action_rx: Option<std::sync::mpsc::Receiver<Action>>,
     pub permission_fault: Option<String>,
     pub ui_dirty: bool,
     pub permissions: AgentPermissions,
+    /// Int `+ - * /` overflow behaviour; faults by default.
+    pub overflow_mode: crate::arith::OverflowMode,
     pub call_stack: Vec<StackFrame>,
+    // ── Determinism (seeded RNG + virtual clock) ─────────────────────
+    pub determinism: Option<crate::determinism::Determinism>,
+    // ── Tooling ──────────────────────────────────────────────────────
+    pub debugger: Option<Box<crate::debugger::Debugger>>,
+    /// When set, `Print` output is sent here instead of stdout.
+    pub stdout_tx: Option<std::sync::mpsc::Sender<String>>,
+    pub profiler: Option<Box<crate::profiler::Profiler>>,
+    pub tracer: Option<Box<crate::tracer::Tracer>>,
     // ── 2D / Weapon ──────────────────────────────────────────────────
     pub render_canvas_active: bool,
     pub camera3d_view_proj: Option<[[f32; 4]; 4]>,
@@ -202,7 +332,24 @@ pub struct ExecutionEngine {
 // threads simultaneously, so Send alone is sufficient.
 unsafe impl Send for ExecutionEngine {}
 
-pub enum Action { UpdateData(String, RelType) }
+/// The native a script-driven render loop calls once per frame; the engine
+/// pumps timers and coroutines right after it.
+const FRAME_PRESENT: &str = "registry_window_update";
+
+/// Host → script messages, sent through `ExecutionEngine::action_sender` and
+/// applied by `poll_actions`.
+pub enum Action {
+    /// Assigns a variable as if by `let name = value;`.
+    UpdateData(String, RelType),
+    /// `Node::FnDef`s that replace the current definitions (hot reload).
+    ReloadFunctions(Vec<Node>),
+    /// Calls a script function. The result goes to the reply channel if one is
+    /// given; otherwise faults are reported on stderr.
+    CallFunction(String, Vec<RelType>, Option<std::sync::mpsc::Sender<ExecResult>>),
+    /// Signals a named event: calls `on_<name>(payload)` if the script defines
+    /// it, else appends `{name, payload}` to the global `events` array.
+    Event(String, RelType),
+}
 
 pub enum ExecResult { Value(RelType), ReturnBlockInfo(RelType), Fault { msg: String, node: String } }
 
@@ -226,7 +373,16 @@ impl ExecutionEngine {
         self.evaluate(node)
     }
 
+    /// Per-frame pump: delivers finished `Fetch` requests to their callbacks,
+    /// fires due timers (`poll_timers`) and resumes coroutines
+    /// (`poll_coroutines`). Scripts reach it at every `PollEvents` and after
+    /// every `registry_window_update`; hosts without such a loop call it
+    /// themselves. Does nothing when called from inside a pump.
     pub fn poll_async_bridge(&mut self) {
+        if self.pumping {
+            return;
+        }
+        self.pumping = true;
         let mut payloads = Vec::new();
         if let Some(bridge) = &self.async_bridge {
             while let Some(payload) = bridge.try_recv() {
@@ -242,6 +398,68 @@ impl ExecutionEngine {
             self.memory.insert("fetch_error".into(), is_err);
             let _ = self.evaluate(&payload.callback_node);
         }
+        self.poll_timers();
+        self.poll_coroutines();
+        self.pumping = false;
+    }
+
+    /// A sender for injecting actions from host threads. Actions are applied
+    /// at the next `poll_actions`: every `while` iteration and `PollEvents`,
+    /// or explicitly by the host between `execute` calls.
+    pub fn action_sender(&mut self) -> std::sync::mpsc::Sender<Action> {
+        if self.action_tx.is_none() {
+            let (tx, rx) = std::sync::mpsc::channel();
+            self.action_tx = Some(tx);
+            self.action_rx = Some(rx);
+        }
+        self.action_tx.clone().unwrap()
+    }
+
+    /// Applies actions queued through `action_tx`. Called once per loop
+    /// iteration, so changes land between statements, never inside one.
+    pub fn poll_actions(&mut self) {
+        let actions: Vec<Action> = match &self.action_rx {
+            Some(rx) => rx.try_iter().collect(),
+            None => return,
+        };
+        for action in actions {
+            match action {
+                Action::UpdateData(name, val) => self.set_var(name, val),
+                Action::ReloadFunctions(defs) => {
+                    for def in &defs {
+                        let _ = self.evaluate(def);
+                    }
+                }
+                Action::CallFunction(name, args, reply) => {
+                    let res = self.call_function(&name, args);
+                    match reply {
+                        Some(reply) => { let _ = reply.send(res); }
+                        None => if let ExecResult::Fault { msg, node } = res {
+                            eprintln!("[action] {} failed: {} (at {})", name, msg, node);
+                        }
+                    }
+                }
+                Action::Event(name, payload) => self.dispatch_event(name, payload),
+            }
+        }
+    }
+
+    fn dispatch_event(&mut self, name: String, payload: RelType) {
+        let handler = format!("on_{}", name);
+        if let Some(RelType::FnDef(_, params, _)) = self.get_var(&handler) {
+            let args = if params.is_empty() { vec![] } else { vec![payload] };
+            if let ExecResult::Fault { msg, node } = self.call_function(&handler, args) {
+                eprintln!("[action] {} failed: {} (at {})", handler, msg, node);
+            }
+            return;
+        }
+        let mut entry = ObjectMap::new();
+        entry.insert("name".to_string(), RelType::Str(name));
+        entry.insert("payload".to_string(), payload);
+        match self.memory.get_mut("events") {
+            Some(RelType::Array(queue)) => queue.push(RelType::Object(entry)),
+            _ => { self.memory.insert("events".into(), RelType::Array(vec![RelType::Object(entry)])); }
+        }
     }
 
     pub fn get_var(&self, name: &str) -> Option<RelType> {
@@ -252,6 +470,7 @@ impl ExecutionEngine {
     }
 
     pub fn set_var(&mut self, name: String, val: RelType) {
+        if self.tracer.is_some() { self.trace_set_var(&name, &val); }
         // Walk the call stack from innermost → outermost looking for an existing binding.
         // If found, update in place (assignment to an already-declared variable).
         for frame in self.call_stack.iter_mut().rev() {
@@ -283,6 +502,21 @@ impl ExecutionEngine {
         Err(ExecResult::Fault { msg: "Target is not a map/object".into(), node: "Node::MapSet/PropertySet".into() })
     }
 
+    /// In-place key removal, the counterpart of `mutate_map_insert`.
+    pub fn mutate_map_remove(&mut self, name: &str, key: &str) -> Result<Option<RelType>, ExecResult> {
+        for frame in self.call_stack.iter_mut().rev() {
+            if let Some(RelType::Object(m)) = frame.locals.get_mut(name) {
+                return Ok(m.shift_remove(key));
+            } else if frame.locals.contains_key(name) {
+                return Err(ExecResult::Fault { msg: "Target is not a map/object".into(), node: "Node::MapRemove".into() });
+            }
+        }
+        if let Some(RelType::Object(m)) = self.memory.get_mut(name) {
+            return Ok(m.shift_remove(key));
+        }
+        Err(ExecResult::Fault { msg: "Target is not a map/object".into(), node: "Node::MapRemove".into() })
+    }
+
     /// Sprint 88 Optimization: In-place Array Modification
     /// Avoids cloning the entire Vec when setting a single index
     pub fn mutate_array_set(&mut self, name: &str, idx: usize, val: RelType) -> Result<RelType, ExecResult> {
@@ -338,6 +572,7 @@ impl ExecutionEngine {
             memory: HashMap::new(),
             startup_time: std::time::Instant::now(),
             native_modules: Vec::new(),
+            natives: crate::natives::catalogue::builtin_natives(),
             bridge: Box::new(CoreBridge),
             camera_active: false,
             camera_pos: [0.0, 1.0, 0.0],
@@ -368,12 +603,22 @@ impl ExecutionEngine {
             audio_stream_handle: None,
             samples: HashMap::new(),
             async_bridge: Some(crate::async_bridge::AsyncBridge::new()),
+            timers: crate::timers::Timers::default(),
+            coroutines: crate::coroutines::Coroutines::default(),
+            pumping: false,
+            workers: crate::workers::Workers::default(),
             action_tx: None,
             action_rx: None,
             permission_fault: None,
             ui_dirty: false,
             permissions: AgentPermissions::default(),
-            call_stack: vec![StackFrame { locals: HashMap::new() }],
+            overflow_mode: crate::arith::OverflowMode::default(),
+            determinism: None,
+            call_stack: vec![StackFrame { locals: HashMap::new(), function: "<main>".into(), line: 0 }],
+            debugger: None,
+            stdout_tx: None,
+            profiler: None,
+            tracer: None,
             render_canvas_active: false,
             camera3d_view_proj: None,
             canvas_material: [1.0, 1.0, 1.0, 1.0, 0.0, 0.5, 0.0, 0.0],
@@ -390,16 +635,30 @@ impl ExecutionEngine {
         engine.action_rx = Some(rx);
         engine.native_modules.push(Box::new(crate::natives::math::MathModule));
         engine.native_modules.push(Box::new(crate::natives::io::IoModule));
+        engine.native_modules.push(Box::new(crate::natives::string::StringModule));
+        engine.native_modules.push(Box::new(crate::natives::json::JsonModule));
+        engine.native_modules.push(Box::new(crate::natives::regex::RegexModule::default()));
+        engine.native_modules.push(Box::new(crate::natives::datetime::DateTimeModule));
         engine.native_modules.push(Box::new(crate::natives::registry::RegistryModule));
         engine
     }
 
     pub fn evaluate_extra(&mut self, node: &Node) -> ExecResult {
         match node {
-            Node::PollEvents(body) => { self.evaluate(body) }
+            Node::PollEvents(body) => {
+                self.poll_actions();
+                self.poll_async_bridge();
+                self.evaluate(body)
+            }
             Node::Print(expr) => {
                 match self.evaluate(expr) {
-                    ExecResult::Value(v) => { println!("{}", v); ExecResult::Value(RelType::Void) }
+                    ExecResult::Value(v) => {
+                        match &self.stdout_tx {
+                            Some(tx) => { let _ = tx.send(v.to_string()); }
+                            None => println!("{}", v),
+                        }
+                        ExecResult::Value(RelType::Void)
+                    }
                     err => err,
                 }
             }
@@ -474,7 +733,7 @@ impl ExecutionEngine {
                 if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::FileRead".into() }; }
                 if let ExecResult::Value(RelType::Str(p)) = self.evaluate(path) {
                     // FINDING-05: Canonicalize path to prevent directory traversal escapes
-                    match Self::validate_fs_path(&p) {
+                    match self.permissions.check_read_path(&p) {
                         Err(e) => ExecResult::Fault { msg: format!("Security: {}", e), node: "Node::FileRead".into() },
                         Ok(safe_path) => match std::fs::read_to_string(&safe_path) {
                             Ok(s) => ExecResult::Value(RelType::Str(s)),
@@ -487,7 +746,7 @@ impl ExecutionEngine {
                 if !self.permissions.allow_fs_write { return ExecResult::Fault { msg: "Permission Denied: allow_fs_write is false".into(), node: "Node::FileWrite".into() }; }
                 if let (ExecResult::Value(RelType::Str(p)), ExecResult::Value(RelType::Str(d))) = (self.evaluate(path), self.evaluate(data)) {
                     // FINDING-05: Canonicalize path to prevent directory traversal escapes
-                    match Self::validate_fs_path_write(&p) {
+                    match self.permissions.check_write_path(&p) {
                         Err(e) => ExecResult::Fault { msg: format!("Security: {}", e), node: "Node::FileWrite".into() },
                         Ok(safe_path) => {
                             if let Err(e) = std::fs::write(&safe_path, &d) { return ExecResult::Fault { msg: format!("File write error: {}", e), node: "Node::FileWrite".into() }; }
@@ -500,7 +759,7 @@ impl ExecutionEngine {
                 if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::FSRead".into() }; }
                 if let ExecResult::Value(RelType::Str(p)) = self.evaluate(path) {
                     // FINDING-05: Canonicalize path to prevent directory traversal escapes
-                    match Self::validate_fs_path(&p) {
+                    match self.permissions.check_read_path(&p) {
                         Err(e) => ExecResult::Fault { msg: format!("Security: {}", e), node: "Node::FSRead".into() },
                         Ok(safe_path) => match std::fs::read_to_string(&safe_path) {
                             Ok(s) => ExecResult::Value(RelType::Str(s)),
@@ -513,7 +772,7 @@ impl ExecutionEngine {
                 if !self.permissions.allow_fs_write { return ExecResult::Fault { msg: "Permission Denied: allow_fs_write is false".into(), node: "Node::FSWrite".into() }; }
                 if let (ExecResult::Value(RelType::Str(p)), ExecResult::Value(RelType::Str(d))) = (self.evaluate(path), self.evaluate(data)) {
                     // FINDING-05: Canonicalize path to prevent directory traversal escapes
-                    match Self::validate_fs_path_write(&p) {
+                    match self.permissions.check_write_path(&p) {
                         Err(e) => ExecResult::Fault { msg: format!("Security: {}", e), node: "Node::FSWrite".into() },
                         Ok(safe_path) => {
                             if let Err(e) = std::fs::write(&safe_path, &d) { return ExecResult::Fault { msg: format!("FSWrite error: {}", e), node: "Node::FSWrite".into() }; }
@@ -525,10 +784,23 @@ impl ExecutionEngine {
             Node::NativeCall(name, args) => {
                 let mut v_args = Vec::with_capacity(args.len());
                 for a in args { match self.evaluate(a) { ExecResult::Value(v) => v_args.push(v), err => return err } }
-                for mod_ in &self.native_modules {
-                    if let Some(res) = mod_.handle(name, &v_args, &self.permissions) { return res; }
-                }
-                ExecResult::Fault { msg: format!("Native function '{}' not found", name), node: "Node::NativeCall".into() }
+                if let Some(profiler) = self.profiler.as_mut() { profiler.enter_frame(name, true); }
+                let res = match self
+                    .deterministic_native(name, &v_args)
+                    .or_else(|| self.catalogue_native(name, &v_args))
+                    .or_else(|| self.array_native(name, &v_args))
+                    .or_else(|| self.timer_native(name, &v_args))
+                    .or_else(|| self.coroutine_native(name, &v_args))
+                    .or_else(|| self.worker_native(name, &v_args))
+                {
+                    Some(res) => Some(res),
+                    None => self.native_modules.iter().find_map(|mod_| mod_.handle(name, &v_args, &self.permissions)),
+                };
+                if let Some(profiler) = self.profiler.as_mut() { profiler.exit_frame(); }
+                let res = res.unwrap_or_else(|| ExecResult::Fault { msg: format!("Native function '{}' not found", name), node: "Node::NativeCall".into() });
+                if self.tracer.is_some() { self.trace_native(name, &v_args, &res); }
+                if name == FRAME_PRESENT { self.poll_async_bridge(); }
+                res
             }
             Node::ExternCall { module, function, args } => {
                 let mut v_args = Vec::with_capacity(args.len());
@@ -562,8 +834,17 @@ impl ExecutionEngine {
                     }
                 }
 
-                if let Some(res) = self.bridge.handle(module, function, &v_args, &self.permissions) { return res; }
-                ExecResult::Fault { msg: format!("Extern function '{}.{}' not found", module, function), node: "Node::ExternCall".into() }
+                if let Some(profiler) = self.profiler.as_mut() { profiler.enter_frame(&format!("{}.{}", module, function), true); }
+                let res = match module.as_str() {
+                    "registry" => self.deterministic_native(function, &v_args),
+                    _ => None,
+                };
+                let res = res.or_else(|| self.bridge.handle(module, function, &v_args, &self.permissions));
+                if let Some(profiler) = self.profiler.as_mut() { profiler.exit_frame(); }
+                let res = res.unwrap_or_else(|| ExecResult::Fault { msg: format!("Extern function '{}.{}' not found", module, function), node: "Node::ExternCall".into() });
+                if self.tracer.is_some() { self.trace_native(&format!("{}.{}", module, function), &v_args, &res); }
+                if function == FRAME_PRESENT { self.poll_async_bridge(); }
+                res
             }
             Node::UIWindow(_id, _title, body) => {
                 self.evaluate(body)
@@ -579,15 +860,34 @@ impl ExecutionEngine {
             Node::UIFillParent => ExecResult::Value(RelType::Void),
             Node::Fetch { method, url, callback } => {
                 // FINDING-03 FIX: Check network permission before dispatching fetch
-                if !self.permissions.allow_network {
-                    return ExecResult::Fault { msg: "Permission Denied: allow_network is false. Use --allow-network flag.".into(), node: "Node::Fetch".into() };
+                if let Err(msg) = self.permissions.check_url(url) {
+                    return ExecResult::Fault { msg, node: "Node::Fetch".into() };
                 }
                 if let Some(bridge) = &self.async_bridge {
-                    bridge.dispatch_fetch(method.clone(), url.clone(), callback.clone());
+                    bridge.dispatch_fetch(method.clone(), url.clone(), callback.clone(), self.permissions.allowed_domains.clone());
                     ExecResult::Value(RelType::Void)
                 } else { ExecResult::Fault { msg: "AsyncBridge not initialized".into(), node: "Node::Fetch".into() } }
             }
-            Node::Extract { .. } => ExecResult::Fault { msg: "Extract not implemented".into(), node: "Node::Extract".into() },
+            Node::Extract { source, path } => {
+                let source = match self.evaluate(source) { ExecResult::Value(v) => v, err => return err };
+                let path = match self.evaluate(path) {
+                    ExecResult::Value(RelType::Str(p)) => p,
+                    ExecResult::Value(_) => return ExecResult::Fault { msg: "Extract path must be a String".into(), node: "Node::Extract".into() },
+                    err => return err,
+                };
+                // Strings are JSON documents, e.g. the raw body left in `fetch_result`.
+                let doc = match source {
+                    RelType::Str(json) => match crate::natives::fs::try_parse_json(&json) {
+                        Ok(doc) => doc,
+                        Err(e) => return ExecResult::Fault { msg: format!("Extract source is not valid JSON: {}", e), node: "Node::Extract".into() },
+                    },
+                    other => other,
+                };
+                match crate::json_path::extract(&doc, &path) {
+                    Ok(v) => ExecResult::Value(v),
+                    Err(e) => ExecResult::Fault { msg: format!("Extract: {}", e), node: "Node::Extract".into() },
+                }
+            }
             Node::EvalJSONNative(json_expr) => {
                 if let ExecResult::Value(RelType::Str(json)) = self.evaluate(json_expr) {
                     ExecResult::Value(crate::natives::fs::fs_parse_json(&json))
@@ -0,0 +1,79 @@
+use crate::ast::Node;
+use crate::executor::Action;
+use crate::natives::catalogue::NativeInfo;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime};
+
+/// Top-level `FnDef`s of a script (statement locations are looked through).
+pub fn extract_functions(ast: &Node) -> Vec<Node> {
+    let stmts: &[Node] = match ast {
+        Node::Block(stmts) => stmts,
+        other => std::slice::from_ref(other),
+    };
+    stmts
+        .iter()
+        .map(|stmt| match stmt {
+            Node::Located { stmt, .. } => stmt.as_ref(),
+            other => other,
+        })
+        .filter(|stmt| matches!(stmt, Node::FnDef(..)))
+        .cloned()
+        .collect()
+}
+
+/// Parses, type checks and validates `source` against `natives` (the running
+/// engine's catalogue), as `run_knc` does at startup, and returns its function
+/// definitions. `.knoten` sources are parsed as DSL, anything else as a JSON AST.
+pub fn prepare_reload(path: &str, source: &str, optimize: bool, natives: &[NativeInfo]) -> Result<Vec<Node>, String> {
+    let mut ast = crate::parser::parse_script(path, source, false)?;
+    let mut typer = crate::optimizer::TypeChecker::new();
+    let _ = typer.check(&ast);
+    if !typer.errors.is_empty() {
+        return Err(format!("Type errors:\n - {}", typer.errors.join("\n - ")));
+    }
+    if let Err(errors) = crate::validator::Validator::with_natives(natives).validate(&ast) {
+        return Err(format!("Validation errors:\n - {}", errors.join("\n - ")));
+    }
+    if optimize {
+        ast = crate::optimizer::optimize(ast);
+    }
+    Ok(extract_functions(&ast))
+}
+
+/// Polls `path` for modifications and sends the new function definitions to a
+/// running engine as `Action::ReloadFunctions`. Globals, registry handles and
+/// windows are untouched; top-level statements are not re-run. Parse, type and
+/// validation errors are reported on stderr and the running definitions are kept.
+///
+/// The thread ends when the engine (the receiving side) is gone.
+pub fn spawn_watcher(path: String, tx: Sender<Action>, optimize: bool, natives: Vec<NativeInfo>, interval: Duration) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let modified = |p: &str| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+        let mut last: Option<SystemTime> = modified(&path);
+        loop {
+            std::thread::sleep(interval);
+            let now = modified(&path);
+            if now.is_none() || now == last {
+                continue;
+            }
+            last = now;
+            let source = match std::fs::read_to_string(&path) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("[watch] Cannot read '{}': {}", path, e);
+                    continue;
+                }
+            };
+            match prepare_reload(&path, &source, optimize, &natives) {
+                Ok(defs) => {
+                    let count = defs.len();
+                    if tx.send(Action::ReloadFunctions(defs)).is_err() {
+                        return;
+                    }
+                    println!("[watch] Reloaded {} function(s) from {}", count, path);
+                }
+                Err(e) => eprintln!("[watch] Reload of {} rejected, keeping previous code:\n{}", path, e),
+            }
+        }
+    })
+}
@@ -0,0 +1,302 @@
+//! Path queries for `Node::Extract`.
+//!
+//! Two syntaxes are accepted:
+//! - JSON Pointer (RFC 6901): `""` or `/store/book/0/title`. Selects at most
+//!   one value; a missing target yields Void.
+//! - JSONPath: `$`, `.name`, `['name']`, `[0]`, `[-1]`, `[*]`, `.*`, `..name`,
+//!   `[start:end:step]` and unions such as `[0,2]` or `['a','b']`. A path
+//!   made only of names and indices selects one value (Void if missing);
+//!   any other path returns an Array of all matches in document order.
+//!   Filter expressions (`[?(...)]`) are not supported.
+
+use crate::executor::RelType;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+/// Evaluates `path` against `root`. Errors describe a malformed path.
+pub fn extract(root: &RelType, path: &str) -> Result<RelType, String> {
+    if path.is_empty() || path.starts_with('/') {
+        return pointer(root, path);
+    }
+    let segments = parse(path)?;
+    let definite = segments
+        .iter()
+        .all(|s| matches!(s, Segment::Child(sel) if matches!(sel.as_slice(), [Selector::Name(_) | Selector::Index(_)])));
+    let mut nodes = vec![root];
+    for segment in &segments {
+        let mut next = Vec::new();
+        match segment {
+            Segment::Child(selectors) => {
+                for node in &nodes {
+                    for sel in selectors {
+                        select(node, sel, &mut next);
+                    }
+                }
+            }
+            Segment::Descendant(selectors) => {
+                for node in &nodes {
+                    let mut all = Vec::new();
+                    descendants(node, &mut all);
+                    for d in all {
+                        for sel in selectors {
+                            select(d, sel, &mut next);
+                        }
+                    }
+                }
+            }
+        }
+        nodes = next;
+    }
+    if definite {
+        return Ok(nodes.first().map_or(RelType::Void, |v| (*v).clone()));
+    }
+    Ok(RelType::Array(nodes.into_iter().cloned().collect()))
+}
+
+fn pointer(root: &RelType, path: &str) -> Result<RelType, String> {
+    let mut node = root;
+    for raw in path.split('/').skip(1) {
+        let token = unescape_pointer(raw)?;
+        let child = match node {
+            RelType::Object(map) => map.get(&token),
+            RelType::Array(items) => {
+                let valid = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+                if !valid && token != "-" {
+                    return Err(format!("invalid array index '{}' in pointer", token));
+                }
+                token.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        };
+        match child {
+            Some(c) => node = c,
+            None => return Ok(RelType::Void),
+        }
+    }
+    Ok(node.clone())
+}
+
+fn unescape_pointer(token: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => out.push('~'),
+            Some('1') => out.push('/'),
+            _ => return Err(format!("invalid escape in pointer token '{}' (use ~0 or ~1)", token)),
+        }
+    }
+    Ok(out)
+}
+
+/// Pre-order list of `node` and everything below it.
+fn descendants<'a>(node: &'a RelType, out: &mut Vec<&'a RelType>) {
+    out.push(node);
+    match node {
+        RelType::Array(items) => items.iter().for_each(|v| descendants(v, out)),
+        RelType::Object(map) => map.values().for_each(|v| descendants(v, out)),
+        _ => {}
+    }
+}
+
+fn select<'a>(node: &'a RelType, sel: &Selector, out: &mut Vec<&'a RelType>) {
+    match (sel, node) {
+        (Selector::Name(name), RelType::Object(map)) => out.extend(map.get(name)),
+        (Selector::Index(i), RelType::Array(items)) => {
+            let idx = if *i < 0 { items.len() as i64 + i } else { *i };
+            out.extend(usize::try_from(idx).ok().and_then(|idx| items.get(idx)));
+        }
+        (Selector::Wildcard, RelType::Array(items)) => out.extend(items.iter()),
+        (Selector::Wildcard, RelType::Object(map)) => out.extend(map.values()),
+        (Selector::Slice(start, end, step), RelType::Array(items)) => {
+            let len = items.len() as i64;
+            let norm = |v: i64| if v < 0 { (len + v).max(if *step > 0 { 0 } else { -1 }) } else { v.min(len) };
+            if *step > 0 {
+                let (mut i, end) = (start.map_or(0, norm), end.map_or(len, norm));
+                while i < end {
+                    out.push(&items[i as usize]);
+                    let Some(next) = i.checked_add(*step) else { break };
+                    i = next;
+                }
+            } else {
+                let (mut i, end) = (start.map_or(len - 1, |s| norm(s).min(len - 1)), end.map_or(-1, norm));
+                while i > end {
+                    out.push(&items[i as usize]);
+                    let Some(next) = i.checked_add(*step) else { break };
+                    i = next;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+struct PathParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    path: &'a str,
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>, String> {
+    let mut p = PathParser { chars: path.chars().collect(), pos: 0, path };
+    if p.bump() != Some('$') {
+        return Err(format!("path '{}' must start with '$' (JSONPath) or '/' (JSON Pointer)", path));
+    }
+    let mut segments = Vec::new();
+    while let Some(c) = p.bump() {
+        match c {
+            '.' if p.peek() == Some('.') => {
+                p.pos += 1;
+                let selectors = match p.peek() {
+                    Some('[') => {
+                        p.pos += 1;
+                        p.bracket()?
+                    }
+                    _ => vec![p.dot_member()?],
+                };
+                segments.push(Segment::Descendant(selectors));
+            }
+            '.' => segments.push(Segment::Child(vec![p.dot_member()?])),
+            '[' => segments.push(Segment::Child(p.bracket()?)),
+            _ => return Err(p.unexpected(c)),
+        }
+    }
+    Ok(segments)
+}
+
+impl PathParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn unexpected(&self, c: char) -> String {
+        format!("unexpected '{}' at position {} in path '{}'", c, self.pos, self.path)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    /// `*` or a bare member name after `.` / `..`.
+    fn dot_member(&mut self) -> Result<Selector, String> {
+        if self.peek() == Some('*') {
+            self.pos += 1;
+            return Ok(Selector::Wildcard);
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '$') {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(match self.peek() {
+                Some(c) => self.unexpected(c),
+                None => format!("path '{}' ends with '.'", self.path),
+            });
+        }
+        Ok(Selector::Name(self.chars[start..self.pos].iter().collect()))
+    }
+
+    /// Comma-separated selectors up to the closing `]`; the `[` is consumed.
+    fn bracket(&mut self) -> Result<Vec<Selector>, String> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_spaces();
+            selectors.push(self.bracket_selector()?);
+            self.skip_spaces();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(selectors),
+                Some(c) => {
+                    self.pos -= 1;
+                    return Err(self.unexpected(c));
+                }
+                None => return Err(format!("unclosed '[' in path '{}'", self.path)),
+            }
+        }
+    }
+
+    fn bracket_selector(&mut self) -> Result<Selector, String> {
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(q @ ('\'' | '"')) => {
+                self.pos += 1;
+                let mut name = String::new();
+                loop {
+                    match self.bump() {
+                        Some('\\') => name.extend(self.bump()),
+                        Some(c) if c == q => return Ok(Selector::Name(name)),
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unterminated string in path '{}'", self.path)),
+                    }
+                }
+            }
+            Some('?') => Err(format!("filter expressions are not supported in path '{}'", self.path)),
+            _ => {
+                let first = self.int()?;
+                if self.peek() != Some(':') {
+                    return first.map(Selector::Index).ok_or_else(|| match self.peek() {
+                        Some(c) => self.unexpected(c),
+                        None => format!("unclosed '[' in path '{}'", self.path),
+                    });
+                }
+                self.pos += 1;
+                let end = self.int()?;
+                let step = if self.peek() == Some(':') {
+                    self.pos += 1;
+                    self.int()?.unwrap_or(1)
+                } else {
+                    1
+                };
+                if step == 0 {
+                    return Err(format!("slice step must not be 0 in path '{}'", self.path));
+                }
+                Ok(Selector::Slice(first, end, step))
+            }
+        }
+    }
+
+    /// Optional signed integer.
+    fn int(&mut self) -> Result<Option<i64>, String> {
+        self.skip_spaces();
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        self.skip_spaces();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        text.parse().map(Some).map_err(|_| format!("invalid index '{}' in path '{}'", text, self.path))
+    }
+}
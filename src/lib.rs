@@ -1,14 +1,27 @@
+pub mod arith;
 pub mod ast;
 pub mod async_bridge;
 pub mod compiler;
+pub mod coroutines;
+pub mod dap;
+pub mod debugger;
+pub mod determinism;
 pub mod dsl_emitter;
 pub mod evaluator;
+pub mod hot_reload;
+pub mod json_path;
 pub mod executor;
 pub mod natives;
 pub mod window;
 pub mod optimizer;
 pub mod parser;
+pub mod profiler;
+pub mod repl;
+pub mod snapshot;
 pub mod test_lib;
+pub mod timers;
+pub mod tracer;
 pub mod validator;
 pub mod vm;
+pub mod workers;
 pub mod math;
@@ -0,0 +1,197 @@
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::{arg, check_arity, describe, fault};
+use std::cmp::Ordering;
+
+/// Longest Array `Array.Range` creates.
+const MAX_RANGE_LEN: i64 = 10_000_000;
+
+/// Order of two `SortBy` keys: numbers, Strings or Bools, not mixed.
+fn compare_keys(a: &RelType, b: &RelType) -> Option<Ordering> {
+    match (a, b) {
+        (RelType::Int(x), RelType::Int(y)) => Some(x.cmp(y)),
+        (RelType::Int(x), RelType::Float(y)) => (*x as f64).partial_cmp(y),
+        (RelType::Float(x), RelType::Int(y)) => x.partial_cmp(&(*y as f64)),
+        (RelType::Float(x), RelType::Float(y)) => x.partial_cmp(y),
+        (RelType::Str(x), RelType::Str(y)) => Some(x.cmp(y)),
+        (RelType::Bool(x), RelType::Bool(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+/// Catalogue entries of the `Array.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        // Array.* callbacks are a function value or a function name; they may
+        // declare fewer parameters than are passed.
+        ("", "Array.Map", &["arr: Array", "f: Function"], "Array", None, "f(item, index) for every item."),
+        ("", "Array.Filter", &["arr: Array", "f: Function"], "Array", None, "Items for which f(item, index) returns true."),
+        ("", "Array.Reduce", &["arr: Array", "f: Function", "init: Any"], "Any", None, "Folds the items with f(acc, item, index), starting from init."),
+        ("", "Array.SortBy", &["arr: Array", "f: Function"], "Array", None, "Stable sort by the key f(item, index); keys are numbers, Strings or Bools."),
+        ("", "Array.Find", &["arr: Array", "f: Function"], "Any", None, "First item for which f(item, index) returns true, Void if none."),
+        ("", "Array.Any", &["arr: Array", "f: Function"], "Bool", None, "Whether f(item, index) returns true for some item."),
+        ("", "Array.All", &["arr: Array", "f: Function"], "Bool", None, "Whether f(item, index) returns true for every item."),
+        ("", "Array.Slice", &["arr: Array", "start: Int", "end: Int"], "Array", None, "Items in [start, end), clamped to the Array."),
+        ("", "Array.Concat", &["a: Array", "b: Array"], "Array", None, "Items of a followed by those of b."),
+        ("", "Array.Range", &["start: Int", "end: Int"], "Array", None, "Ints in [start, end)."),
+    ]
+}
+
+impl ExecutionEngine {
+    /// Calls a script callback: a function value or the name of a function.
+    /// It receives as many of `args` as it declares parameters, so callbacks
+    /// may ignore trailing ones such as the index.
+    pub(crate) fn call_back(&mut self, native: &str, f: &RelType, args: &[RelType]) -> Result<RelType, ExecResult> {
+        let (name, params, body) = match f {
+            RelType::FnDef(name, params, body) => (name.clone(), params.clone(), body.clone()),
+            RelType::Str(name) => match self.get_var(name) {
+                Some(RelType::FnDef(_, params, body)) => (name.clone(), params, body),
+                _ => return Err(fault(native, format!("'{}' is not a function", name))),
+            },
+            other => return Err(fault(native, format!("callback must be a Function, got {}", describe(other)))),
+        };
+        if params.len() > args.len() {
+            return Err(fault(native, format!("callback '{}' takes at most {} parameters, has {}", name, args.len(), params.len())));
+        }
+        match self.invoke_function(&name, &params, &body, args[..params.len()].to_vec()) {
+            ExecResult::Value(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+
+    /// Like `call_back`, for predicates that must return a Bool.
+    fn call_predicate(&mut self, native: &str, f: &RelType, args: &[RelType]) -> Result<bool, ExecResult> {
+        match self.call_back(native, f, args)? {
+            RelType::Bool(b) => Ok(b),
+            other => Err(fault(native, format!("callback must return a Bool, got {}", describe(&other)))),
+        }
+    }
+
+    /// `Array.*` natives. They need the engine itself to run callbacks, so they
+    /// are dispatched here instead of through a `NativeModule`. Callbacks get
+    /// `(item, index)` (`(acc, item, index)` for Reduce); faults they raise are
+    /// returned unchanged.
+    pub(crate) fn array_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Array.Map" => self.array_map(name, args),
+            "Array.Filter" => self.array_filter(name, args),
+            "Array.Reduce" => self.array_reduce(name, args),
+            "Array.SortBy" => self.array_sort_by(name, args),
+            "Array.Find" => self.array_find(name, args),
+            "Array.Any" | "Array.All" => self.array_any_all(name, args),
+            "Array.Slice" => array_slice(name, args),
+            "Array.Concat" => array_concat(name, args),
+            "Array.Range" => array_range(name, args),
+            _ => return None,
+        };
+        Some(res.unwrap_or_else(|fault| fault))
+    }
+
+    fn array_map(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        let mut out = Vec::with_capacity(items.len());
+        for (i, item) in items.into_iter().enumerate() {
+            out.push(self.call_back(name, &args[1], &[item, RelType::Int(i as i64)])?);
+        }
+        Ok(ExecResult::Value(RelType::Array(out)))
+    }
+
+    fn array_filter(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        let mut out = Vec::new();
+        for (i, item) in items.into_iter().enumerate() {
+            if self.call_predicate(name, &args[1], &[item.clone(), RelType::Int(i as i64)])? {
+                out.push(item);
+            }
+        }
+        Ok(ExecResult::Value(RelType::Array(out)))
+    }
+
+    fn array_reduce(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 3)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        let mut acc = args[2].clone();
+        for (i, item) in items.into_iter().enumerate() {
+            acc = self.call_back(name, &args[1], &[acc, item, RelType::Int(i as i64)])?;
+        }
+        Ok(ExecResult::Value(acc))
+    }
+
+    /// Stable sort by the key the callback returns for each item.
+    fn array_sort_by(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        let mut keyed = Vec::with_capacity(items.len());
+        for (i, item) in items.into_iter().enumerate() {
+            keyed.push((self.call_back(name, &args[1], &[item.clone(), RelType::Int(i as i64)])?, item));
+        }
+        let mut incomparable = None;
+        keyed.sort_by(|(a, _), (b, _)| {
+            compare_keys(a, b).unwrap_or_else(|| {
+                incomparable.get_or_insert_with(|| format!("cannot compare keys {} and {}", describe(a), describe(b)));
+                Ordering::Equal
+            })
+        });
+        if let Some(msg) = incomparable {
+            return Err(fault(name, msg));
+        }
+        Ok(ExecResult::Value(RelType::Array(keyed.into_iter().map(|(_, item)| item).collect())))
+    }
+
+    /// First item the predicate accepts, Void if none does.
+    fn array_find(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        for (i, item) in items.into_iter().enumerate() {
+            if self.call_predicate(name, &args[1], &[item.clone(), RelType::Int(i as i64)])? {
+                return Ok(ExecResult::Value(item));
+            }
+        }
+        Ok(ExecResult::Value(RelType::Void))
+    }
+
+    /// Stops at the first item that decides the result.
+    fn array_any_all(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let items: Vec<RelType> = arg(name, args, 0)?;
+        let want = name == "Array.Any";
+        for (i, item) in items.into_iter().enumerate() {
+            if self.call_predicate(name, &args[1], &[item, RelType::Int(i as i64)])? == want {
+                return Ok(ExecResult::Value(RelType::Bool(want)));
+            }
+        }
+        Ok(ExecResult::Value(RelType::Bool(!want)))
+    }
+}
+
+/// Items in [start, end); both ends are clamped to the Array.
+fn array_slice(name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+    check_arity(name, args, 3)?;
+    let items: Vec<RelType> = arg(name, args, 0)?;
+    let (start, end): (i64, i64) = (arg(name, args, 1)?, arg(name, args, 2)?);
+    if start < 0 || end < 0 {
+        return Err(fault(name, format!("indices must not be negative, got {} and {}", start, end)));
+    }
+    let start = (start as usize).min(items.len());
+    let end = (end as usize).clamp(start, items.len());
+    Ok(ExecResult::Value(RelType::Array(items[start..end].to_vec())))
+}
+
+fn array_concat(name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+    check_arity(name, args, 2)?;
+    let (mut a, b): (Vec<RelType>, Vec<RelType>) = (arg(name, args, 0)?, arg(name, args, 1)?);
+    a.extend(b);
+    Ok(ExecResult::Value(RelType::Array(a)))
+}
+
+/// Ints in [start, end); empty when end <= start.
+fn array_range(name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+    check_arity(name, args, 2)?;
+    let (start, end): (i64, i64) = (arg(name, args, 0)?, arg(name, args, 1)?);
+    if end.saturating_sub(start) > MAX_RANGE_LEN {
+        return Err(fault(name, format!("range is longer than {} items", MAX_RANGE_LEN)));
+    }
+    Ok(ExecResult::Value(RelType::Array((start..end).map(RelType::Int).collect())))
+}
@@ -1,4 +1,5 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
+use crate::natives::catalogue::{Entry, FS_READ, FS_WRITE};
 
 pub trait BridgeModule: Send {
     fn handle(&self, module: &str, function: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult>;
@@ -6,6 +7,66 @@ pub trait BridgeModule: Send {
 
 pub struct CoreBridge;
 
+/// Catalogue entries of the extern modules; `registry` is also reachable through `NativeCall`.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        // ExternCall test_lib
+        ("test_lib", "calculate_hash", &["data: String"], "Int", None, "Hash of a string (FFI example)."),
+        ("test_lib", "greet_user", &["name: String"], "String", None, "Greeting for a name (FFI example)."),
+        ("test_lib", "normalize_vector", &["v: Object"], "Object", None, "Normalizes an {x, y, z} Float vector."),
+        // ExternCall ui
+        ("ui", "ui_init_window", &["width: Int", "height: Int", "title: String"], "Bool", None, "Legacy; always false. Use registry_create_window."),
+        ("ui", "ui_clear", &["color: Int"], "Void", None, "Legacy no-op."),
+        ("ui", "ui_draw_rect", &["x: Int", "y: Int", "w: Int", "h: Int", "color: Int"], "Void", None, "Legacy no-op."),
+        ("ui", "ui_draw_text", &["x: Int", "y: Int", "text: String", "color: Int"], "Void", None, "Legacy no-op."),
+        ("ui", "ui_present", &[], "Bool", None, "Legacy; always false."),
+        ("ui", "ui_is_key_down", &["key: String"], "Bool", None, "Legacy; always false."),
+        ("ui", "ui_get_key_pressed", &[], "String", None, "Legacy; always empty."),
+        // ExternCall fs
+        ("fs", "fs_read_file", &["path: String"], "String", FS_READ, "Reads a whole file."),
+        ("fs", "fs_parse_json", &["json: String"], "Any", None, "Parses JSON into script values."),
+        ("fs", "obj_has_key", &["obj: Object", "key: String"], "Bool", None, "Whether an Object has a key."),
+        ("fs", "obj_set", &["obj: Object", "key: String", "value: Any"], "Object", None, "Copy of an Object with one key set."),
+        ("fs", "obj_get", &["obj: Object", "key: String"], "Any", None, "Value of a key, Void if missing."),
+        ("fs", "array_length", &["arr: Array"], "Int", None, "Number of elements."),
+        ("fs", "array_get", &["arr: Array", "index: Int"], "Any", None, "Element at index, Void if out of range."),
+        // ExternCall registry
+        ("registry", "registry_create_counter", &[], "Handle", None, "Creates a counter resource."),
+        ("registry", "registry_increment", &["counter: Handle"], "Void", None, "Increments a counter."),
+        ("registry", "registry_get_value", &["counter: Handle"], "Int", None, "Current value of a counter."),
+        ("registry", "registry_free", &["handle: Handle"], "Void", None, "Frees a resource immediately."),
+        ("registry", "registry_retain", &["handle: Handle"], "Void", None, "Adds a reference to a resource."),
+        ("registry", "registry_release", &["handle: Handle"], "Void", None, "Drops a reference; frees at zero."),
+        ("registry", "registry_create_window", &["width: Int", "height: Int", "title: String"], "Handle", None, "Opens a GPU window."),
+        ("registry", "registry_window_update", &["win: Handle"], "Bool", None, "Presents a frame; false once the window is closed."),
+        ("registry", "registry_window_close", &["win: Handle"], "Void", None, "Closes a window."),
+        ("registry", "registry_dump", &[], "Int", None, "Prints live resources and returns their count."),
+        ("registry", "registry_file_create", &["path: String"], "Handle", FS_WRITE, "Creates (truncates) a file for writing."),
+        ("registry", "registry_file_write", &["file: Handle", "text: String"], "Void", FS_WRITE, "Writes text to a file handle."),
+        ("registry", "registry_now", &[], "Handle", None, "Current instant; virtual in deterministic mode."),
+        ("registry", "registry_elapsed_ms", &["since: Handle"], "Int", None, "Milliseconds since an instant from registry_now."),
+        ("registry", "registry_gpu_init", &[], "Handle", None, "Initializes a headless GPU context."),
+        ("registry", "registry_fill_color", &["win: Handle", "r: Int", "g: Int", "b: Int"], "Void", None, "Reserved; currently a no-op."),
+        ("registry", "registry_voxel_world_create", &["width: Int", "height: Int", "title: String"], "Handle", None, "Legacy voxel module; disabled, returns Handle<-1>."),
+        ("registry", "registry_voxel_add_block", &["world: Handle", "x: Int", "y: Int", "z: Int"], "Void", None, "Legacy no-op."),
+        ("registry", "registry_voxel_render_frame", &["world: Handle"], "Bool", None, "Legacy; always false."),
+        ("registry", "registry_texture_load", &["path: String"], "Handle", FS_READ, "Loads an image as a texture."),
+        ("registry", "registry_draw_quad_3d", &["win: Handle", "tex: Handle", "x: Number", "y: Number", "z: Number", "sx: Number", "sy: Number"], "Void", None, "Draws a textured quad."),
+        ("registry", "registry_draw_sphere", &["win: Handle", "tex: Handle", "r: Number", "rings: Int", "sectors: Int", "x: Number", "y: Number", "z: Number"], "Void", None, "Draws a textured sphere."),
+        ("registry", "registry_draw_cube", &["win: Handle", "tex: Handle", "w: Number", "h: Number", "d: Number", "x: Number", "y: Number", "z: Number"], "Void", None, "Draws a textured cube."),
+        ("registry", "registry_draw_cylinder", &["win: Handle", "tex: Handle", "r: Number", "h: Number", "segments: Int", "x: Number", "y: Number", "z: Number"], "Void", None, "Draws a textured cylinder."),
+        ("registry", "registry_set_camera", &["fov: Number", "x: Number", "y: Number", "z: Number"], "Void", None, "Sets the camera of the active window."),
+        ("registry", "registry_set_camera_for_window", &["win: Handle", "fov: Number", "x: Number", "y: Number", "z: Number"], "Void", None, "Sets the camera of a window."),
+        ("registry", "registry_is_key_pressed", &["code: Int"], "Float", None, "1.0 while a key code is held, else 0.0."),
+        ("registry", "registry_get_mouse_delta_x", &[], "Float", None, "Horizontal mouse movement since the last frame."),
+        ("registry", "registry_get_mouse_delta_y", &[], "Float", None, "Vertical mouse movement since the last frame."),
+        ("registry", "registry_get_last_char", &[], "Int", None, "Code point of the last typed character, 0 if none."),
+        ("registry", "registry_read_file", &["path: String"], "String", FS_READ, "Reads a whole file."),
+        ("registry", "registry_write_file", &["path: String", "content: String"], "Bool", FS_WRITE, "Replaces a file's content."),
+        ("registry", "registry_get_ultimate_answer", &[], "Int", None, "Returns 42 (FFI smoke test)."),
+    ]
+}
+
 impl BridgeModule for CoreBridge {
     fn handle(&self, module: &str, function: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
         if module == "test_lib" {
@@ -69,7 +130,7 @@ impl BridgeModule for CoreBridge {
                         let input_vec = crate::test_lib::Vector3 { x, y, z };
                         let out_vec = crate::test_lib::normalize_vector(input_vec);
 
-                        let mut out_map = std::collections::HashMap::new();
+                        let mut out_map = crate::executor::ObjectMap::new();
                         out_map.insert("x".to_string(), RelType::Float(out_vec.x));
                         out_map.insert("y".to_string(), RelType::Float(out_vec.y));
                         out_map.insert("z".to_string(), RelType::Float(out_vec.z));
@@ -273,7 +334,11 @@ impl BridgeModule for CoreBridge {
                     }
                     if args.len() == 1 {
                         if let RelType::Str(path) = &args[0] {
-                            let content = crate::natives::fs::fs_read_file(path.clone());
+                            let safe_path = match permissions.check_read_path(path) {
+                                Ok(p) => p.to_string_lossy().into_owned(),
+                                Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Bridge::fs.fs_read_file".into() }),
+                            };
+                            let content = crate::natives::fs::fs_read_file(safe_path);
                             return Some(ExecResult::Value(RelType::Str(content)));
                         }
                     }
@@ -480,7 +545,11 @@ impl BridgeModule for CoreBridge {
                     }
                     if args.len() == 1 {
                         if let RelType::Str(path) = &args[0] {
-                            let id = crate::natives::registry::registry_file_create(path.clone());
+                            let safe_path = match permissions.check_write_path(path) {
+                                Ok(p) => p.to_string_lossy().into_owned(),
+                                Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Bridge::registry.registry_file_create".into() }),
+                            };
+                            let id = crate::natives::registry::registry_file_create(safe_path);
                             return Some(ExecResult::Value(RelType::Handle(crate::executor::NativeHandle(id))));
                         }
                     }
@@ -604,7 +673,11 @@ impl BridgeModule for CoreBridge {
                     }
                     if args.len() == 1 {
                         if let RelType::Str(path) = &args[0] {
-                            let id = crate::natives::registry::registry_texture_load(path.clone());
+                            let safe_path = match permissions.check_read_path(path) {
+                                Ok(p) => p.to_string_lossy().into_owned(),
+                                Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Bridge::registry.registry_texture_load".into() }),
+                            };
+                            let id = crate::natives::registry::registry_texture_load(safe_path);
                             return Some(ExecResult::Value(RelType::Handle(crate::executor::NativeHandle(id))));
                         }
                     }
@@ -859,7 +932,11 @@ impl BridgeModule for CoreBridge {
                     }
                     if args.len() == 1 {
                         if let RelType::Str(path) = &args[0] {
-                            let content = crate::natives::registry::registry_read_file(path.clone());
+                            let safe_path = match permissions.check_read_path(path) {
+                                Ok(p) => p.to_string_lossy().into_owned(),
+                                Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Bridge::registry.registry_read_file".into() }),
+                            };
+                            let content = crate::natives::registry::registry_read_file(safe_path);
                             return Some(ExecResult::Value(RelType::Str(content)));
                         }
                     }
@@ -877,7 +954,11 @@ impl BridgeModule for CoreBridge {
                     }
                     if args.len() == 2 {
                         if let (RelType::Str(path), RelType::Str(content)) = (&args[0], &args[1]) {
-                            let ok = crate::natives::registry::registry_write_file(path.clone(), content.clone());
+                            let safe_path = match permissions.check_write_path(path) {
+                                Ok(p) => p.to_string_lossy().into_owned(),
+                                Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Bridge::registry.registry_write_file".into() }),
+                            };
+                            let ok = crate::natives::registry::registry_write_file(safe_path, content.clone());
                             return Some(ExecResult::Value(RelType::Bool(ok)));
                         }
                     }
@@ -0,0 +1,117 @@
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+
+/// Description of one callable native, as listed by `Native.List` and
+/// `run_knc --list-natives`.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct NativeInfo {
+    /// `NativeCall` name, or the function name of an `ExternCall`.
+    pub name: String,
+    /// `ExternCall` module; `None` for natives only reachable through `NativeCall`.
+    /// `registry` functions are reachable both ways.
+    pub module: Option<String>,
+    /// `name: Type` per parameter. `Number` accepts Int and Float.
+    pub params: Vec<String>,
+    pub returns: String,
+    /// `FS_READ`, `FS_WRITE` or `NETWORK` when a permission is required.
+    pub permission: Option<String>,
+    pub doc: String,
+}
+
+impl NativeInfo {
+    pub fn to_rel(&self) -> RelType {
+        let mut map = crate::executor::ObjectMap::new();
+        map.insert("name".to_string(), RelType::Str(self.name.clone()));
+        map.insert("module".to_string(), self.module.clone().map_or(RelType::Void, RelType::Str));
+        map.insert("params".to_string(), RelType::Array(self.params.iter().cloned().map(RelType::Str).collect()));
+        map.insert("returns".to_string(), RelType::Str(self.returns.clone()));
+        map.insert("permission".to_string(), self.permission.clone().map_or(RelType::Void, RelType::Str));
+        map.insert("doc".to_string(), RelType::Str(self.doc.clone()));
+        RelType::Object(map)
+    }
+
+    /// `module.name(params) -> returns`, for listings.
+    pub fn signature(&self) -> String {
+        let qualified = match &self.module {
+            Some(m) => format!("{}.{}", m, self.name),
+            None => self.name.clone(),
+        };
+        format!("{}({}) -> {}", qualified, self.params.join(", "), self.returns)
+    }
+}
+
+/// (module, name, params, returns, permission, doc); an empty module means `NativeCall`.
+pub(crate) type Entry = (&'static str, &'static str, &'static [&'static str], &'static str, Option<&'static str>, &'static str);
+
+pub(crate) const FS_READ: Option<&str> = Some("FS_READ");
+pub(crate) const FS_WRITE: Option<&str> = Some("FS_WRITE");
+
+/// Entry lists in listing order. Each lives next to the code that dispatches
+/// its natives, so adding a native and cataloguing it happen in one place.
+const MODULES: &[fn() -> &'static [Entry]] = &[
+    crate::natives::math::catalogue,
+    crate::natives::io::catalogue,
+    crate::natives::string::catalogue,
+    crate::natives::regex::catalogue,
+    crate::natives::json::catalogue,
+    crate::natives::datetime::catalogue,
+    crate::timers::catalogue,
+    crate::coroutines::catalogue,
+    crate::workers::catalogue,
+    crate::natives::array::catalogue,
+    catalogue,
+    crate::natives::bridge::catalogue,
+];
+
+/// Natives built into every engine.
+pub fn builtin_natives() -> Vec<NativeInfo> {
+    MODULES
+        .iter()
+        .flat_map(|catalogue| catalogue())
+        .map(|(module, name, params, returns, permission, doc)| NativeInfo {
+            name: name.to_string(),
+            module: (!module.is_empty()).then(|| module.to_string()),
+            params: params.iter().map(|p| p.to_string()).collect(),
+            returns: returns.to_string(),
+            permission: permission.map(str::to_string),
+            doc: doc.to_string(),
+        })
+        .collect()
+}
+
+/// Whether `NativeCall(name, ..)` resolves to a catalogued native.
+pub fn is_native_call(catalogue: &[NativeInfo], name: &str) -> bool {
+    catalogue.iter().any(|n| n.name == name && matches!(n.module.as_deref(), None | Some("registry")))
+}
+
+/// Whether `ExternCall { module, function, .. }` resolves to a catalogued native.
+pub fn is_extern_call(catalogue: &[NativeInfo], module: &str, function: &str) -> bool {
+    catalogue.iter().any(|n| n.name == function && n.module.as_deref() == Some(module))
+}
+
+/// Catalogue entries of the engine-level natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Native.List", &[], "Array", None, "This catalogue as Objects {name, module, params, returns, permission, doc}."),
+    ]
+}
+
+impl ExecutionEngine {
+    /// Built-in natives plus those added through `register`.
+    pub fn native_catalogue(&self) -> &[NativeInfo] {
+        &self.natives
+    }
+
+    /// Engine-level natives that need the engine itself rather than a `NativeModule`.
+    pub(crate) fn catalogue_native(&self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        match name {
+            "Native.List" if args.is_empty() => {
+                Some(ExecResult::Value(RelType::Array(self.natives.iter().map(NativeInfo::to_rel).collect())))
+            }
+            "Native.List" => Some(ExecResult::Fault {
+                msg: format!("Native.List expects 0 arguments, got {}", args.len()),
+                node: "Native::Native.List".into(),
+            }),
+            _ => None,
+        }
+    }
+}
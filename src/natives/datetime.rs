@@ -0,0 +1,266 @@
+use crate::executor::{AgentPermissions, ExecResult, ObjectMap, RelType};
+use crate::natives::NativeModule;
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::call_typed;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, FixedOffset, Months, NaiveDate, NaiveDateTime, Offset, SecondsFormat, TimeZone, Timelike, Utc};
+
+/// Date and time natives. Timestamps are Ints counting milliseconds since
+/// 1970-01-01T00:00:00Z, durations are Ints in milliseconds, so plain `+`
+/// and `-` work on both.
+pub struct DateTimeModule;
+
+/// Where "now" and the local time zone come from.
+#[derive(Clone, Copy)]
+pub struct Clock {
+    pub now_ms: i64,
+    /// Deterministic engines treat local time as UTC so runs reproduce on any machine.
+    pub local_is_utc: bool,
+}
+
+impl Clock {
+    fn system() -> Self {
+        Clock { now_ms: Utc::now().timestamp_millis(), local_is_utc: false }
+    }
+
+    fn local_offset(&self, at: &DateTime<Utc>) -> FixedOffset {
+        if self.local_is_utc {
+            Utc.fix()
+        } else {
+            chrono::Local.offset_from_utc_datetime(&at.naive_utc()).fix()
+        }
+    }
+}
+
+const MS_PER_UNIT: &[(&str, i64)] =
+    &[("ms", 1), ("s", 1_000), ("min", 60_000), ("h", 3_600_000), ("d", 86_400_000), ("w", 604_800_000)];
+
+fn instant(ts: i64) -> Result<DateTime<Utc>, String> {
+    DateTime::from_timestamp_millis(ts).ok_or_else(|| format!("timestamp {} is out of range", ts))
+}
+
+fn unit_ms(unit: &str) -> Result<i64, String> {
+    MS_PER_UNIT.iter().find(|(u, _)| *u == unit).map(|(_, ms)| *ms).ok_or_else(|| {
+        format!("unknown unit '{}' (expected ms, s, min, h, d, w, month or year)", unit)
+    })
+}
+
+fn iso(at: DateTime<Utc>, offset: FixedOffset) -> String {
+    at.with_timezone(&offset).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn components(at: DateTime<Utc>, offset: FixedOffset) -> RelType {
+    let local = at.with_timezone(&offset);
+    let mut obj = ObjectMap::new();
+    for (key, value) in [
+        ("year", local.year() as i64),
+        ("month", local.month() as i64),
+        ("day", local.day() as i64),
+        ("hour", local.hour() as i64),
+        ("minute", local.minute() as i64),
+        ("second", local.second() as i64),
+        ("millisecond", (local.nanosecond() / 1_000_000).min(999) as i64),
+        ("weekday", local.weekday().number_from_monday() as i64),
+        ("yearday", local.ordinal() as i64),
+        ("offset_minutes", (offset.local_minus_utc() / 60) as i64),
+    ] {
+        obj.insert(key.into(), RelType::Int(value));
+    }
+    RelType::Object(obj)
+}
+
+/// RFC 3339 with any offset, or a date/date-time without offset taken as UTC.
+fn parse(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Ok(dt.timestamp_millis());
+    }
+    for pattern in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, pattern) {
+            return Ok(naive.and_utc().timestamp_millis());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc().timestamp_millis());
+    }
+    Err(format!("'{}' is not an ISO-8601 date or date-time", text))
+}
+
+fn format(ts: i64, pattern: &str) -> Result<String, String> {
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+    if items.iter().any(|i| matches!(i, Item::Error)) {
+        return Err(format!("invalid format pattern '{}'", pattern));
+    }
+    let mut out = String::new();
+    use std::fmt::Write;
+    write!(out, "{}", instant(ts)?.format_with_items(items.into_iter()))
+        .map_err(|_| format!("pattern '{}' cannot be used with this timestamp", pattern))?;
+    Ok(out)
+}
+
+fn add(ts: i64, amount: i64, unit: &str) -> Result<i64, String> {
+    let overflow = || format!("{} {} from {} is out of range", amount, unit, ts);
+    let months = match unit {
+        "month" => Some(amount),
+        "year" => Some(amount.checked_mul(12).ok_or_else(overflow)?),
+        _ => None,
+    };
+    let Some(months) = months else {
+        let delta = amount.checked_mul(unit_ms(unit)?).ok_or_else(overflow)?;
+        return ts.checked_add(delta).ok_or_else(overflow);
+    };
+    // Calendar months; the day is clamped, so Jan 31 + 1 month is the last day of February.
+    let at = instant(ts)?;
+    let n = Months::new(u32::try_from(months.unsigned_abs()).map_err(|_| overflow())?);
+    let moved = if months >= 0 { at.checked_add_months(n) } else { at.checked_sub_months(n) };
+    moved.map(|d| d.timestamp_millis()).ok_or_else(overflow)
+}
+
+/// `P[nW][nD][T[nH][nM][nS]]`, optionally negative; years and months have no fixed length.
+fn parse_duration(text: &str) -> Result<i64, String> {
+    let bad = || format!("'{}' is not an ISO-8601 duration such as P1DT2H30M", text);
+    let (negative, rest) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let rest = rest.strip_prefix('P').ok_or_else(bad)?;
+    if rest.is_empty() || rest == "T" || rest.ends_with('T') {
+        return Err(bad());
+    }
+    let (date_part, time_part) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut total: f64 = 0.0;
+    for (part, units) in [(date_part, &[('W', 604_800_000.0), ('D', 86_400_000.0)][..]), (time_part, &[('H', 3_600_000.0), ('M', 60_000.0), ('S', 1_000.0)][..])] {
+        let mut number = String::new();
+        let mut last = -1i32;
+        for c in part.chars() {
+            if c.is_ascii_digit() || c == '.' {
+                number.push(c);
+                continue;
+            }
+            let pos = units.iter().position(|(u, _)| *u == c).ok_or_else(|| {
+                if matches!(c, 'Y' | 'M') { format!("'{}': years and months have no fixed length", text) } else { bad() }
+            })?;
+            if pos as i32 <= last || number.is_empty() {
+                return Err(bad());
+            }
+            last = pos as i32;
+            total += number.parse::<f64>().map_err(|_| bad())? * units[pos].1;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return Err(bad());
+        }
+    }
+    if total > i64::MAX as f64 {
+        return Err(format!("'{}' is too long", text));
+    }
+    let ms = total.round() as i64;
+    Ok(if negative { -ms } else { ms })
+}
+
+fn format_duration(ms: i64) -> String {
+    let sign = if ms < 0 { "-" } else { "" };
+    let mut rest = ms.unsigned_abs();
+    let mut out = format!("{}P", sign);
+    let days = rest / 86_400_000;
+    rest %= 86_400_000;
+    if days > 0 {
+        out.push_str(&format!("{}D", days));
+    }
+    if rest > 0 || days == 0 {
+        out.push('T');
+        let (h, m) = (rest / 3_600_000, rest % 3_600_000 / 60_000);
+        let (s, millis) = (rest % 60_000 / 1_000, rest % 1_000);
+        if h > 0 {
+            out.push_str(&format!("{}H", h));
+        }
+        if m > 0 {
+            out.push_str(&format!("{}M", m));
+        }
+        if s > 0 || millis > 0 || (h == 0 && m == 0) {
+            if millis > 0 {
+                out.push_str(format!("{}.{:03}", s, millis).trim_end_matches('0'));
+            } else {
+                out.push_str(&s.to_string());
+            }
+            out.push('S');
+        }
+    }
+    out
+}
+
+/// The natives that read the clock or the local time zone; deterministic
+/// engines answer these with their own `Clock`.
+pub fn is_clock_native(name: &str) -> bool {
+    matches!(name, "DateTime.Now" | "DateTime.NowIso" | "DateTime.NowLocalIso" | "DateTime.ToLocalIso" | "DateTime.LocalComponents")
+}
+
+/// Runs a `DateTime.*` native against `clock`. None for other names.
+pub fn datetime_native(name: &str, args: &[RelType], clock: Clock) -> Option<ExecResult> {
+    let res = match name {
+        "DateTime.Now" => call_typed(name, args, move || clock.now_ms),
+        "DateTime.NowIso" => call_typed(name, args, move || instant(clock.now_ms).map(|at| iso(at, Utc.fix()))),
+        "DateTime.NowLocalIso" => call_typed(name, args, move || instant(clock.now_ms).map(|at| iso(at, clock.local_offset(&at)))),
+        "DateTime.ToIso" => call_typed(name, args, |ts: i64| instant(ts).map(|at| iso(at, Utc.fix()))),
+        "DateTime.ToLocalIso" => call_typed(name, args, move |ts: i64| instant(ts).map(|at| iso(at, clock.local_offset(&at)))),
+        "DateTime.Parse" => call_typed(name, args, |text: String| parse(&text)),
+        "DateTime.Format" => call_typed(name, args, |ts: i64, pattern: String| format(ts, &pattern)),
+        "DateTime.Components" => call_typed(name, args, |ts: i64| instant(ts).map(|at| components(at, Utc.fix()))),
+        "DateTime.LocalComponents" => call_typed(name, args, move |ts: i64| instant(ts).map(|at| components(at, clock.local_offset(&at)))),
+        "DateTime.FromComponents" => {
+            call_typed(name, args, |year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64| {
+                let field = |v: i64| u32::try_from(v).ok();
+                i32::try_from(year)
+                    .ok()
+                    .zip(field(month).zip(field(day)))
+                    .and_then(|(y, (m, d))| NaiveDate::from_ymd_opt(y, m, d))
+                    .zip(field(hour).zip(field(minute)).zip(field(second)))
+                    .and_then(|(date, ((h, mi), s))| date.and_hms_opt(h, mi, s))
+                    .map(|dt| dt.and_utc().timestamp_millis())
+                    .ok_or_else(|| format!("{}-{}-{} {}:{}:{} is not a valid date and time", year, month, day, hour, minute, second))
+            })
+        }
+        "DateTime.Add" => call_typed(name, args, |ts: i64, amount: i64, unit: String| add(ts, amount, &unit)),
+        // Whole units between two timestamps, truncated toward zero.
+        "DateTime.Diff" => call_typed(name, args, |a: i64, b: i64, unit: String| {
+            if matches!(unit.as_str(), "month" | "year") {
+                return Err(format!("'{}' has no fixed length; use a fixed unit with Diff", unit));
+            }
+            let diff = (a as i128 - b as i128) / unit_ms(&unit)? as i128;
+            i64::try_from(diff).map_err(|_| "difference is out of range".to_string())
+        }),
+        "DateTime.ParseDuration" => call_typed(name, args, |text: String| parse_duration(&text)),
+        "DateTime.FormatDuration" => call_typed(name, args, format_duration),
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// Catalogue entries of the `DateTime.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "DateTime.Now", &[], "Int", None, "Current time in ms since the Unix epoch; virtual in deterministic mode."),
+        ("", "DateTime.NowIso", &[], "String", None, "Current UTC time as ISO-8601, e.g. 2026-10-18T09:30:00.000Z."),
+        ("", "DateTime.NowLocalIso", &[], "String", None, "Current local time as ISO-8601 with its UTC offset."),
+        ("", "DateTime.ToIso", &["ts: Int"], "String", None, "UTC ISO-8601 text of a timestamp."),
+        ("", "DateTime.ToLocalIso", &["ts: Int"], "String", None, "Local ISO-8601 text of a timestamp, with its UTC offset."),
+        ("", "DateTime.Parse", &["text: String"], "Int", None, "ISO-8601 date or date-time to a timestamp; no offset means UTC."),
+        ("", "DateTime.Format", &["ts: Int", "pattern: String"], "String", None, "Formats a timestamp in UTC with strftime codes such as %Y-%m-%d."),
+        ("", "DateTime.Components", &["ts: Int"], "Object", None, "UTC {year, month, day, hour, minute, second, millisecond, weekday, yearday, offset_minutes}."),
+        ("", "DateTime.LocalComponents", &["ts: Int"], "Object", None, "Like Components, in local time."),
+        ("", "DateTime.FromComponents", &["year: Int", "month: Int", "day: Int", "hour: Int", "minute: Int", "second: Int"], "Int", None, "Timestamp of a UTC date and time; faults on invalid dates."),
+        ("", "DateTime.Add", &["ts: Int", "amount: Int", "unit: String"], "Int", None, "Adds ms, s, min, h, d, w, month or year; months clamp the day."),
+        ("", "DateTime.Diff", &["a: Int", "b: Int", "unit: String"], "Int", None, "Whole units from b to a (ms, s, min, h, d or w), truncated."),
+        ("", "DateTime.ParseDuration", &["text: String"], "Int", None, "ISO-8601 duration such as P1DT2H30M to ms."),
+        ("", "DateTime.FormatDuration", &["ms: Int"], "String", None, "ms as an ISO-8601 duration such as P1DT2H30M."),
+    ]
+}
+
+impl NativeModule for DateTimeModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        if !func_name.starts_with("DateTime.") {
+            return None;
+        }
+        datetime_native(func_name, args, Clock::system())
+    }
+}
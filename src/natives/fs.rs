@@ -1,5 +1,4 @@
-use crate::executor::RelType;
-use std::collections::HashMap;
+use crate::executor::{ObjectMap, RelType};
 
 /// Reads a file and returns its contents as a String.
 pub fn fs_read_file(path: String) -> String {
@@ -16,20 +15,22 @@ pub fn fs_read_file(path: String) -> String {
 }
 
 /// Parses a JSON string into a nested RelType structure.
-/// - JSON Object → RelType::Object(HashMap)
+/// - JSON Object → RelType::Object (keys in document order)
 /// - JSON Array → RelType::Array(Vec)
 /// - JSON String → RelType::Str
 /// - JSON Number → RelType::Int or RelType::Float
 /// - JSON Bool → RelType::Bool
 /// - JSON Null → RelType::Void
 pub fn fs_parse_json(json_str: &str) -> RelType {
-    match serde_json::from_str::<serde_json::Value>(json_str) {
-        Ok(value) => json_value_to_reltype(&value),
-        Err(e) => {
-            eprintln!("[KnotenCore FS] JSON parse error: {}", e);
-            RelType::Void
-        }
-    }
+    try_parse_json(json_str).unwrap_or_else(|e| {
+        eprintln!("[KnotenCore FS] JSON parse error: {}", e);
+        RelType::Void
+    })
+}
+
+/// Like `fs_parse_json`, but reports invalid input instead of returning Void.
+pub fn try_parse_json(json_str: &str) -> Result<RelType, String> {
+    serde_json::from_str::<serde_json::Value>(json_str).map(|value| json_value_to_reltype(&value)).map_err(|e| e.to_string())
 }
 
 fn json_value_to_reltype(value: &serde_json::Value) -> RelType {
@@ -50,7 +51,7 @@ fn json_value_to_reltype(value: &serde_json::Value) -> RelType {
             RelType::Array(arr.iter().map(json_value_to_reltype).collect())
         }
         serde_json::Value::Object(obj) => {
-            let mut map = HashMap::new();
+            let mut map = ObjectMap::new();
             for (k, v) in obj {
                 map.insert(k.clone(), json_value_to_reltype(v));
             }
@@ -58,3 +59,40 @@ fn json_value_to_reltype(value: &serde_json::Value) -> RelType {
         }
     }
 }
+
+/// Converts a RelType into plain JSON (the inverse of `fs_parse_json`).
+/// - RelType::Void → JSON Null
+/// - Non-finite floats → JSON Null
+/// - Handles and functions → descriptive strings (e.g. "Handle<3>")
+pub fn reltype_to_json_value(value: &RelType) -> serde_json::Value {
+    match value {
+        RelType::Void => serde_json::Value::Null,
+        RelType::Bool(b) => serde_json::Value::Bool(*b),
+        RelType::Int(i) => serde_json::Value::from(*i),
+        RelType::Float(f) => serde_json::Number::from_f64(*f).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null),
+        RelType::Str(s) => serde_json::Value::String(s.clone()),
+        RelType::Array(arr) => serde_json::Value::Array(arr.iter().map(reltype_to_json_value).collect()),
+        RelType::Object(map) => {
+            serde_json::Value::Object(map.iter().map(|(k, v)| (k.clone(), reltype_to_json_value(v))).collect())
+        }
+        RelType::Handle(_) | RelType::FnDef(..) | RelType::Call(..) => serde_json::Value::String(value.to_string()),
+    }
+}
+
+/// Strict variant of `reltype_to_json_value` for data that has to parse back
+/// to the same value: non-finite floats, handles and functions are errors.
+pub fn try_reltype_to_json_value(value: &RelType) -> Result<serde_json::Value, String> {
+    Ok(match value {
+        RelType::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("{} cannot be represented in JSON", f))?,
+        RelType::Array(arr) => serde_json::Value::Array(arr.iter().map(try_reltype_to_json_value).collect::<Result<_, _>>()?),
+        RelType::Object(map) => serde_json::Value::Object(
+            map.iter().map(|(k, v)| Ok((k.clone(), try_reltype_to_json_value(v)?))).collect::<Result<_, String>>()?,
+        ),
+        RelType::Handle(_) | RelType::FnDef(..) | RelType::Call(..) => {
+            return Err(format!("{} cannot be represented in JSON", crate::natives::typed::describe(value)));
+        }
+        RelType::Void | RelType::Bool(_) | RelType::Int(_) | RelType::Str(_) => reltype_to_json_value(value),
+    })
+}
@@ -1,8 +1,19 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
 use crate::natives::NativeModule;
+use crate::natives::catalogue::{Entry, FS_READ, FS_WRITE};
 
 pub struct IoModule;
 
+/// Catalogue entries of the `IO.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "IO.WriteFile", &["path: String", "content: String"], "Bool", FS_WRITE, "Replaces a file's content; false on I/O errors."),
+        ("", "IO.ReadFile", &["path: String"], "String", FS_READ, "Reads a whole file; empty on I/O errors."),
+        ("", "IO.AppendFile", &["path: String", "content: String"], "Bool", FS_WRITE, "Appends to a file, creating it if needed."),
+        ("", "IO.FileExists", &["path: String"], "Bool", FS_READ, "Whether the path exists."),
+    ]
+}
+
 impl NativeModule for IoModule {
     fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
         match func_name {
@@ -20,7 +31,11 @@ impl NativeModule for IoModule {
                     });
                 }
                 if let (RelType::Str(path), RelType::Str(content)) = (&args[0], &args[1]) {
-                    match std::fs::write(path, content) {
+                    let safe_path = match permissions.check_write_path(path) {
+                        Ok(p) => p,
+                        Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Native::IO.WriteFile".into() }),
+                    };
+                    match std::fs::write(&safe_path, content) {
                         Ok(_) => Some(ExecResult::Value(RelType::Bool(true))),
                         Err(_) => Some(ExecResult::Value(RelType::Bool(false))),
                     }
@@ -45,7 +60,17 @@ impl NativeModule for IoModule {
                     });
                 }
                 if let RelType::Str(path) = &args[0] {
-                    match std::fs::read_to_string(path) {
+                    // A missing file inside the granted directories reads as "", like any
+                    // other I/O error; existing files are canonicalized so symlinks cannot
+                    // lead out of them.
+                    let checked = permissions
+                        .check_lookup_path(path)
+                        .and_then(|p| if p.exists() { permissions.check_read_path(path) } else { Ok(p) });
+                    let safe_path = match checked {
+                        Ok(p) => p,
+                        Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Native::IO.ReadFile".into() }),
+                    };
+                    match std::fs::read_to_string(&safe_path) {
                         Ok(content) => Some(ExecResult::Value(RelType::Str(content))),
                         Err(_) => Some(ExecResult::Value(RelType::Str("".to_string()))),
                     }
@@ -71,10 +96,14 @@ impl NativeModule for IoModule {
                 }
                 if let (RelType::Str(path), RelType::Str(content)) = (&args[0], &args[1]) {
                     use std::io::Write;
+                    let safe_path = match permissions.check_write_path(path) {
+                        Ok(p) => p,
+                        Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Native::IO.AppendFile".into() }),
+                    };
                     let mut file = match std::fs::OpenOptions::new()
                         .append(true)
                         .create(true)
-                        .open(path)
+                        .open(&safe_path)
                     {
                         Ok(f) => f,
                         Err(_) => return Some(ExecResult::Value(RelType::Bool(false))),
@@ -104,9 +133,11 @@ impl NativeModule for IoModule {
                     });
                 }
                 if let RelType::Str(path) = &args[0] {
-                    Some(ExecResult::Value(RelType::Bool(
-                        std::path::Path::new(path).exists(),
-                    )))
+                    let safe_path = match permissions.check_lookup_path(path) {
+                        Ok(p) => p,
+                        Err(e) => return Some(ExecResult::Fault { msg: format!("Security: {}", e), node: "Native::IO.FileExists".into() }),
+                    };
+                    Some(ExecResult::Value(RelType::Bool(safe_path.exists())))
                 } else {
                     Some(ExecResult::Fault {
                         msg: "IO.FileExists expects a String".to_string(),
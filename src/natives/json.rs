@@ -0,0 +1,38 @@
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::catalogue::Entry;
+use crate::natives::fs::{try_parse_json, try_reltype_to_json_value};
+use crate::natives::typed::call_typed;
+
+/// `JSON.Stringify` / `JSON.Parse`. Void is `null`, Ints stay Ints and Floats
+/// keep a decimal point, so values round-trip unchanged; object keys keep
+/// their order.
+pub struct JsonModule;
+
+fn stringify(value: &RelType, pretty: bool) -> Result<String, String> {
+    let json = try_reltype_to_json_value(value)?;
+    let text = if pretty { serde_json::to_string_pretty(&json) } else { serde_json::to_string(&json) };
+    text.map_err(|e| e.to_string())
+}
+
+/// Catalogue entries of the `JSON.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "JSON.Stringify", &["value: Any", "pretty?: Bool"], "String", None, "JSON text of a value; Void is null. Faults on NaN, Infinity, Handles and functions."),
+        ("", "JSON.Parse", &["json: String"], "Any", None, "Parses JSON text; null becomes Void. Faults on invalid input."),
+    ]
+}
+
+impl NativeModule for JsonModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        match func_name {
+            // `pretty` is optional.
+            "JSON.Stringify" if args.len() == 2 => {
+                Some(call_typed(func_name, args, |value: RelType, pretty: bool| stringify(&value, pretty)))
+            }
+            "JSON.Stringify" => Some(call_typed(func_name, args, |value: RelType| stringify(&value, false))),
+            "JSON.Parse" => Some(call_typed(func_name, args, |text: String| try_parse_json(&text))),
+            _ => None,
+        }
+    }
+}
@@ -1,33 +1,29 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
 use crate::natives::NativeModule;
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::call_typed;
 use noise::{NoiseFn, Perlin};
 
 pub struct MathModule;
 
+/// Catalogue entries of the `Math.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Math.Random", &[], "Float", None, "Uniform random number in [0, 1); seeded in deterministic mode."),
+        ("", "Math.Sin", &["x: Number"], "Float", None, "Sine of x (radians)."),
+        ("", "Math.Cos", &["x: Number"], "Float", None, "Cosine of x (radians)."),
+        ("", "Math.Floor", &["x: Number"], "Number", None, "Rounds down; Ints are returned unchanged."),
+        ("", "Math.Ceil", &["x: Number"], "Number", None, "Rounds up; Ints are returned unchanged."),
+        ("", "Math.Perlin2D", &["x: Number", "y: Number"], "Float", None, "2D Perlin noise at (x, y)."),
+    ]
+}
+
 impl NativeModule for MathModule {
     fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
         match func_name {
             "Math.Random" => Some(ExecResult::Value(RelType::Float(rand::random::<f64>()))),
-            "Math.Sin" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault { msg: "Math.Sin expects 1 argument".to_string(), node: "Native::Math.Sin".into() });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.sin()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Float((i as f64).sin()))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Sin expects a Number".to_string(), node: "Native::Math.Sin".into() }),
-                }
-            }
-            "Math.Cos" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault { msg: "Math.Cos expects 1 argument".to_string(), node: "Native::Math.Cos".into() });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.cos()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Float((i as f64).cos()))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Cos expects a Number".to_string(), node: "Native::Math.Cos".into() }),
-                }
-            }
+            "Math.Sin" => Some(call_typed(func_name, args, |x: f64| x.sin())),
+            "Math.Cos" => Some(call_typed(func_name, args, |x: f64| x.cos())),
             "Math.Floor" => {
                 if args.len() != 1 {
                     return Some(ExecResult::Fault {
@@ -54,38 +50,41 @@ impl NativeModule for MathModule {
                     _ => Some(ExecResult::Fault { msg: "Math.Ceil expects a Number".to_string(), node: "Native::Math.Ceil".into() }),
                 }
             }
-            "Math.Perlin2D" => {
-                if args.len() != 2 {
-                    return Some(ExecResult::Fault {
-                        msg: "Math.Perlin2D expects 2 arguments (x, y)".to_string(),
-                        node: "Native::Math.Perlin2D".into()
-                    });
-                }
-                let x = match args[0] {
-                    RelType::Float(f) => f,
-                    RelType::Int(i) => i as f64,
-                    _ => {
-                        return Some(ExecResult::Fault {
-                            msg: "Math.Perlin2D arg 1 must be a Number".to_string(),
-                            node: "Native::Math.Perlin2D".into()
-                        });
-                    }
-                };
-                let y = match args[1] {
-                    RelType::Float(f) => f,
-                    RelType::Int(i) => i as f64,
-                    _ => {
-                        return Some(ExecResult::Fault {
-                            msg: "Math.Perlin2D arg 2 must be a Number".to_string(),
-                            node: "Native::Math.Perlin2D".into()
-                        });
-                    }
-                };
-                let perlin = Perlin::new(1); // Explicit seed for stability
-                let val = perlin.get([x, y]);
-                Some(ExecResult::Value(RelType::Float(val)))
-            }
+            "Math.Perlin2D" => Some(perlin2d(args, 1)), // Explicit seed for stability
             _ => None,
         }
     }
 }
+
+/// 2D Perlin noise at (x, y). `MathModule` always uses seed 1; deterministic
+/// engines pass their own seed.
+pub fn perlin2d(args: &[RelType], seed: u32) -> ExecResult {
+    if args.len() != 2 {
+        return ExecResult::Fault {
+            msg: "Math.Perlin2D expects 2 arguments (x, y)".to_string(),
+            node: "Native::Math.Perlin2D".into()
+        };
+    }
+    let x = match args[0] {
+        RelType::Float(f) => f,
+        RelType::Int(i) => i as f64,
+        _ => {
+            return ExecResult::Fault {
+                msg: "Math.Perlin2D arg 1 must be a Number".to_string(),
+                node: "Native::Math.Perlin2D".into()
+            };
+        }
+    };
+    let y = match args[1] {
+        RelType::Float(f) => f,
+        RelType::Int(i) => i as f64,
+        _ => {
+            return ExecResult::Fault {
+                msg: "Math.Perlin2D arg 2 must be a Number".to_string(),
+                node: "Native::Math.Perlin2D".into()
+            };
+        }
+    };
+    let perlin = Perlin::new(seed);
+    ExecResult::Value(RelType::Float(perlin.get([x, y])))
+}
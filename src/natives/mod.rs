@@ -1,10 +1,17 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
 
+pub mod array;
 pub mod bridge;
+pub mod catalogue;
+pub mod datetime;
 pub mod fs;
 pub mod io;
+pub mod json;
 pub mod math;
+pub mod regex;
 pub mod registry;
+pub mod string;
+pub mod typed;
 pub mod ui;
 
 pub trait NativeModule: Send {
@@ -0,0 +1,162 @@
+use crate::executor::{AgentPermissions, ExecResult, ObjectMap, RelType};
+use crate::natives::NativeModule;
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::call_typed;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Longest accepted pattern, in bytes.
+const MAX_PATTERN_LEN: usize = 4096;
+/// Memory limit for one compiled program and for its lazy DFA cache.
+const MAX_COMPILED_SIZE: usize = 1 << 20;
+/// Compiled patterns kept per engine; the cache starts over once full.
+const MAX_CACHED: usize = 128;
+/// Most matches `Regex.FindAll` returns and parts `Regex.Split` produces.
+const MAX_RESULTS: usize = 100_000;
+/// Longest string `Regex.Replace` builds, in bytes.
+const MAX_RESULT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Regular expression natives (Rust `regex` syntax). Matching runs in time
+/// linear in the input, and the limits above bound pattern size, memory,
+/// result counts and replaced text. Indices in results count chars, like the
+/// `String` natives.
+#[derive(Default)]
+pub struct RegexModule {
+    cache: RefCell<HashMap<String, ::regex::Regex>>,
+}
+
+impl RegexModule {
+    fn compile(&self, pattern: &str) -> Result<::regex::Regex, String> {
+        if let Some(re) = self.cache.borrow().get(pattern) {
+            return Ok(re.clone());
+        }
+        if pattern.len() > MAX_PATTERN_LEN {
+            return Err(format!("pattern is longer than {} bytes", MAX_PATTERN_LEN));
+        }
+        let re = ::regex::RegexBuilder::new(pattern)
+            .size_limit(MAX_COMPILED_SIZE)
+            .dfa_size_limit(MAX_COMPILED_SIZE)
+            .build()
+            .map_err(|e| format!("invalid pattern: {}", e))?;
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        cache.insert(pattern.to_string(), re.clone());
+        Ok(re)
+    }
+}
+
+/// Converts match byte offsets to char indices. Successive matches start at
+/// increasing offsets, so each call only counts the chars since the last one.
+struct CharIndex<'t> {
+    text: &'t str,
+    byte: usize,
+    chars: usize,
+}
+
+impl<'t> CharIndex<'t> {
+    fn new(text: &'t str) -> Self {
+        CharIndex { text, byte: 0, chars: 0 }
+    }
+
+    fn at(&mut self, byte: usize) -> usize {
+        self.chars += self.text[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+}
+
+/// `{text, index, groups, named}`: the whole match, its char index, the
+/// numbered groups (Void where a group did not take part) and the named groups.
+fn match_to_rel(re: &::regex::Regex, index: &mut CharIndex, caps: &::regex::Captures) -> RelType {
+    let whole = caps.get(0).expect("group 0 always participates");
+    let group = |m: Option<::regex::Match>| m.map_or(RelType::Void, |m| RelType::Str(m.as_str().to_string()));
+    let mut named = ObjectMap::new();
+    for name in re.capture_names().flatten() {
+        named.insert(name.to_string(), group(caps.name(name)));
+    }
+    let mut obj = ObjectMap::new();
+    obj.insert("text".into(), RelType::Str(whole.as_str().to_string()));
+    obj.insert("index".into(), RelType::Int(index.at(whole.start()) as i64));
+    obj.insert("groups".into(), RelType::Array(caps.iter().skip(1).map(group).collect()));
+    obj.insert("named".into(), RelType::Object(named));
+    RelType::Object(obj)
+}
+
+/// Catalogue entries of the `Regex.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Regex.Match", &["pattern: String", "text: String"], "Any", None, "First match as {text, index, groups, named}, Void if none."),
+        ("", "Regex.FindAll", &["pattern: String", "text: String"], "Array", None, "Every non-overlapping match as {text, index, groups, named}."),
+        ("", "Regex.Replace", &["pattern: String", "text: String", "replacement: String"], "String", None, "Replaces every match; $1 and ${name} refer to groups. Faults past 64 MiB."),
+        ("", "Regex.Split", &["pattern: String", "text: String"], "Array", None, "Parts between matches."),
+    ]
+}
+
+impl NativeModule for RegexModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        if !func_name.starts_with("Regex.") {
+            return None;
+        }
+        // Compiled up front: typed closures are 'static and cannot borrow the cache.
+        // A non-String pattern is reported by `call_typed` before `re` is used.
+        let re = match args.first() {
+            Some(RelType::Str(pattern)) => self.compile(pattern),
+            _ => Err(String::new()),
+        };
+        let res = match func_name {
+            // First match, Void if there is none.
+            "Regex.Match" => call_typed(func_name, args, move |_pattern: String, text: String| {
+                let re = re.clone()?;
+                Ok::<_, String>(re.captures(&text).map_or(RelType::Void, |caps| match_to_rel(&re, &mut CharIndex::new(&text), &caps)))
+            }),
+            "Regex.FindAll" => call_typed(func_name, args, move |_pattern: String, text: String| {
+                let re = re.clone()?;
+                let mut index = CharIndex::new(&text);
+                let mut found = Vec::new();
+                for caps in re.captures_iter(&text) {
+                    if found.len() == MAX_RESULTS {
+                        return Err(format!("more than {} matches", MAX_RESULTS));
+                    }
+                    found.push(match_to_rel(&re, &mut index, &caps));
+                }
+                Ok(found)
+            }),
+            // `$1` and `${name}` in the replacement refer to groups.
+            "Regex.Replace" => call_typed(func_name, args, move |_pattern: String, text: String, replacement: String| {
+                let re = re.clone()?;
+                // Built match by match so an empty pattern cannot multiply the input unchecked.
+                let mut out = String::new();
+                let mut last = 0;
+                for caps in re.captures_iter(&text) {
+                    let m = caps.get(0).unwrap();
+                    out.push_str(&text[last..m.start()]);
+                    caps.expand(&replacement, &mut out);
+                    last = m.end();
+                    if out.len() > MAX_RESULT_BYTES {
+                        return Err("result would exceed 64 MiB".to_string());
+                    }
+                }
+                out.push_str(&text[last..]);
+                if out.len() > MAX_RESULT_BYTES {
+                    return Err("result would exceed 64 MiB".to_string());
+                }
+                Ok::<_, String>(out)
+            }),
+            "Regex.Split" => call_typed(func_name, args, move |_pattern: String, text: String| {
+                let re = re.clone()?;
+                let mut parts = Vec::new();
+                for part in re.split(&text) {
+                    if parts.len() == MAX_RESULTS {
+                        return Err(format!("more than {} parts", MAX_RESULTS));
+                    }
+                    parts.push(part.to_string());
+                }
+                Ok(parts)
+            }),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
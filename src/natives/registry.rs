@@ -143,6 +143,7 @@ pub enum NativeHandle {
     Window(WindowProxy),
     File(File),
     Timestamp(std::time::Instant),
+    VirtualTimestamp(i64), // Milliseconds on an engine's deterministic clock
     GpuContext(GpuContext),
     VoxelWorld(SendVoxelWorld),
     Texture(TextureAsset),
@@ -418,6 +419,7 @@ pub fn registry_dump() -> i64 {
                 NativeHandle::Window(_) => "Window",
                 NativeHandle::File(_) => "File",
                 NativeHandle::Timestamp(_) => "Timestamp",
+                NativeHandle::VirtualTimestamp(_) => "VirtualTimestamp",
                 NativeHandle::GpuContext(_) => "GpuContext",
                 NativeHandle::VoxelWorld(SendVoxelWorld(s)) => {
                     println!("      voxels={}, {}x{}", s.voxels.len(), s.width, s.height);
@@ -442,23 +444,96 @@ pub fn registry_dump() -> i64 {
 // ── Timestamp Orchestration ────────────────────────────────────────
 
 pub fn registry_now() -> i64 {
+    registry_insert_handle(NativeHandle::Timestamp(std::time::Instant::now()))
+}
+
+/// Deterministic-mode `registry_now`: records `now_ms` of the engine's virtual clock.
+pub fn registry_now_virtual(now_ms: i64) -> i64 {
+    registry_insert_handle(NativeHandle::VirtualTimestamp(now_ms))
+}
+
+fn registry_insert_handle(handle: NativeHandle) -> i64 {
     let mut id_guard = COUNTER_NEXT_ID.lock().unwrap_or_else(|e| e.into_inner());
     let id = *id_guard;
     *id_guard += 1;
 
     with_registry(|registry| {
-        registry.insert(
-            id,
-            RegistryEntry {
-                handle: NativeHandle::Timestamp(std::time::Instant::now()),
-                ref_count: 1,
-            },
-        );
+        registry.insert(id, RegistryEntry { handle, ref_count: 1 });
     });
 
     id as i64
 }
 
+// ── Snapshot / Restore ─────────────────────────────────────────────
+
+/// Serializable form of a registry entry. Windows, files, GPU contexts and
+/// textures hold OS resources and are only recorded by kind.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum HandleSnapshot {
+    Counter(i64),
+    Timestamp { elapsed_ms: u64 },
+    VirtualTimestamp(i64),
+    VoxelWorld { width: usize, height: usize, voxels: Vec<[i32; 3]> },
+    Unrestorable(String),
+}
+
+pub fn registry_snapshot_handle(handle_id: i64) -> Option<HandleSnapshot> {
+    if handle_id < 0 {
+        return None;
+    }
+    with_registry(|registry| {
+        let entry = registry.get(&(handle_id as usize))?;
+        Some(match &entry.handle {
+            NativeHandle::Counter(c) => HandleSnapshot::Counter(c.count),
+            NativeHandle::Timestamp(t) => HandleSnapshot::Timestamp { elapsed_ms: t.elapsed().as_millis() as u64 },
+            NativeHandle::VirtualTimestamp(t) => HandleSnapshot::VirtualTimestamp(*t),
+            NativeHandle::VoxelWorld(SendVoxelWorld(w)) => {
+                HandleSnapshot::VoxelWorld { width: w.width, height: w.height, voxels: w.voxels.clone() }
+            }
+            NativeHandle::Window(_) => HandleSnapshot::Unrestorable("Window".into()),
+            NativeHandle::File(_) => HandleSnapshot::Unrestorable("File".into()),
+            NativeHandle::GpuContext(_) => HandleSnapshot::Unrestorable("GpuContext".into()),
+            NativeHandle::Texture(_) => HandleSnapshot::Unrestorable("Texture".into()),
+        })
+    })
+}
+
+/// Re-creates a snapshotted entry under a fresh id with the given reference
+/// count. Returns -1 for unrestorable kinds.
+pub fn registry_restore_handle(snapshot: &HandleSnapshot, ref_count: usize) -> i64 {
+    let handle = match snapshot {
+        HandleSnapshot::Counter(count) => NativeHandle::Counter(StatefulCounter { count: *count }),
+        HandleSnapshot::Timestamp { elapsed_ms } => {
+            let now = std::time::Instant::now();
+            NativeHandle::Timestamp(now.checked_sub(std::time::Duration::from_millis(*elapsed_ms)).unwrap_or(now))
+        }
+        HandleSnapshot::VirtualTimestamp(t) => NativeHandle::VirtualTimestamp(*t),
+        HandleSnapshot::VoxelWorld { width, height, voxels } => {
+            NativeHandle::VoxelWorld(SendVoxelWorld(VoxelWorldState { width: *width, height: *height, voxels: voxels.clone() }))
+        }
+        HandleSnapshot::Unrestorable(_) => return -1,
+    };
+    let id = registry_insert_handle(handle);
+    with_registry(|registry| {
+        if let Some(entry) = registry.get_mut(&(id as usize)) {
+            entry.ref_count = ref_count;
+        }
+    });
+    id
+}
+
+/// Deterministic-mode `registry_elapsed_ms`: virtual milliseconds between the
+/// timestamp and `now_ms`. Returns -1 for anything but a virtual timestamp.
+pub fn registry_elapsed_ms_virtual(handle_id: i64, now_ms: i64) -> i64 {
+    if handle_id < 0 {
+        return 0;
+    }
+    with_registry(|registry| match registry.get(&(handle_id as usize)).map(|e| &e.handle) {
+        Some(NativeHandle::VirtualTimestamp(t)) => now_ms - t,
+        _ => -1,
+    })
+}
+
 pub fn registry_elapsed_ms(handle_id: i64) -> i64 {
     if handle_id < 0 {
         return 0;
@@ -0,0 +1,87 @@
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::call_typed;
+
+/// String natives. Lengths and indices count Unicode scalar values (chars),
+/// not bytes, so `String.Substring("größe", 0, 3)` is `"grö"`.
+pub struct StringModule;
+
+/// Upper bound for `String.Repeat`, so a script cannot exhaust host memory.
+const MAX_REPEAT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Byte offset of the char at `idx`, or the string length when `idx` is past the end.
+fn byte_offset(s: &str, idx: usize) -> usize {
+    s.char_indices().nth(idx).map_or(s.len(), |(b, _)| b)
+}
+
+fn non_negative(what: &str, v: i64) -> Result<usize, String> {
+    usize::try_from(v).map_err(|_| format!("{} must not be negative, got {}", what, v))
+}
+
+/// Catalogue entries of the `String.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "String.Len", &["s: String"], "Int", None, "Number of chars (Unicode scalar values)."),
+        ("", "String.Split", &["s: String", "sep: String"], "Array", None, "Parts between separators; an empty separator splits into chars."),
+        ("", "String.Join", &["items: Array", "sep: String"], "String", None, "Elements as printed, joined with a separator."),
+        ("", "String.Trim", &["s: String"], "String", None, "Removes leading and trailing whitespace."),
+        ("", "String.Replace", &["s: String", "from: String", "to: String"], "String", None, "Replaces every occurrence of a non-empty pattern."),
+        ("", "String.Find", &["s: String", "needle: String"], "Int", None, "Char index of the first match, -1 if none."),
+        ("", "String.Substring", &["s: String", "start: Int", "end: Int"], "String", None, "Chars in [start, end), clamped to the string."),
+        ("", "String.ToUpper", &["s: String"], "String", None, "Uppercase copy."),
+        ("", "String.ToLower", &["s: String"], "String", None, "Lowercase copy."),
+        ("", "String.StartsWith", &["s: String", "prefix: String"], "Bool", None, "Whether s begins with prefix."),
+        ("", "String.EndsWith", &["s: String", "suffix: String"], "Bool", None, "Whether s ends with suffix."),
+        ("", "String.Chars", &["s: String"], "Array", None, "One single-char String per char."),
+        ("", "String.Repeat", &["s: String", "count: Int"], "String", None, "s repeated count times."),
+    ]
+}
+
+impl NativeModule for StringModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        let res = match func_name {
+            "String.Len" => call_typed(func_name, args, |s: String| s.chars().count() as i64),
+            "String.Split" => call_typed(func_name, args, |s: String, sep: String| -> Vec<String> {
+                if sep.is_empty() {
+                    s.chars().map(String::from).collect()
+                } else {
+                    s.split(sep.as_str()).map(String::from).collect()
+                }
+            }),
+            // Elements are converted like `Print` shows them, so numbers can be joined too.
+            "String.Join" => call_typed(func_name, args, |items: Vec<RelType>, sep: String| {
+                items.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(&sep)
+            }),
+            "String.Trim" => call_typed(func_name, args, |s: String| s.trim().to_string()),
+            "String.Replace" => call_typed(func_name, args, |s: String, from: String, to: String| {
+                if from.is_empty() { Err("pattern must not be empty") } else { Ok(s.replace(&from, &to)) }
+            }),
+            // Char index of the first match, -1 if there is none.
+            "String.Find" => call_typed(func_name, args, |s: String, needle: String| {
+                s.find(&needle).map_or(-1, |b| s[..b].chars().count() as i64)
+            }),
+            // Chars in [start, end); both ends are clamped to the string.
+            "String.Substring" => call_typed(func_name, args, |s: String, start: i64, end: i64| {
+                let (start, end) = (non_negative("start", start)?, non_negative("end", end)?);
+                let from = byte_offset(&s, start);
+                let to = byte_offset(&s, end.max(start));
+                Ok::<_, String>(s[from..to].to_string())
+            }),
+            "String.ToUpper" => call_typed(func_name, args, |s: String| s.to_uppercase()),
+            "String.ToLower" => call_typed(func_name, args, |s: String| s.to_lowercase()),
+            "String.StartsWith" => call_typed(func_name, args, |s: String, prefix: String| s.starts_with(&prefix)),
+            "String.EndsWith" => call_typed(func_name, args, |s: String, suffix: String| s.ends_with(&suffix)),
+            "String.Chars" => call_typed(func_name, args, |s: String| s.chars().map(String::from).collect::<Vec<_>>()),
+            "String.Repeat" => call_typed(func_name, args, |s: String, n: i64| {
+                let n = non_negative("count", n)?;
+                if s.len().checked_mul(n).is_none_or(|len| len > MAX_REPEAT_BYTES) {
+                    return Err("result would exceed 64 MiB".to_string());
+                }
+                Ok::<_, String>(s.repeat(n))
+            }),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
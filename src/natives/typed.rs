@@ -0,0 +1,298 @@
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, RelType};
+use crate::natives::NativeModule;
+use crate::natives::catalogue::NativeInfo;
+use indexmap::IndexMap;
+
+/// Script type of a value with its article, as used in fault messages.
+pub fn describe(v: &RelType) -> &'static str {
+    match v {
+        RelType::Int(_) => "an Int",
+        RelType::Float(_) => "a Float",
+        RelType::Bool(_) => "a Bool",
+        RelType::Str(_) => "a String",
+        RelType::Array(_) => "an Array",
+        RelType::Object(_) => "an Object",
+        RelType::Handle(_) => "a Handle",
+        RelType::FnDef(..) | RelType::Call(..) => "a Function",
+        RelType::Void => "Void",
+    }
+}
+
+/// Conversion of a native argument into a Rust value.
+pub trait FromRelType: Sized {
+    /// Expected script type with its article, e.g. "a Number".
+    const EXPECTED: &'static str;
+    fn from_rel(v: &RelType) -> Option<Self>;
+}
+
+/// Conversion of a Rust return value into a script value.
+pub trait IntoRelType {
+    /// Script type name shown in the native catalogue.
+    const TYPE_NAME: &'static str;
+    fn into_rel(self) -> RelType;
+}
+
+/// What a native closure may return: any `IntoRelType`, or a `Result` whose
+/// error becomes a Fault.
+pub trait IntoNativeResult {
+    const TYPE_NAME: &'static str;
+    fn into_result(self, name: &str) -> ExecResult;
+}
+
+impl FromRelType for f64 {
+    const EXPECTED: &'static str = "a Number";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        match v {
+            RelType::Float(f) => Some(*f),
+            RelType::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromRelType for f32 {
+    const EXPECTED: &'static str = "a Number";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        f64::from_rel(v).map(|f| f as f32)
+    }
+}
+
+impl FromRelType for i64 {
+    const EXPECTED: &'static str = "an Int";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        if let RelType::Int(i) = v { Some(*i) } else { None }
+    }
+}
+
+impl FromRelType for bool {
+    const EXPECTED: &'static str = "a Bool";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        if let RelType::Bool(b) = v { Some(*b) } else { None }
+    }
+}
+
+impl FromRelType for String {
+    const EXPECTED: &'static str = "a String";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        if let RelType::Str(s) = v { Some(s.clone()) } else { None }
+    }
+}
+
+/// Accepts any value unchanged.
+impl FromRelType for RelType {
+    const EXPECTED: &'static str = "Any";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        Some(v.clone())
+    }
+}
+
+impl<T: FromRelType> FromRelType for Vec<T> {
+    const EXPECTED: &'static str = "an Array";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        if let RelType::Array(items) = v { items.iter().map(T::from_rel).collect() } else { None }
+    }
+}
+
+/// Objects convert to and from `IndexMap`s (`ObjectMap` for `RelType` values),
+/// so key order survives the round trip through a native.
+impl<T: FromRelType> FromRelType for IndexMap<String, T> {
+    const EXPECTED: &'static str = "an Object";
+    fn from_rel(v: &RelType) -> Option<Self> {
+        if let RelType::Object(map) = v {
+            map.iter().map(|(k, v)| T::from_rel(v).map(|v| (k.clone(), v))).collect()
+        } else {
+            None
+        }
+    }
+}
+
+impl IntoRelType for () {
+    const TYPE_NAME: &'static str = "Void";
+    fn into_rel(self) -> RelType { RelType::Void }
+}
+impl IntoRelType for f64 {
+    const TYPE_NAME: &'static str = "Float";
+    fn into_rel(self) -> RelType { RelType::Float(self) }
+}
+impl IntoRelType for f32 {
+    const TYPE_NAME: &'static str = "Float";
+    fn into_rel(self) -> RelType { RelType::Float(self as f64) }
+}
+impl IntoRelType for i64 {
+    const TYPE_NAME: &'static str = "Int";
+    fn into_rel(self) -> RelType { RelType::Int(self) }
+}
+impl IntoRelType for bool {
+    const TYPE_NAME: &'static str = "Bool";
+    fn into_rel(self) -> RelType { RelType::Bool(self) }
+}
+impl IntoRelType for String {
+    const TYPE_NAME: &'static str = "String";
+    fn into_rel(self) -> RelType { RelType::Str(self) }
+}
+impl IntoRelType for &str {
+    const TYPE_NAME: &'static str = "String";
+    fn into_rel(self) -> RelType { RelType::Str(self.to_string()) }
+}
+impl IntoRelType for RelType {
+    const TYPE_NAME: &'static str = "Any";
+    fn into_rel(self) -> RelType { self }
+}
+impl<T: IntoRelType> IntoRelType for Vec<T> {
+    const TYPE_NAME: &'static str = "Array";
+    fn into_rel(self) -> RelType { RelType::Array(self.into_iter().map(T::into_rel).collect()) }
+}
+impl<T: IntoRelType> IntoRelType for IndexMap<String, T> {
+    const TYPE_NAME: &'static str = "Object";
+    fn into_rel(self) -> RelType { RelType::Object(self.into_iter().map(|(k, v)| (k, v.into_rel())).collect()) }
+}
+/// `None` becomes Void.
+impl<T: IntoRelType> IntoRelType for Option<T> {
+    const TYPE_NAME: &'static str = "Any";
+    fn into_rel(self) -> RelType { self.map_or(RelType::Void, T::into_rel) }
+}
+
+impl<T: IntoRelType> IntoNativeResult for T {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+    fn into_result(self, _name: &str) -> ExecResult {
+        ExecResult::Value(self.into_rel())
+    }
+}
+
+impl<T: IntoRelType, E: std::fmt::Display> IntoNativeResult for Result<T, E> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+    fn into_result(self, name: &str) -> ExecResult {
+        match self {
+            Ok(v) => ExecResult::Value(v.into_rel()),
+            Err(e) => ExecResult::Fault { msg: format!("{}: {}", name, e), node: format!("Native::{}", name) },
+        }
+    }
+}
+
+/// A Rust closure callable as a native. Implemented for `Fn`s of up to six
+/// `FromRelType` arguments; `Args` is the argument tuple and only guides inference.
+pub trait NativeFn<Args>: Send + Sync + 'static {
+    fn arity(&self) -> usize;
+    /// Parameter types and return type, as listed in the native catalogue.
+    fn signature(&self) -> (Vec<&'static str>, &'static str);
+    fn call(&self, name: &str, args: &[RelType]) -> ExecResult;
+}
+
+/// `"{native}: {msg}"` at `Native::{native}`, the fault of engine-level natives.
+pub(crate) fn fault(native: &str, msg: String) -> ExecResult {
+    ExecResult::Fault { msg: format!("{}: {}", native, msg), node: format!("Native::{}", native) }
+}
+
+/// The arity fault shared by all typed natives.
+pub(crate) fn check_arity(name: &str, args: &[RelType], n: usize) -> Result<(), ExecResult> {
+    if args.len() == n {
+        return Ok(());
+    }
+    Err(ExecResult::Fault {
+        msg: format!("{} expects {} argument{}, got {}", name, n, if n == 1 { "" } else { "s" }, args.len()),
+        node: format!("Native::{}", name),
+    })
+}
+
+pub(crate) fn arg<T: FromRelType>(name: &str, args: &[RelType], idx: usize) -> Result<T, ExecResult> {
+    T::from_rel(&args[idx]).ok_or_else(|| ExecResult::Fault {
+        msg: format!("{} expects {} for argument {}, got {}", name, T::EXPECTED, idx + 1, describe(&args[idx])),
+        node: format!("Native::{}", name),
+    })
+}
+
+/// "a Number" -> "Number".
+fn type_name(expected: &'static str) -> &'static str {
+    expected.strip_prefix("an ").or_else(|| expected.strip_prefix("a ")).unwrap_or(expected)
+}
+
+macro_rules! impl_native_fn {
+    ($n:expr $(, $ty:ident $idx:tt)*) => {
+        impl<F, R $(, $ty)*> NativeFn<($($ty,)*)> for F
+        where
+            F: Fn($($ty),*) -> R + Send + Sync + 'static,
+            R: IntoNativeResult,
+            $($ty: FromRelType,)*
+        {
+            fn arity(&self) -> usize { $n }
+
+            fn signature(&self) -> (Vec<&'static str>, &'static str) {
+                (vec![$(type_name(<$ty as FromRelType>::EXPECTED)),*], R::TYPE_NAME)
+            }
+
+            #[allow(unused_variables)]
+            fn call(&self, name: &str, args: &[RelType]) -> ExecResult {
+                if let Err(fault) = check_arity(name, args, $n) {
+                    return fault;
+                }
+                $(let $ty = match arg::<$ty>(name, args, $idx) { Ok(v) => v, Err(fault) => return fault };)*
+                self($($ty),*).into_result(name)
+            }
+        }
+    };
+}
+
+#[allow(non_snake_case)]
+mod impls {
+    use super::*;
+    impl_native_fn!(0);
+    impl_native_fn!(1, A 0);
+    impl_native_fn!(2, A 0, B 1);
+    impl_native_fn!(3, A 0, B 1, C 2);
+    impl_native_fn!(4, A 0, B 1, C 2, D 3);
+    impl_native_fn!(5, A 0, B 1, C 2, D 3, E 4);
+    impl_native_fn!(6, A 0, B 1, C 2, D 3, E 4, G 5);
+}
+
+/// Runs `f` with converted `args`; for use inside hand-written `NativeModule`s.
+pub fn call_typed<Args, F: NativeFn<Args>>(name: &str, args: &[RelType], f: F) -> ExecResult {
+    f.call(name, args)
+}
+
+type BoxedNative = Box<dyn Fn(&str, &[RelType]) -> ExecResult + Send + Sync>;
+
+/// One native registered through `ExecutionEngine::register`.
+struct TypedNative {
+    name: String,
+    f: BoxedNative,
+}
+
+impl NativeModule for TypedNative {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        if func_name != self.name {
+            return None;
+        }
+        Some((self.f)(func_name, args))
+    }
+}
+
+impl ExecutionEngine {
+    /// Registers a Rust closure as the native `name`, callable via `NativeCall`:
+    ///
+    /// ```ignore
+    /// engine.register("Math.Lerp", |a: f64, b: f64, t: f64| a + (b - a) * t).doc = "Linear interpolation".into();
+    /// ```
+    ///
+    /// Arguments are converted with `FromRelType`; wrong counts or types fault
+    /// with `Native::<name>` before the closure runs. Registered natives take
+    /// precedence over the built-in `NativeModule`s (Math, IO, String, Regex,
+    /// JSON, DateTime, registry), but not over the natives the engine answers
+    /// itself: `Array.*`, `Timer.*`, `Coroutine.*`, `Worker.*`, `Native.List`
+    /// and, in deterministic mode, the clock and random natives. The returned
+    /// catalogue entry can be used to document the native for `Native.List`.
+    pub fn register<Args, F: NativeFn<Args>>(&mut self, name: &str, f: F) -> &mut NativeInfo {
+        let (params, returns) = f.signature();
+        let native = TypedNative { name: name.to_string(), f: Box::new(move |name, args| f.call(name, args)) };
+        self.native_modules.insert(0, Box::new(native));
+        self.natives.retain(|n| !(n.name == name && n.module.is_none()));
+        self.natives.push(NativeInfo {
+            name: name.to_string(),
+            module: None,
+            params: params.iter().enumerate().map(|(i, t)| format!("arg{}: {}", i + 1, t)).collect(),
+            returns: returns.to_string(),
+            permission: None,
+            doc: String::new(),
+        });
+        self.natives.last_mut().unwrap()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::arith::OverflowMode;
 use crate::ast::Node;
 
 pub fn count_nodes(node: &Node) -> usize {
@@ -13,6 +14,7 @@ pub fn count_nodes(node: &Node) -> usize {
         | Node::InitAudio
         | Node::GetLastKeypress
         | Node::MapCreate
+        | Node::Yield
         | Node::Load { .. }
         | Node::Import(_) => {}
 
@@ -39,6 +41,7 @@ pub fn count_nodes(node: &Node) -> usize {
         | Node::ArrayGet(l, r)
         | Node::MapGet(l, r)
         | Node::MapHasKey(l, r)
+        | Node::MapRemove(l, r)
         | Node::FileWrite(l, r)
         | Node::FSWrite(l, r)
         | Node::LoadTextureAtlas(l, r)
@@ -49,6 +52,10 @@ pub fn count_nodes(node: &Node) -> usize {
         Node::Assign(_, val)
         | Node::Store { value: val, .. }
         | Node::ArrayLen(val)
+        | Node::MapKeys(val)
+        | Node::MapValues(val)
+        | Node::MapEntries(val)
+        | Node::MapLen(val)
         | Node::Print(val)
         | Node::EvalJSONNative(val)
         | Node::ToString(val)
@@ -209,6 +216,9 @@ pub fn count_nodes(node: &Node) -> usize {
         Node::AddWorldAABB { min, max } => {
             count += count_nodes(min) + count_nodes(max);
         }
+        Node::Located { stmt, .. } => {
+            count += count_nodes(stmt);
+        }
     }
     count
 }
@@ -225,6 +235,7 @@ pub fn optimize(node: Node) -> Node {
         Node::InitVoxelMap => Node::InitVoxelMap,
         Node::InitAudio => Node::InitAudio,
         Node::GetLastKeypress => Node::GetLastKeypress,
+        Node::Yield => Node::Yield,
         Node::Fetch {
             method,
             url,
@@ -329,6 +340,11 @@ pub fn optimize(node: Node) -> Node {
             Box::new(optimize(*v)),
         ),
         Node::MapHasKey(m, k) => Node::MapHasKey(Box::new(optimize(*m)), Box::new(optimize(*k))),
+        Node::MapKeys(m) => Node::MapKeys(Box::new(optimize(*m))),
+        Node::MapValues(m) => Node::MapValues(Box::new(optimize(*m))),
+        Node::MapEntries(m) => Node::MapEntries(Box::new(optimize(*m))),
+        Node::MapRemove(m, k) => Node::MapRemove(Box::new(optimize(*m)), Box::new(optimize(*k))),
+        Node::MapLen(m) => Node::MapLen(Box::new(optimize(*m))),
         Node::UIWindow(id, title, block) => {
             // Modified
             Node::UIWindow(id, Box::new(optimize(*title)), Box::new(optimize(*block)))
@@ -339,7 +355,7 @@ pub fn optimize(node: Node) -> Node {
         Node::Concat(l, r) => Node::Concat(Box::new(optimize(*l)), Box::new(optimize(*r))),
 
         Node::ObjectLiteral(map) => {
-            let mut opt_map = std::collections::HashMap::new();
+            let mut opt_map = indexmap::IndexMap::new();
             for (k, v) in map {
                 opt_map.insert(k, optimize(v));
             }
@@ -520,6 +536,11 @@ pub fn optimize(node: Node) -> Node {
             min: Box::new(optimize(*min)),
             max: Box::new(optimize(*max)),
         },
+        Node::Located { line, col, stmt } => Node::Located {
+            line,
+            col,
+            stmt: Box::new(optimize(*stmt)),
+        },
     }
 }
 
@@ -528,18 +549,16 @@ fn optimize_math_op(left: Node, right: Node, op: char) -> Node {
     let opt_r = optimize(right);
 
     match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => match op {
-            '+' => Node::IntLiteral(l + r),
-            '-' => Node::IntLiteral(l - r),
-            '*' => Node::IntLiteral(l * r),
-            '/' => {
-                if *r != 0 {
-                    Node::IntLiteral(l / r)
-                } else {
-                    Node::Div(Box::new(opt_l), Box::new(opt_r))
-                }
-            }
-            _ => unreachable!(),
+        // Overflow and division by zero are left to the runtime, which faults
+        // or wraps according to the engine's `OverflowMode`.
+        (Node::IntLiteral(l), Node::IntLiteral(r)) => match crate::arith::int_op(OverflowMode::Checked, op, *l, *r) {
+            Some(v) => Node::IntLiteral(v),
+            None => match op {
+                '+' => Node::Add(Box::new(opt_l), Box::new(opt_r)),
+                '-' => Node::Sub(Box::new(opt_l), Box::new(opt_r)),
+                '*' => Node::Mul(Box::new(opt_l), Box::new(opt_r)),
+                _ => Node::Div(Box::new(opt_l), Box::new(opt_r)),
+            },
         },
         (Node::FloatLiteral(l), Node::FloatLiteral(r)) => match op {
             '+' => Node::FloatLiteral(l + r),
@@ -599,14 +618,15 @@ fn optimize_gt(left: Node, right: Node) -> Node {
 fn optimize_bitwise(left: Node, right: Node, op: char) -> Node {
     let opt_l = optimize(left);
     let opt_r = optimize(right);
-    match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => match op {
-            '&' => Node::IntLiteral(l & r),
-            '<' => Node::IntLiteral(l << r),
-            '>' => Node::IntLiteral(l >> r),
-            _ => unreachable!(),
-        },
-        _ => match op {
+    // Out-of-range shifts are left to the runtime, like overflowing math.
+    let folded = match (&opt_l, &opt_r) {
+        (Node::IntLiteral(l), Node::IntLiteral(r)) if op == '&' => Some(l & r),
+        (Node::IntLiteral(l), Node::IntLiteral(r)) => crate::arith::int_op(OverflowMode::Checked, op, *l, *r),
+        _ => None,
+    };
+    match folded {
+        Some(v) => Node::IntLiteral(v),
+        None => match op {
             '&' => Node::BitAnd(Box::new(opt_l), Box::new(opt_r)),
             '<' => Node::BitShiftLeft(Box::new(opt_l), Box::new(opt_r)),
             '>' => Node::BitShiftRight(Box::new(opt_l), Box::new(opt_r)),
@@ -775,12 +795,36 @@ impl TypeChecker {
                 Ok(Type::Any)
             }
 
+            Node::MapKeys(m) | Node::MapValues(m) | Node::MapEntries(m) | Node::MapLen(m) | Node::MapRemove(m, _) => {
+                let mt = self.check(m)?;
+                if !matches!(mt, Type::Map(_) | Type::Object | Type::Any) {
+                    self.errors.push(format!("TypeError: {} expects a Map, found {:?}", node.kind(), mt));
+                }
+                match node {
+                    Node::MapLen(_) => Ok(Type::Int),
+                    Node::MapRemove(_, k) => {
+                        let kt = self.check(k)?;
+                        if kt != Type::String && kt != Type::Any {
+                            self.errors.push(format!("TypeError: MapRemove key expects String, found {:?}", kt));
+                        }
+                        match mt {
+                            Type::Map(v) => Ok(*v),
+                            _ => Ok(Type::Any),
+                        }
+                    }
+                    _ => Ok(Type::Array(vec![])),
+                }
+            }
+
             // ToString always produces a String
             Node::ToString(inner) => {
                 self.check(inner)?;
                 Ok(Type::String)
             }
 
+            // Source locations are transparent to type inference
+            Node::Located { stmt, .. } => self.check(stmt),
+
             _ => {
                 // Fallback catch-all for node types we haven't strictly typed yet
                 // The optimizer shouldn't block Graphics or Arrays without specific rules
@@ -1,4 +1,28 @@
 use crate::ast::Node;
+use std::fmt;
+
+/// A syntax error at a source position. Displays as the JSON diagnostic the
+/// tooling expects: `{"diagnostic": {"line": .., "col": .., "hint": ".."}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub hint: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
+            self.line,
+            self.col,
+            self.hint.replace("\"", "\\\"")
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -34,6 +58,7 @@ pub enum Token {
     KeywordWhile,
     KeywordFn,
     KeywordReturn,
+    KeywordYield,
     KeywordImport,
     BuiltinNull,
     EOF,
@@ -96,10 +121,14 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    pub fn next_token(&mut self) -> Token {
+    fn error(&self, hint: String) -> ParseError {
+        ParseError { line: self.line, col: self.col, hint }
+    }
+
+    pub fn next_token(&mut self) -> Result<Token, ParseError> {
         self.skip_whitespace();
         if self.pos >= self.input.len() {
-            return Token::EOF;
+            return Ok(Token::EOF);
         }
 
         let c = self.peek_char().unwrap();
@@ -114,20 +143,22 @@ impl<'a> Lexer<'a> {
                     break;
                 }
             }
-            return match s.as_str() {
+            return Ok(match s.as_str() {
                 "let" => Token::KeywordLet,
                 "if" => Token::KeywordIf,
                 "else" => Token::KeywordElse,
                 "while" => Token::KeywordWhile,
                 "fn" => Token::KeywordFn,
                 "return" => Token::KeywordReturn,
+                "yield" => Token::KeywordYield,
                 "import" => Token::KeywordImport,
                 "null" => Token::BuiltinNull,
                 _ => Token::Ident(s),
-            };
+            });
         }
 
         if c.is_ascii_digit() {
+            let (line, col) = (self.line, self.col);
             let mut s = String::new();
             let mut is_float = false;
             while let Some(ch) = self.peek_char() {
@@ -151,11 +182,12 @@ impl<'a> Lexer<'a> {
                     break;
                 }
             }
-            if is_float {
-                return Token::Float(s.parse().unwrap());
+            let bad_literal = || ParseError { line, col, hint: format!("Invalid number literal '{}'", s) };
+            return if is_float {
+                s.parse().map(Token::Float).map_err(|_| bad_literal())
             } else {
-                return Token::Int(s.parse().unwrap());
-            }
+                s.parse().map(Token::Int).map_err(|_| bad_literal())
+            };
         }
 
         if c == '"' {
@@ -169,13 +201,13 @@ impl<'a> Lexer<'a> {
                 s.push(ch);
                 self.advance();
             }
-            return Token::Str(s);
+            return Ok(Token::Str(s));
         }
 
         self.advance();
         let next_c = self.peek_char().unwrap_or(' ');
 
-        match c {
+        Ok(match c {
             '(' => Token::LParen,
             ')' => Token::RParen,
             '{' => Token::LBrace,
@@ -224,37 +256,50 @@ impl<'a> Lexer<'a> {
                     Token::Gt
                 }
             }
-            _ => {
-                let escaped_hint = format!("Unexpected char '{}'", c).replace("\"", "\\\"");
-                let json = format!(
-                    r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-                    self.line, self.col, escaped_hint
-                );
-                panic!("{}", json);
-            }
-        }
+            _ => return Err(self.error(format!("Unexpected char '{}'", c))),
+        })
     }
 }
 
 pub struct Parser {
     tokens: Vec<(Token, usize, usize)>,
     pos: usize,
+    locations: bool,
+    lex_error: Option<ParseError>,
 }
 
 impl Parser {
     pub fn new(input: &str) -> Self {
         let mut lexer = Lexer::new(input);
         let mut tokens = Vec::new();
+        let mut lex_error = None;
         loop {
+            // Skip leading trivia first so each token records its own position.
+            lexer.skip_whitespace();
             let line = lexer.line;
             let col = lexer.col;
-            let t = lexer.next_token();
+            let t = match lexer.next_token() {
+                Ok(t) => t,
+                Err(e) => {
+                    // Reported by `try_parse` before any token is consumed.
+                    lex_error = Some(e);
+                    Token::EOF
+                }
+            };
             tokens.push((t.clone(), line, col));
             if t == Token::EOF {
                 break;
             }
         }
-        Self { tokens, pos: 0 }
+        Self { tokens, pos: 0, locations: false, lex_error }
+    }
+
+    /// Like `new`, but wraps every statement in `Node::Located` so the
+    /// debugger and tracing tools can map evaluation back to source lines.
+    pub fn with_locations(input: &str) -> Self {
+        let mut parser = Self::new(input);
+        parser.locations = true;
+        parser
     }
 
     fn peek(&self) -> &Token {
@@ -273,134 +318,150 @@ impl Parser {
         t
     }
 
-    fn diagnostic_panic(&self, hint: &str) -> ! {
+    fn error(&self, hint: &str) -> ParseError {
         let (line, col) = self.peek_pos();
-        let escaped_hint = hint.replace("\"", "\\\"");
-        let json = format!(
-            r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-            line, col, escaped_hint
-        );
-        panic!("{}", json);
+        ParseError { line, col, hint: hint.to_string() }
     }
 
-    fn expect(&mut self, expected: Token) {
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
         let (line, col) = self.peek_pos();
         let t = self.advance();
         if t != expected {
-            let escaped_hint =
-                format!("Expected {:?}, found {:?}", expected, t).replace("\"", "\\\"");
-            let json = format!(
-                r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-                line, col, escaped_hint
-            );
-            panic!("{}", json);
+            return Err(ParseError { line, col, hint: format!("Expected {:?}, found {:?}", expected, t) });
         }
+        Ok(())
     }
 
+    /// Parses the whole input, panicking with the JSON diagnostic on a syntax
+    /// error. Tools that must survive bad input use `try_parse` instead.
     pub fn parse(&mut self) -> Node {
+        self.try_parse().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_parse(&mut self) -> Result<Node, ParseError> {
+        if let Some(e) = self.lex_error.take() {
+            return Err(e);
+        }
         let mut statements = Vec::new();
         while *self.peek() != Token::EOF {
-            statements.push(self.parse_statement());
+            statements.push(self.parse_located_statement()?);
         }
-        Node::Block(statements)
+        Ok(Node::Block(statements))
     }
 
-    fn parse_statement(&mut self) -> Node {
-        match self.peek() {
+    fn parse_located_statement(&mut self) -> Result<Node, ParseError> {
+        let (line, col) = self.peek_pos();
+        let stmt = self.parse_statement()?;
+        if self.locations {
+            Ok(Node::Located { line, col, stmt: Box::new(stmt) })
+        } else {
+            Ok(stmt)
+        }
+    }
+
+    fn parse_statement(&mut self) -> Result<Node, ParseError> {
+        Ok(match self.peek() {
             Token::KeywordLet => {
                 self.advance();
                 let ident = match self.advance() {
                     Token::Ident(name) => name,
-                    _ => self.diagnostic_panic("Expected identifier after let"),
+                    _ => return Err(self.error("Expected identifier after let")),
                 };
-                self.expect(Token::Assign);
-                let expr = self.parse_expression();
-                self.expect(Token::Semi);
+                self.expect(Token::Assign)?;
+                let expr = self.parse_expression()?;
+                self.expect(Token::Semi)?;
                 Node::Assign(ident, Box::new(expr))
             }
             Token::KeywordIf => {
                 self.advance();
-                self.expect(Token::LParen);
-                let cond = self.parse_expression();
-                self.expect(Token::RParen);
-                let then_branch = self.parse_block();
+                self.expect(Token::LParen)?;
+                let cond = self.parse_expression()?;
+                self.expect(Token::RParen)?;
+                let then_branch = self.parse_block()?;
                 let mut else_branch = None;
                 if *self.peek() == Token::KeywordElse {
                     self.advance();
-                    else_branch = Some(Box::new(self.parse_block()));
+                    else_branch = Some(Box::new(self.parse_block()?));
                 }
                 Node::If(Box::new(cond), Box::new(then_branch), else_branch)
             }
             Token::KeywordWhile => {
                 self.advance();
-                self.expect(Token::LParen);
-                let cond = self.parse_expression();
-                self.expect(Token::RParen);
-                let body = self.parse_block();
+                self.expect(Token::LParen)?;
+                let cond = self.parse_expression()?;
+                self.expect(Token::RParen)?;
+                let body = self.parse_block()?;
                 Node::While(Box::new(cond), Box::new(body))
             }
             Token::KeywordFn => {
                 self.advance();
                 let name = match self.advance() {
                     Token::Ident(name) => name,
-                    _ => self.diagnostic_panic("Expected function name"),
+                    _ => return Err(self.error("Expected function name")),
                 };
-                self.expect(Token::LParen);
+                self.expect(Token::LParen)?;
                 let mut args = Vec::new();
                 while *self.peek() != Token::RParen {
-                    if let Token::Ident(arg) = self.advance() {
-                        args.push(arg);
+                    match self.advance() {
+                        Token::Ident(arg) => args.push(arg),
+                        // Anything else would spin here forever once the input runs out.
+                        other => return Err(self.error(&format!("Expected parameter name, found {:?}", other))),
                     }
                     if *self.peek() == Token::Comma {
                         self.advance();
                     }
                 }
-                self.expect(Token::RParen);
-                let body = self.parse_block();
+                self.expect(Token::RParen)?;
+                let body = self.parse_block()?;
                 Node::FnDef(name, args, Box::new(body))
             }
             Token::KeywordReturn => {
                 self.advance();
-                let expr = self.parse_expression();
-                self.expect(Token::Semi);
+                let expr = self.parse_expression()?;
+                self.expect(Token::Semi)?;
                 Node::Return(Box::new(expr))
             }
-            Token::LBrace => self.parse_block(),
+            Token::KeywordYield => {
+                self.advance();
+                self.expect(Token::Semi)?;
+                Node::Yield
+            }
+            Token::LBrace => self.parse_block()?,
             _ => {
-                let expr = self.parse_expression();
+                let expr = self.parse_expression()?;
 
                 // Check for -> { block } which is If(expr, Block, None)
                 if *self.peek() == Token::Arrow {
                     self.advance();
-                    let block = self.parse_block();
-                    return Node::If(Box::new(expr), Box::new(block), None);
+                    let block = self.parse_block()?;
+                    return Ok(Node::If(Box::new(expr), Box::new(block), None));
                 }
 
                 // Check for fat arrow => { block } for async callbacks (Fetch)
                 if *self.peek() == Token::FatArrow {
                     self.advance();
-                    let callback = self.parse_block();
+                    let callback = self.parse_block()?;
 
                     if let Node::Call(name, args) = expr && name == "Fetch" && args.len() == 2 {
                         let method = if let Node::StringLiteral(s) = &args[0] {
                             s.clone()
                         } else {
-                            self.diagnostic_panic("Fetch expects Method as string")
+                            return Err(self.error("Fetch expects Method as string"));
                         };
                         let url = if let Node::StringLiteral(s) = &args[1] {
                             s.clone()
                         } else {
-                            self.diagnostic_panic("Fetch expects URL as string")
+                            return Err(self.error("Fetch expects URL as string"));
                         };
-                        return Node::Fetch {
+                        return Ok(Node::Fetch {
                             method,
                             url,
                             callback: Box::new(callback),
-                        };
+                        });
                     }
-                    self.diagnostic_panic(
+                    return Err(self.error(
                         "FatArrow '=>' can only be used with Fetch(method, url) calls",
-                    );
+                    ));
                 }
 
                 if *self.peek() == Token::Semi {
@@ -408,112 +469,112 @@ impl Parser {
                 }
                 expr
             }
-        }
+        })
     }
 
-    fn parse_block(&mut self) -> Node {
-        self.expect(Token::LBrace);
+    fn parse_block(&mut self) -> Result<Node, ParseError> {
+        self.expect(Token::LBrace)?;
         let mut stmts = Vec::new();
         while *self.peek() != Token::RBrace && *self.peek() != Token::EOF {
-            stmts.push(self.parse_statement());
+            stmts.push(self.parse_located_statement()?);
         }
-        self.expect(Token::RBrace);
-        Node::Block(stmts)
+        self.expect(Token::RBrace)?;
+        Ok(Node::Block(stmts))
     }
 
-    fn parse_expression(&mut self) -> Node {
+    fn parse_expression(&mut self) -> Result<Node, ParseError> {
         self.parse_assignment()
     }
 
-    fn parse_assignment(&mut self) -> Node {
-        let left = self.parse_comparison();
+    fn parse_assignment(&mut self) -> Result<Node, ParseError> {
+        let left = self.parse_comparison()?;
         if *self.peek() == Token::Assign {
             self.advance();
-            let right = self.parse_expression(); // right-associative
-            match left {
+            let right = self.parse_expression()?; // right-associative
+            Ok(match left {
                 Node::Identifier(name) => Node::Assign(name, Box::new(right)),
                 Node::ArrayGet(arr, index) => Node::ArraySet(arr, index, Box::new(right)),
                 Node::MapGet(map, key) => Node::MapSet(map, key, Box::new(right)),
                 Node::PropertyGet(obj, prop) => Node::PropertySet(obj, prop, Box::new(right)),
                 Node::Index(container, idx) => Node::ArraySet(container, idx, Box::new(right)), // Fallback mapping
-                _ => self.diagnostic_panic("Invalid assignment target"),
-            }
+                _ => return Err(self.error("Invalid assignment target")),
+            })
         } else {
-            left
+            Ok(left)
         }
     }
 
-    fn parse_comparison(&mut self) -> Node {
-        let mut node = self.parse_term();
+    fn parse_comparison(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.parse_term()?;
         loop {
             match self.peek() {
                 Token::EqEq => {
                     self.advance();
-                    node = Node::Eq(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Eq(Box::new(node), Box::new(self.parse_term()?));
                 }
                 Token::Lt => {
                     self.advance();
-                    node = Node::Lt(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Lt(Box::new(node), Box::new(self.parse_term()?));
                 }
                 Token::Gt => {
                     self.advance();
-                    node = Node::Gt(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Gt(Box::new(node), Box::new(self.parse_term()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_term(&mut self) -> Node {
-        let mut node = self.parse_factor();
+    fn parse_term(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.parse_factor()?;
         loop {
             match self.peek() {
                 Token::Plus => {
                     self.advance();
-                    node = Node::Add(Box::new(node), Box::new(self.parse_factor()));
+                    node = Node::Add(Box::new(node), Box::new(self.parse_factor()?));
                 }
                 Token::Minus => {
                     self.advance();
-                    node = Node::Sub(Box::new(node), Box::new(self.parse_factor()));
+                    node = Node::Sub(Box::new(node), Box::new(self.parse_factor()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_factor(&mut self) -> Node {
-        let mut node = self.parse_primary();
+    fn parse_factor(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.parse_primary()?;
         loop {
             match self.peek() {
                 Token::Star => {
                     self.advance();
-                    node = Node::Mul(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::Mul(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Slash => {
                     self.advance();
-                    node = Node::Div(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::Div(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Shl => {
                     self.advance();
-                    node = Node::BitShiftLeft(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitShiftLeft(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Shr => {
                     self.advance();
-                    node = Node::BitShiftRight(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitShiftRight(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Amp => {
                     self.advance();
-                    node = Node::BitAnd(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitAnd(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_primary(&mut self) -> Node {
+    fn parse_primary(&mut self) -> Result<Node, ParseError> {
         let mut node = match self.peek().clone() {
             Token::Int(v) => {
                 self.advance();
@@ -540,20 +601,20 @@ impl Parser {
                         self.advance(); // consume '('
                         let mut args = Vec::new();
                         while *self.peek() != Token::RParen {
-                            args.push(self.parse_expression());
+                            args.push(self.parse_expression()?);
                             if *self.peek() == Token::Comma {
                                 self.advance();
                             }
                         }
-                        self.expect(Token::RParen);
+                        self.expect(Token::RParen)?;
 
                         // Trailing closure block support
                         let mut trailing_block = None;
                         if *self.peek() == Token::LBrace {
-                            trailing_block = Some(Box::new(self.parse_block()));
+                            trailing_block = Some(Box::new(self.parse_block()?));
                         }
 
-                        self.construct_node_from_call(&name, args, trailing_block)
+                        self.construct_node_from_call(&name, args, trailing_block)?
                     } else {
                         Node::Identifier(name)
                     }
@@ -561,25 +622,25 @@ impl Parser {
             }
             Token::LParen => {
                 self.advance();
-                let expr = self.parse_expression();
-                self.expect(Token::RParen);
+                let expr = self.parse_expression()?;
+                self.expect(Token::RParen)?;
                 expr
             }
             Token::LBracket => {
                 self.advance();
                 let mut args = Vec::new();
                 while *self.peek() != Token::RBracket {
-                    args.push(self.parse_expression());
+                    args.push(self.parse_expression()?);
                     if *self.peek() == Token::Comma {
                         self.advance();
                     }
                 }
-                self.expect(Token::RBracket);
+                self.expect(Token::RBracket)?;
                 Node::ArrayCreate(args)
             }
             _ => {
                 let hint = format!("Unexpected token in expression: {:?}", self.peek());
-                self.diagnostic_panic(&hint)
+                return Err(self.error(&hint));
             }
         };
 
@@ -587,21 +648,29 @@ impl Parser {
         loop {
             if *self.peek() == Token::LBracket {
                 self.advance();
-                let idx = self.parse_expression();
-                self.expect(Token::RBracket);
+                let idx = self.parse_expression()?;
+                self.expect(Token::RBracket)?;
                 node = Node::Index(Box::new(node), Box::new(idx));
             } else if *self.peek() == Token::Dot {
                 self.advance();
                 if let Token::Ident(prop) = self.advance() {
                     node = Node::PropertyGet(Box::new(node), prop);
                 } else {
-                    self.diagnostic_panic("Expected property name after dot");
+                    return Err(self.error("Expected property name after dot"));
                 }
             } else {
                 break;
             }
         }
-        node
+        Ok(node)
+    }
+
+    /// Takes the next positional argument of built-in `name`.
+    fn arg(&self, name: &str, args: &mut Vec<Node>) -> Result<Box<Node>, ParseError> {
+        if args.is_empty() {
+            return Err(self.error(&format!("Too few arguments for {}", name)));
+        }
+        Ok(Box::new(args.remove(0)))
     }
 
     fn construct_node_from_call(
@@ -609,93 +678,115 @@ impl Parser {
         name: &str,
         mut args: Vec<Node>,
         trailing_block: Option<Box<Node>>,
-    ) -> Node {
+    ) -> Result<Node, ParseError> {
         // Automatically append trailing block if present
         if let Some(b) = trailing_block {
             args.push(*b);
         }
+        let args = &mut args;
 
-        match name {
+        Ok(match name {
             // AST Map generated directly by Agent
-            "Print" | "print" => Node::Print(Box::new(args.remove(0))),
+            "Print" | "print" => Node::Print(self.arg(name, args)?),
             "Time" => Node::Time,
             "GlobalTime" => Node::GlobalTime,
-            "Sin" => Node::Sin(Box::new(args.remove(0))),
-            "Cos" => Node::Cos(Box::new(args.remove(0))),
-            "Abs" => Node::Abs(Box::new(args.remove(0))),
+            "Sin" => Node::Sin(self.arg(name, args)?),
+            "Cos" => Node::Cos(self.arg(name, args)?),
+            "Abs" => Node::Abs(self.arg(name, args)?),
             "InitGraphics" => Node::InitGraphics,
             "InitAudio" => Node::InitAudio,
             "GetLastKeypress" => Node::GetLastKeypress,
             "UIWindow" => Node::UIWindow(
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.arg(name, args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIWindow expects exact String ID arg")
+                    return Err(self.error("UIWindow expects exact String ID arg"));
                 },
-                Box::new(args.remove(0)),
-                Box::new(args.remove(0)),
+                self.arg(name, args)?,
+                self.arg(name, args)?,
             ),
-            "UILabel" => Node::UILabel(Box::new(args.remove(0))),
-            "UIButton" => Node::UIButton(Box::new(args.remove(0))),
-            "UITextInput" => Node::UITextInput(Box::new(args.remove(0))),
+            "UILabel" => Node::UILabel(self.arg(name, args)?),
+            "UIButton" => Node::UIButton(self.arg(name, args)?),
+            "UITextInput" => Node::UITextInput(self.arg(name, args)?),
             "UIScrollArea" => Node::UIScrollArea(
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.arg(name, args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIScrollArea expects exact String ID arg")
+                    return Err(self.error("UIScrollArea expects exact String ID arg"));
                 },
-                Box::new(args.remove(0)),
+                self.arg(name, args)?,
             ),
-            "UIHorizontal" => Node::UIHorizontal(Box::new(args.remove(0))),
-            "UIFullscreen" => Node::UIFullscreen(Box::new(args.remove(0))),
+            "UIHorizontal" => Node::UIHorizontal(self.arg(name, args)?),
+            "UIFullscreen" => Node::UIFullscreen(self.arg(name, args)?),
             "UIGrid" => Node::UIGrid(
-                if let Node::IntLiteral(i) = args.remove(0) {
+                if let Node::IntLiteral(i) = *self.arg(name, args)? {
                     i
                 } else {
-                    self.diagnostic_panic("UIGrid expects Int args")
+                    return Err(self.error("UIGrid expects Int args"));
                 },
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.arg(name, args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIGrid expects String ID")
+                    return Err(self.error("UIGrid expects String ID"));
                 },
-                Box::new(args.remove(0)),
+                self.arg(name, args)?,
             ),
             "UISetStyle" => {
-                let r = Box::new(args.remove(0));
-                let s = Box::new(args.remove(0));
-                let a = Box::new(args.remove(0));
-                let f = Box::new(args.remove(0));
+                let r = self.arg(name, args)?;
+                let s = self.arg(name, args)?;
+                let a = self.arg(name, args)?;
+                let f = self.arg(name, args)?;
                 let (i, h) = if args.len() >= 2 {
-                    (
-                        Some(Box::new(args.remove(0))),
-                        Some(Box::new(args.remove(0))),
-                    )
+                    (Some(self.arg(name, args)?), Some(self.arg(name, args)?))
                 } else {
                     (None, None)
                 };
                 Node::UISetStyle(r, s, a, f, i, h)
             }
-            "Concat" => Node::Concat(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArrayLen" => Node::ArrayLen(Box::new(args.remove(0))),
-            "ArrayPush" => Node::ArrayPush(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArrayGet" => Node::ArrayGet(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArraySet" => Node::ArraySet(Box::new(args.remove(0)), Box::new(args.remove(0)), Box::new(args.remove(0))),
+            "Concat" => Node::Concat(self.arg(name, args)?, self.arg(name, args)?),
+            "ArrayLen" => Node::ArrayLen(self.arg(name, args)?),
+            "ArrayPush" => Node::ArrayPush(self.arg(name, args)?, self.arg(name, args)?),
+            "ArrayGet" => Node::ArrayGet(self.arg(name, args)?, self.arg(name, args)?),
+            "ArraySet" => Node::ArraySet(self.arg(name, args)?, self.arg(name, args)?, self.arg(name, args)?),
             "MapCreate" => Node::MapCreate,
-            "MapGet" => Node::MapGet(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "MapSet" => Node::MapSet(Box::new(args.remove(0)), Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "MapHasKey" => Node::MapHasKey(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ToString" => Node::ToString(Box::new(args.remove(0))),
-            "FileRead" => Node::FileRead(Box::new(args.remove(0))),
-            "FSRead" => Node::FSRead(Box::new(args.remove(0))),
-            "FSWrite" => Node::FSWrite(Box::new(args.remove(0)), Box::new(args.remove(0))),
+            "MapGet" => Node::MapGet(self.arg(name, args)?, self.arg(name, args)?),
+            "MapSet" => Node::MapSet(self.arg(name, args)?, self.arg(name, args)?, self.arg(name, args)?),
+            "MapHasKey" => Node::MapHasKey(self.arg(name, args)?, self.arg(name, args)?),
+            "MapKeys" => Node::MapKeys(self.arg(name, args)?),
+            "MapValues" => Node::MapValues(self.arg(name, args)?),
+            "MapEntries" => Node::MapEntries(self.arg(name, args)?),
+            "MapRemove" => Node::MapRemove(self.arg(name, args)?, self.arg(name, args)?),
+            "MapLen" => Node::MapLen(self.arg(name, args)?),
+            "ToString" => Node::ToString(self.arg(name, args)?),
+            "Extract" => Node::Extract { source: self.arg(name, args)?, path: self.arg(name, args)? },
+            "FileRead" => Node::FileRead(self.arg(name, args)?),
+            "FSRead" => Node::FSRead(self.arg(name, args)?),
+            "FSWrite" => Node::FSWrite(self.arg(name, args)?, self.arg(name, args)?),
             "CheckCollision" => Node::CheckCollision {
-                a_min: Box::new(args.remove(0)),
-                a_max: Box::new(args.remove(0)),
-                b_min: Box::new(args.remove(0)),
-                b_max: Box::new(args.remove(0)),
+                a_min: self.arg(name, args)?,
+                a_max: self.arg(name, args)?,
+                b_min: self.arg(name, args)?,
+                b_max: self.arg(name, args)?,
             },
-            _ => Node::Call(name.to_string(), args), // Default to local Call
-        }
+            _ => Node::Call(name.to_string(), std::mem::take(args)), // Default to local Call
+        })
+    }
+}
+
+/// Parses `input`, returning syntax errors instead of panicking. Used by the
+/// interactive tools (REPL, debugger, DAP, hot reload) that must outlive a typo.
+pub fn parse_checked(input: &str, locations: bool) -> Result<Node, ParseError> {
+    let mut parser = if locations { Parser::with_locations(input) } else { Parser::new(input) };
+    parser.try_parse()
+}
+
+/// Parses a script file's contents: `.knoten` sources as DSL (statements
+/// wrapped in `Node::Located` when `locations` is set), anything else as a
+/// JSON AST.
+pub fn parse_script(path: &str, source: &str, locations: bool) -> Result<Node, String> {
+    if path.ends_with(".knoten") {
+        parse_checked(source, locations).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(source).map_err(|e| format!("Invalid AST JSON: {}", e))
     }
 }
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Call count plus inclusive / exclusive wall time for one function, node kind or native.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Stat {
+    pub calls: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+}
+
+struct Open {
+    name: String,
+    start: Instant,
+    children: Duration,
+}
+
+impl Open {
+    fn new(name: String) -> Self {
+        Self { name, start: Instant::now(), children: Duration::ZERO }
+    }
+}
+
+/// Wall-clock profiler fed by the evaluator while `ExecutionEngine::profiler` is set.
+///
+/// Two independent stacks are kept: one of evaluated nodes (grouped by `Node::kind`)
+/// and one of frames, i.e. `FnDef` calls plus native/extern calls. Exclusive time is
+/// inclusive time minus that of children on the same stack. Inclusive time of a
+/// recursive entry is only counted for its outermost activation.
+pub struct Profiler {
+    pub nodes: HashMap<&'static str, Stat>,
+    pub functions: HashMap<String, Stat>,
+    pub natives: HashMap<String, Stat>,
+    node_stack: Vec<(&'static str, Open)>,
+    frames: Vec<(bool, Open)>,
+    /// Exclusive frame time keyed by the `;`-joined frame stack.
+    folded: HashMap<String, Duration>,
+    started: Instant,
+    total: Option<Duration>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            functions: HashMap::new(),
+            natives: HashMap::new(),
+            node_stack: Vec::new(),
+            frames: vec![(false, Open::new("<main>".into()))],
+            folded: HashMap::new(),
+            started: Instant::now(),
+            total: None,
+        }
+    }
+
+    pub fn enter_node(&mut self, kind: &'static str) {
+        self.node_stack.push((kind, Open::new(String::new())));
+    }
+
+    pub fn exit_node(&mut self) {
+        let Some((kind, open)) = self.node_stack.pop() else { return };
+        let elapsed = open.start.elapsed();
+        if let Some((_, parent)) = self.node_stack.last_mut() {
+            parent.children += elapsed;
+        }
+        let recursive = self.node_stack.iter().any(|(k, _)| *k == kind);
+        let stat = self.nodes.entry(kind).or_default();
+        record(stat, elapsed, open.children, recursive);
+    }
+
+    /// Opens a frame for a script function (`native == false`) or a native/extern call.
+    pub fn enter_frame(&mut self, name: &str, native: bool) {
+        self.frames.push((native, Open::new(name.to_string())));
+    }
+
+    pub fn exit_frame(&mut self) {
+        // The root frame is only closed by `finish`.
+        if self.frames.len() <= 1 {
+            return;
+        }
+        let path = self.frame_path();
+        let Some((native, open)) = self.frames.pop() else { return };
+        let elapsed = open.start.elapsed();
+        if let Some((_, parent)) = self.frames.last_mut() {
+            parent.children += elapsed;
+        }
+        *self.folded.entry(path).or_default() += elapsed.saturating_sub(open.children);
+        let recursive = self.frames.iter().any(|(_, f)| f.name == open.name);
+        let table = if native { &mut self.natives } else { &mut self.functions };
+        record(table.entry(open.name).or_default(), elapsed, open.children, recursive);
+    }
+
+    /// Closes the root frame. Further samples are ignored by the reports.
+    pub fn finish(&mut self) {
+        if self.total.is_some() {
+            return;
+        }
+        while self.frames.len() > 1 {
+            self.exit_frame();
+        }
+        let total = self.started.elapsed();
+        let root = &self.frames[0].1;
+        *self.folded.entry(root.name.clone()).or_default() += total.saturating_sub(root.children);
+        self.total = Some(total);
+    }
+
+    fn frame_path(&self) -> String {
+        self.frames.iter().map(|(_, f)| f.name.as_str()).collect::<Vec<_>>().join(";")
+    }
+
+    /// Folded stacks (`main;f;g <nanoseconds>`), one per line, for flamegraph tools.
+    pub fn folded(&mut self) -> String {
+        self.finish();
+        let mut lines: Vec<String> = self
+            .folded
+            .iter()
+            .filter(|(_, d)| !d.is_zero())
+            .map(|(path, d)| format!("{} {}", path, d.as_nanos()))
+            .collect();
+        lines.sort();
+        lines.join("\n") + "\n"
+    }
+
+    /// Per-function, per-node and per-native tables, each sorted by exclusive time.
+    pub fn summary_json(&mut self) -> serde_json::Value {
+        self.finish();
+        fn table<K: std::fmt::Display>(stats: &HashMap<K, Stat>) -> serde_json::Value {
+            let mut rows: Vec<(&K, &Stat)> = stats.iter().collect();
+            rows.sort_by_key(|(_, s)| std::cmp::Reverse(s.exclusive));
+            rows.iter()
+                .map(|(name, s)| {
+                    serde_json::json!({
+                        "name": name.to_string(),
+                        "calls": s.calls,
+                        "inclusive_ms": s.inclusive.as_secs_f64() * 1000.0,
+                        "exclusive_ms": s.exclusive.as_secs_f64() * 1000.0,
+                    })
+                })
+                .collect()
+        }
+        serde_json::json!({
+            "total_ms": self.total.unwrap_or_default().as_secs_f64() * 1000.0,
+            "functions": table(&self.functions),
+            "nodes": table(&self.nodes),
+            "natives": table(&self.natives),
+        })
+    }
+
+    /// Writes `<prefix>.folded` and `<prefix>.profile.json`.
+    pub fn write_reports(&mut self, prefix: &str) -> std::io::Result<(String, String)> {
+        let folded_path = format!("{}.folded", prefix);
+        let json_path = format!("{}.profile.json", prefix);
+        std::fs::write(&folded_path, self.folded())?;
+        let summary = serde_json::to_string_pretty(&self.summary_json()).map_err(std::io::Error::other)?;
+        std::fs::write(&json_path, summary)?;
+        Ok((folded_path, json_path))
+    }
+}
+
+fn record(stat: &mut Stat, elapsed: Duration, children: Duration, recursive: bool) {
+    stat.calls += 1;
+    stat.exclusive += elapsed.saturating_sub(children);
+    if !recursive {
+        stat.inclusive += elapsed;
+    }
+}
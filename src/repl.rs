@@ -0,0 +1,161 @@
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::optimizer::TypeChecker;
+
+pub const HELP: &str = "  <code>         evaluate DSL; blocks may span several lines
+  :vars          list global variables
+  :type <expr>   infer the static type of an expression
+  :ast <expr>    show the JSON AST of an expression
+  :load <file>   run a .knoten script or JSON AST in this session
+  :help          show this help
+  :quit          leave the REPL";
+
+/// Line-oriented front end over one long-lived `ExecutionEngine`, used by the
+/// `knoten_repl` binary. Input is buffered until brackets balance, so `fn`,
+/// `if` and `while` blocks can be typed across several lines.
+pub struct Repl {
+    pub engine: ExecutionEngine,
+    /// Keeps the types of earlier definitions for `:type` and for checking new input.
+    typer: TypeChecker,
+    pending: String,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unclosed `{`, `(` and `[` outside string literals.
+fn open_brackets(src: &str) -> i64 {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in src.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn show_value(v: &RelType) -> String {
+    match v {
+        RelType::Str(s) => format!("{:?}", s),
+        other => other.to_string(),
+    }
+}
+
+/// A single parsed statement is shown on its own rather than wrapped in a Block.
+fn single_statement(ast: Node) -> Node {
+    match ast {
+        Node::Block(mut stmts) if stmts.len() == 1 => stmts.remove(0),
+        other => other,
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self { engine: ExecutionEngine::new(), typer: TypeChecker::new(), pending: String::new() }
+    }
+
+    /// True while a multi-line block is being entered.
+    pub fn is_continuation(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Feeds one line of input. Returns None while more lines are needed to
+    /// complete a block, otherwise the text to show (possibly empty).
+    pub fn feed(&mut self, line: &str) -> Option<String> {
+        if self.pending.is_empty() && line.trim_start().starts_with(':') {
+            return Some(self.meta(line.trim()));
+        }
+        self.pending.push_str(line);
+        self.pending.push('\n');
+        if open_brackets(&self.pending) > 0 {
+            return None;
+        }
+        let src = std::mem::take(&mut self.pending);
+        if src.trim().is_empty() {
+            return Some(String::new());
+        }
+        Some(self.run(&src, false))
+    }
+
+    fn run(&mut self, src: &str, json: bool) -> String {
+        let ast = if json {
+            match serde_json::from_str::<Node>(src) {
+                Ok(ast) => ast,
+                Err(e) => return format!("Invalid AST JSON: {}", e),
+            }
+        } else {
+            match crate::parser::parse_checked(src, false) {
+                Ok(ast) => ast,
+                Err(e) => return format!("Parse error: {}", e),
+            }
+        };
+
+        // Check against a copy so rejected input does not leave types behind.
+        let mut typer = TypeChecker { scopes: self.typer.scopes.clone(), errors: Vec::new() };
+        let _ = typer.check(&ast);
+        if !typer.errors.is_empty() {
+            return typer.errors.join("\n");
+        }
+        self.typer = typer;
+
+        match self.engine.execute(&ast) {
+            ExecResult::Value(RelType::Void) | ExecResult::ReturnBlockInfo(RelType::Void) => String::new(),
+            ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => show_value(&v),
+            fault => fault.to_string(),
+        }
+    }
+
+    fn meta(&mut self, line: &str) -> String {
+        let (cmd, arg) = match line.split_once(char::is_whitespace) {
+            Some((c, a)) => (c, a.trim()),
+            None => (line, ""),
+        };
+        match cmd {
+            ":vars" => self
+                .engine
+                .global_variables()
+                .iter()
+                .map(|(name, val)| format!("{} = {}", name, show_value(val)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ":type" if !arg.is_empty() => match crate::parser::parse_checked(arg, false) {
+                Ok(ast) => {
+                    let mut typer = TypeChecker { scopes: self.typer.scopes.clone(), errors: Vec::new() };
+                    match typer.check(&single_statement(ast)) {
+                        Ok(t) if typer.errors.is_empty() => format!("{:?}", t),
+                        Ok(_) => typer.errors.join("\n"),
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => format!("Parse error: {}", e),
+            },
+            ":ast" if !arg.is_empty() => match crate::parser::parse_checked(arg, false) {
+                Ok(ast) => serde_json::to_string_pretty(&single_statement(ast)).unwrap_or_default(),
+                Err(e) => format!("Parse error: {}", e),
+            },
+            ":load" if !arg.is_empty() => match std::fs::read_to_string(arg) {
+                Ok(src) => self.run(&src, !arg.ends_with(".knoten")),
+                Err(e) => format!("Cannot read '{}': {}", arg, e),
+            },
+            ":help" => HELP.to_string(),
+            _ => format!("Unknown command '{}'. Type :help for a list.", line),
+        }
+    }
+}
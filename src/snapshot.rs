@@ -0,0 +1,196 @@
+use crate::determinism::Determinism;
+use crate::executor::{ExecutionEngine, NativeHandle, PointLightData, RelType, StackFrame};
+use crate::math::AABB;
+use crate::natives::registry::{self, HandleSnapshot};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Bumped whenever the snapshot layout changes incompatibly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CameraState {
+    pub active: bool,
+    pub pos: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+}
+
+/// Script-visible engine state. Produced by `ExecutionEngine::snapshot` and
+/// written as JSON; registry handles referenced by variables travel in `handles`.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub memory: BTreeMap<String, RelType>,
+    pub call_stack: Vec<StackFrame>,
+    pub voxel_map: Vec<([i64; 3], u8)>,
+    pub voxel_map_active: bool,
+    pub world_aabbs: Vec<AABB>,
+    pub camera: CameraState,
+    pub point_lights: Vec<PointLightData>,
+    pub interaction_enabled: bool,
+    pub physics_enabled: bool,
+    pub velocity_y: f32,
+    pub is_grounded: bool,
+    pub determinism: Option<Determinism>,
+    /// Registry entries keyed by the handle id used in `memory` / `call_stack`.
+    pub handles: BTreeMap<i64, HandleSnapshot>,
+}
+
+fn collect_handles(val: &RelType, ids: &mut BTreeSet<i64>) {
+    match val {
+        RelType::Handle(h) => { ids.insert(h.0); }
+        RelType::Array(items) => items.iter().for_each(|v| collect_handles(v, ids)),
+        RelType::Object(map) => map.values().for_each(|v| collect_handles(v, ids)),
+        _ => {}
+    }
+}
+
+/// Rewrites the id of every Handle in `val` in place, without retaining or releasing.
+fn relink_handles(val: &mut RelType, f: &mut dyn FnMut(&mut NativeHandle)) {
+    match val {
+        RelType::Handle(h) => f(h),
+        RelType::Array(items) => items.iter_mut().for_each(|v| relink_handles(v, f)),
+        RelType::Object(map) => map.values_mut().for_each(|v| relink_handles(v, f)),
+        _ => {}
+    }
+}
+
+/// Visits every serialized `RelType::Handle` (`{"Handle": id}`) in `v`.
+/// Serialized RelType objects always nest values one level deeper, so a
+/// single-key object with an integer is unambiguous.
+fn visit_handles(v: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+    match v {
+        Value::Object(map) => {
+            if map.len() == 1 && map.get("Handle").is_some_and(Value::is_i64) {
+                f(map.get_mut("Handle").unwrap());
+                return;
+            }
+            map.values_mut().for_each(|v| visit_handles(v, f));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| visit_handles(v, f)),
+        _ => {}
+    }
+}
+
+impl Snapshot {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Snapshot serialization failed: {}", e))
+    }
+}
+
+impl ExecutionEngine {
+    pub fn snapshot(&self) -> Snapshot {
+        let mut ids = BTreeSet::new();
+        self.memory.values().for_each(|v| collect_handles(v, &mut ids));
+        self.call_stack.iter().flat_map(|f| f.locals.values()).for_each(|v| collect_handles(v, &mut ids));
+        let handles = ids.into_iter().filter_map(|id| registry::registry_snapshot_handle(id).map(|s| (id, s))).collect();
+
+        let mut voxel_map: Vec<([i64; 3], u8)> = self.voxel_map.iter().map(|(k, v)| (*k, *v)).collect();
+        voxel_map.sort();
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            memory: self.memory.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            call_stack: self
+                .call_stack
+                .iter()
+                .map(|f| StackFrame { locals: f.locals.clone(), function: f.function.clone(), line: f.line })
+                .collect(),
+            voxel_map,
+            voxel_map_active: self.voxel_map_active,
+            world_aabbs: self.world_aabbs.clone(),
+            camera: CameraState {
+                active: self.camera_active,
+                pos: self.camera_pos,
+                yaw: self.camera_yaw,
+                pitch: self.camera_pitch,
+                fov: self.camera_fov,
+            },
+            point_lights: self.point_lights.clone(),
+            interaction_enabled: self.interaction_enabled,
+            physics_enabled: self.physics_enabled,
+            velocity_y: self.velocity_y,
+            is_grounded: self.is_grounded,
+            determinism: self.determinism.clone(),
+            handles,
+        }
+    }
+
+    pub fn save_snapshot(&self, path: &str) -> Result<(), String> {
+        let json = self.snapshot().to_json()?;
+        std::fs::write(path, json).map_err(|e| format!("Cannot write snapshot '{}': {}", path, e))
+    }
+
+    /// Replaces the engine's script-visible state with a snapshot.
+    ///
+    /// Registry entries are re-created under fresh ids and every handle in the
+    /// restored variables is rewritten to match, so snapshots can be loaded into
+    /// a process that already uses the original ids. Handles to windows, files,
+    /// GPU contexts and textures cannot be recreated and come back as `Handle<-1>`.
+    /// The handles of the replaced state are released; on error nothing changes.
+    pub fn restore_snapshot(&mut self, json: &str) -> Result<(), String> {
+        let mut value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid snapshot: {}", e))?;
+        let version = value["version"].as_u64().unwrap_or(0);
+        if version != SNAPSHOT_VERSION as u64 {
+            return Err(format!("Unsupported snapshot version {} (expected {})", version, SNAPSHOT_VERSION));
+        }
+        let handles: BTreeMap<i64, HandleSnapshot> = serde_json::from_value(value["handles"].take())
+            .map_err(|e| format!("Invalid snapshot handles: {}", e))?;
+
+        // Handles are deserialized as negative placeholders, which own no registry
+        // entry, so a snapshot that fails to load leaves the registry untouched.
+        let placeholder = |i: usize| -2 - i as i64;
+        let placeholders: HashMap<i64, i64> = handles.keys().enumerate().map(|(i, old)| (*old, placeholder(i))).collect();
+        let mut refs: HashMap<i64, usize> = HashMap::new();
+        for key in ["memory", "call_stack"] {
+            visit_handles(&mut value[key], &mut |id| {
+                let p = placeholders.get(&id.as_i64().unwrap_or(-1)).copied().unwrap_or(-1);
+                *refs.entry(p).or_default() += 1;
+                *id = Value::from(p);
+            });
+        }
+        value["handles"] = Value::Object(Default::default());
+        let mut snapshot: Snapshot = serde_json::from_value(value).map_err(|e| format!("Invalid snapshot: {}", e))?;
+
+        // Each deserialized Handle owns one reference. Entries nothing refers to are not recreated.
+        let remap: HashMap<i64, i64> = handles
+            .values()
+            .enumerate()
+            .filter_map(|(i, snap)| refs.get(&placeholder(i)).map(|n| (placeholder(i), registry::registry_restore_handle(snap, *n))))
+            .collect();
+        let mut relink = |h: &mut NativeHandle| h.0 = remap.get(&h.0).copied().unwrap_or(-1);
+        snapshot.memory.values_mut().for_each(|v| relink_handles(v, &mut relink));
+        snapshot.call_stack.iter_mut().flat_map(|f| f.locals.values_mut()).for_each(|v| relink_handles(v, &mut relink));
+
+        // Release the old state's handles before the restored ones take their place.
+        self.memory.clear();
+        self.call_stack.clear();
+        self.memory = snapshot.memory.into_iter().collect();
+        self.call_stack = snapshot.call_stack;
+        if self.call_stack.is_empty() {
+            self.call_stack.push(StackFrame { locals: HashMap::new(), function: "<main>".into(), line: 0 });
+        }
+        self.voxel_map = snapshot.voxel_map.into_iter().collect();
+        self.voxel_map_active = snapshot.voxel_map_active;
+        self.voxel_map_dirty = true;
+        self.world_aabbs = snapshot.world_aabbs;
+        self.camera_active = snapshot.camera.active;
+        self.camera_pos = snapshot.camera.pos;
+        self.camera_yaw = snapshot.camera.yaw;
+        self.camera_pitch = snapshot.camera.pitch;
+        self.camera_fov = snapshot.camera.fov;
+        self.point_lights = snapshot.point_lights;
+        self.interaction_enabled = snapshot.interaction_enabled;
+        self.physics_enabled = snapshot.physics_enabled;
+        self.velocity_y = snapshot.velocity_y;
+        self.is_grounded = snapshot.is_grounded;
+        self.determinism = snapshot.determinism;
+        Ok(())
+    }
+
+    pub fn load_snapshot(&mut self, path: &str) -> Result<(), String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read snapshot '{}': {}", path, e))?;
+        self.restore_snapshot(&json)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::natives::catalogue::Entry;
+use crate::natives::typed::{arg, check_arity, describe, fault};
+use std::time::Duration;
+
+/// A callback scheduled by `Timer.SetTimeout` or `Timer.SetInterval`.
+#[derive(Clone)]
+pub struct Timer {
+    pub id: i64,
+    /// Engine time (see `ExecutionEngine::now`) at which the timer fires next.
+    pub due: Duration,
+    /// Some for intervals, which are rescheduled after firing.
+    pub interval: Option<Duration>,
+    /// A function value or the name of a function.
+    pub callback: RelType,
+}
+
+/// Pending timers of one engine. Ids are never reused.
+#[derive(Default)]
+pub struct Timers {
+    next_id: i64,
+    pub pending: Vec<Timer>,
+}
+
+impl Timers {
+    fn add(&mut self, due: Duration, interval: Option<Duration>, callback: RelType) -> i64 {
+        self.next_id += 1;
+        self.pending.push(Timer { id: self.next_id, due, interval, callback });
+        self.next_id
+    }
+
+    /// Removes a timer; false if it already fired or was cancelled.
+    pub fn cancel(&mut self, id: i64) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|t| t.id != id);
+        self.pending.len() != before
+    }
+
+    /// Earliest due time of any pending timer.
+    pub fn next_due(&self) -> Option<Duration> {
+        self.pending.iter().map(|t| t.due).min()
+    }
+}
+
+fn delay_arg(name: &str, args: &[RelType], idx: usize) -> Result<Duration, ExecResult> {
+    let ms: i64 = arg(name, args, idx)?;
+    u64::try_from(ms).map(Duration::from_millis).map_err(|_| fault(name, format!("delay must not be negative, got {}", ms)))
+}
+
+/// Catalogue entries of the `Timer.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Timer.SetTimeout", &["callback: Function", "delay_ms: Int"], "Int", None, "Calls callback(id?) once after delay_ms; fired by the host pump. Returns the timer id."),
+        ("", "Timer.SetInterval", &["callback: Function", "interval_ms: Int"], "Int", None, "Calls callback(id?) every interval_ms until cancelled. Returns the timer id."),
+        ("", "Timer.Cancel", &["id: Int"], "Bool", None, "Cancels a timer; false if it already fired or was cancelled."),
+        ("", "Timer.Pending", &[], "Int", None, "Number of timers waiting to fire."),
+    ]
+}
+
+impl ExecutionEngine {
+    /// `Timer.*` natives. Timers only fire from `poll_timers`, so registering
+    /// one never runs script code.
+    pub(crate) fn timer_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Timer.SetTimeout" | "Timer.SetInterval" => self.timer_set(name, args),
+            "Timer.Cancel" => check_arity(name, args, 1)
+                .and_then(|_| arg::<i64>(name, args, 0))
+                .map(|id| ExecResult::Value(RelType::Bool(self.timers.cancel(id)))),
+            "Timer.Pending" => {
+                check_arity(name, args, 0).map(|_| ExecResult::Value(RelType::Int(self.timers.pending.len() as i64)))
+            }
+            _ => return None,
+        };
+        Some(res.unwrap_or_else(|fault| fault))
+    }
+
+    fn timer_set(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let callback = match &args[0] {
+            f @ (RelType::FnDef(..) | RelType::Str(_)) => f.clone(),
+            other => return Err(fault(name, format!("callback must be a Function, got {}", describe(other)))),
+        };
+        let delay = delay_arg(name, args, 1)?;
+        let interval = (name == "Timer.SetInterval").then_some(delay);
+        let id = self.timers.add(self.now() + delay, interval, callback);
+        Ok(ExecResult::Value(RelType::Int(id)))
+    }
+
+    /// Runs the callbacks of every timer that is due, earliest first. Each
+    /// callback may take the timer id as its parameter. An interval fires at
+    /// most once per call and is then rescheduled one interval later (or one
+    /// interval from now if the pump fell behind). Timers created by the
+    /// callbacks wait for the next call. A callback that faults is reported
+    /// on stderr and its timer is cancelled.
+    pub fn poll_timers(&mut self) {
+        let now = self.now();
+        let mut due: Vec<(Duration, i64)> = self.timers.pending.iter().filter(|t| t.due <= now).map(|t| (t.due, t.id)).collect();
+        due.sort();
+        for (_, id) in due {
+            // An earlier callback may have cancelled this one.
+            let Some(pos) = self.timers.pending.iter().position(|t| t.id == id) else { continue };
+            let timer = match self.timers.pending[pos].interval {
+                Some(interval) => {
+                    let timer = self.timers.pending[pos].clone();
+                    let next = timer.due + interval;
+                    self.timers.pending[pos].due = if next <= now { now + interval } else { next };
+                    timer
+                }
+                None => self.timers.pending.remove(pos),
+            };
+            let native = if timer.interval.is_some() { "Timer.SetInterval" } else { "Timer.SetTimeout" };
+            if let Err(res) = self.call_back(native, &timer.callback, &[RelType::Int(id)]) {
+                if let ExecResult::Fault { msg, node } = res {
+                    eprintln!("[timer] timer {} failed: {} (at {})", id, msg, node);
+                }
+                self.timers.cancel(id);
+            }
+        }
+    }
+}
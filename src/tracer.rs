@@ -0,0 +1,118 @@
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::natives::fs::reltype_to_json_value;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Writes one JSON object per line for every traced event:
+///
+/// - `stmt`: a statement finished (node kind, source location if known, value or fault)
+/// - `set`: a variable was written through `set_var`
+/// - `native`: a native or extern call with its arguments and result
+///
+/// Every entry carries `seq`, the current `function` and the call stack `depth`.
+pub struct Tracer {
+    out: Box<dyn Write + Send>,
+    /// When set, only events raised while one of these functions is the
+    /// innermost frame are written. The top level is named `<main>`.
+    functions: Option<HashSet<String>>,
+    seq: u64,
+}
+
+impl Tracer {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self { out, functions: None, seq: 0 }
+    }
+
+    pub fn to_file(path: &str) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(Box::new(std::io::BufWriter::new(file))))
+    }
+
+    /// Restricts tracing to the given functions.
+    pub fn with_functions<I: IntoIterator<Item = String>>(mut self, functions: I) -> Self {
+        self.functions = Some(functions.into_iter().collect());
+        self
+    }
+
+    fn wants(&self, function: &str) -> bool {
+        self.functions.as_ref().is_none_or(|f| f.contains(function))
+    }
+
+    fn record(&mut self, mut entry: Value, fault: bool) {
+        self.seq += 1;
+        entry["seq"] = json!(self.seq);
+        let _ = writeln!(self.out, "{}", entry);
+        // Faults end most runs, so make sure they reach disk before anything else happens.
+        if fault {
+            let _ = self.out.flush();
+        }
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+fn outcome(entry: &mut Value, res: &ExecResult) -> bool {
+    match res {
+        ExecResult::Value(v) => entry["value"] = reltype_to_json_value(v),
+        ExecResult::ReturnBlockInfo(v) => {
+            entry["value"] = reltype_to_json_value(v);
+            entry["returned"] = json!(true);
+        }
+        ExecResult::Fault { msg, node } => {
+            entry["fault"] = json!({ "msg": msg, "node": node });
+            return true;
+        }
+    }
+    false
+}
+
+impl ExecutionEngine {
+    /// Common fields for an entry raised in the innermost frame, if the tracer wants it.
+    /// `line` is the frame's last known source line (absent without locations).
+    fn trace_entry(&self, event: &str) -> Option<Value> {
+        let tracer = self.tracer.as_ref()?;
+        let frame = self.call_stack.last()?;
+        if !tracer.wants(&frame.function) {
+            return None;
+        }
+        let mut entry = json!({ "event": event, "function": frame.function, "depth": self.call_stack.len() });
+        if frame.line > 0 {
+            entry["line"] = json!(frame.line);
+        }
+        Some(entry)
+    }
+
+    pub(crate) fn trace_statement(&mut self, stmt: &Node, res: &ExecResult) {
+        let Some(mut entry) = self.trace_entry("stmt") else { return };
+        let inner = match stmt {
+            Node::Located { line, col, stmt } => {
+                entry["line"] = json!(line);
+                entry["col"] = json!(col);
+                stmt.as_ref()
+            }
+            other => other,
+        };
+        entry["kind"] = json!(inner.kind());
+        let fault = outcome(&mut entry, res);
+        if let Some(tracer) = self.tracer.as_mut() { tracer.record(entry, fault); }
+    }
+
+    pub(crate) fn trace_set_var(&mut self, name: &str, val: &RelType) {
+        let Some(mut entry) = self.trace_entry("set") else { return };
+        entry["name"] = json!(name);
+        entry["value"] = reltype_to_json_value(val);
+        if let Some(tracer) = self.tracer.as_mut() { tracer.record(entry, false); }
+    }
+
+    pub(crate) fn trace_native(&mut self, name: &str, args: &[RelType], res: &ExecResult) {
+        let Some(mut entry) = self.trace_entry("native") else { return };
+        entry["name"] = json!(name);
+        entry["args"] = Value::Array(args.iter().map(reltype_to_json_value).collect());
+        let fault = outcome(&mut entry, res);
+        if let Some(tracer) = self.tracer.as_mut() { tracer.record(entry, fault); }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::ast::Node;
+use crate::natives::catalogue::{self, NativeInfo};
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
@@ -6,6 +7,8 @@ use std::path::Path;
 pub struct Validator {
     pub errors: Vec<String>,
     import_stack: HashSet<String>,
+    /// Natives that `NativeCall` / `ExternCall` may name.
+    natives: Vec<NativeInfo>,
 }
 
 impl Default for Validator {
@@ -19,9 +22,16 @@ impl Validator {
         Self {
             errors: Vec::new(),
             import_stack: HashSet::new(),
+            natives: catalogue::builtin_natives(),
         }
     }
 
+    /// Validates against a custom catalogue, e.g. `engine.native_catalogue()`
+    /// when the host has registered natives of its own.
+    pub fn with_natives(natives: &[NativeInfo]) -> Self {
+        Self { natives: natives.to_vec(), ..Self::new() }
+    }
+
     pub fn validate(&mut self, node: &Node) -> Result<(), Vec<String>> {
         self.errors.clear();
         self.import_stack.clear();
@@ -109,7 +119,7 @@ impl Validator {
             Node::ArrayLen(arr) => {
                 self.check_node(arr);
             }
-            Node::MapGet(map, key) | Node::MapHasKey(map, key) => {
+            Node::MapGet(map, key) | Node::MapHasKey(map, key) | Node::MapRemove(map, key) => {
                 self.check_node(map);
                 self.check_node(key);
             }
@@ -118,6 +128,9 @@ impl Validator {
                 self.check_node(key);
                 self.check_node(val);
             }
+            Node::MapKeys(n) | Node::MapValues(n) | Node::MapEntries(n) | Node::MapLen(n) => {
+                self.check_node(n);
+            }
             Node::Sin(n)
             | Node::Cos(n)
             | Node::FileRead(n)
@@ -163,6 +176,9 @@ impl Validator {
                 if name.is_empty() {
                     self.errors
                         .push("Call/NativeCall: Function name cannot be empty".to_string());
+                } else if matches!(node, Node::NativeCall(..)) && !catalogue::is_native_call(&self.natives, name) {
+                    self.errors
+                        .push(format!("NativeCall: Unknown native '{}'", name));
                 }
                 for arg in args {
                     self.check_node(arg);
@@ -176,6 +192,9 @@ impl Validator {
                 if module.is_empty() || function.is_empty() {
                     self.errors
                         .push("ExternCall: Module and function cannot be empty".to_string());
+                } else if !catalogue::is_extern_call(&self.natives, module, function) {
+                    self.errors
+                        .push(format!("ExternCall: Unknown native '{}.{}'", module, function));
                 }
                 for arg in args {
                     self.check_node(arg);
@@ -283,6 +302,7 @@ impl Validator {
             | Node::InitAudio
             | Node::GetLastKeypress
             | Node::InitVoxelMap
+            | Node::Yield
             | Node::UIFillParent
             | Node::StopNote(_) => {}
             Node::DrawRect { x, y, width, height, color } => {
@@ -370,6 +390,9 @@ impl Validator {
                 self.check_node(min);
                 self.check_node(max);
             }
+            Node::Located { stmt, .. } => {
+                self.check_node(stmt);
+            }
         }
     }
 }
@@ -1,6 +1,7 @@
 pub mod storage;
 
 use crate::ast::Node;
+use crate::arith::OverflowMode;
 use crate::executor::RelType;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -101,15 +102,22 @@ impl VMCompiler {
 #[derive(Default)]
 pub struct VM {
     stack: Vec<RelType>,
+    /// Int overflow behaviour; `Checked` returns an error like the tree evaluator's fault.
+    pub overflow_mode: OverflowMode,
 }
 
 impl VM {
     pub fn new() -> Self {
         Self {
             stack: Vec::with_capacity(256), // Pre-allocate for speed
+            overflow_mode: OverflowMode::default(),
         }
     }
 
+    fn int_op(&self, op: char, a: i64, b: i64) -> Result<RelType, String> {
+        crate::arith::int_op(self.overflow_mode, op, a, b).map(RelType::Int).ok_or_else(|| crate::arith::overflow_message(op, a, b))
+    }
+
     #[inline(always)]
     pub fn execute(&mut self, code: &[Opcode]) -> Result<RelType, String> {
         self.stack.clear();
@@ -123,7 +131,7 @@ impl VM {
                     let r = self.stack.pop().unwrap_or(RelType::Void);
                     let l = self.stack.pop().unwrap_or(RelType::Void);
                     match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a + b)),
+                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(self.int_op('+', a, b)?),
                         (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a + b)),
                         (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 + b)),
                         (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a + b as f64)),
@@ -134,7 +142,7 @@ impl VM {
                     let r = self.stack.pop().unwrap_or(RelType::Void);
                     let l = self.stack.pop().unwrap_or(RelType::Void);
                     match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a - b)),
+                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(self.int_op('-', a, b)?),
                         (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a - b)),
                         (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 - b)),
                         (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a - b as f64)),
@@ -145,7 +153,7 @@ impl VM {
                     let r = self.stack.pop().unwrap_or(RelType::Void);
                     let l = self.stack.pop().unwrap_or(RelType::Void);
                     match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a * b)),
+                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(self.int_op('*', a, b)?),
                         (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a * b)),
                         (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 * b)),
                         (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a * b as f64)),
@@ -158,7 +166,7 @@ impl VM {
                     match (l, r) {
                         (RelType::Int(a), RelType::Int(b)) => {
                             if b == 0 { return Err("Div by zero".into()); }
-                            self.stack.push(RelType::Int(a / b))
+                            self.stack.push(self.int_op('/', a, b)?)
                         },
                         (RelType::Float(a), RelType::Float(b)) => {
                             if b == 0.0 { return Err("Div by zero".into()); }
@@ -0,0 +1,212 @@
+//! Worker engines: script functions running on their own thread.
+//!
+//! `Worker.Spawn` starts a fresh `ExecutionEngine` on a new thread with
+//! copies of the parent's function definitions and its permissions, or a
+//! subset of them. Parent and worker share nothing; values travel over
+//! channels as JSON text, so only data crosses (no Handles or functions).
+
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, ObjectMap, RelType};
+use crate::natives::catalogue::Entry;
+use crate::natives::fs::{try_parse_json, try_reltype_to_json_value};
+use crate::natives::typed::{arg, check_arity, describe, fault};
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Workers one engine may have running or unjoined at once.
+const MAX_WORKERS: usize = 64;
+/// Stack size of worker threads, matching `run_knc`'s main script thread.
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+/// In `Worker.Send` / `Worker.Recv`, the id that addresses a worker's parent.
+const PARENT: i64 = 0;
+
+/// The parent's end of a worker.
+struct Child {
+    to_worker: Sender<String>,
+    from_worker: Receiver<String>,
+    thread: JoinHandle<Result<String, String>>,
+}
+
+/// A worker's end of the link to its parent.
+struct ParentLink {
+    to_parent: Sender<String>,
+    from_parent: Receiver<String>,
+}
+
+#[derive(Default)]
+pub struct Workers {
+    next_id: i64,
+    children: HashMap<i64, Child>,
+    parent: Option<ParentLink>,
+}
+
+fn encode(native: &str, value: &RelType) -> Result<String, ExecResult> {
+    try_reltype_to_json_value(value)
+        .map(|json| json.to_string())
+        .map_err(|e| fault(native, format!("cannot send value: {}", e)))
+}
+
+/// serde_json writes values of any depth but refuses to read past 128 levels,
+/// so a message `encode` produced can still fail here.
+fn decode(native: &str, text: &str) -> Result<RelType, ExecResult> {
+    try_parse_json(text).map_err(|e| fault(native, format!("cannot receive value: {}", e)))
+}
+
+/// The parent's permissions, narrowed by `{network, fs_read, fs_write}` flags.
+/// Flags can only take permissions away.
+fn narrow(native: &str, parent: &AgentPermissions, requested: &ObjectMap) -> Result<AgentPermissions, ExecResult> {
+    let mut perms = parent.clone();
+    for (key, value) in requested {
+        let RelType::Bool(wanted) = value else {
+            return Err(fault(native, format!("permission '{}' must be a Bool, got {}", key, describe(value))));
+        };
+        let slot = match key.as_str() {
+            "network" => &mut perms.allow_network,
+            "fs_read" => &mut perms.allow_fs_read,
+            "fs_write" => &mut perms.allow_fs_write,
+            _ => return Err(fault(native, format!("unknown permission '{}' (expected network, fs_read or fs_write)", key))),
+        };
+        if *wanted && !*slot {
+            return Err(fault(native, format!("cannot grant '{}': the spawning engine does not have it", key)));
+        }
+        *slot = *wanted;
+    }
+    Ok(perms)
+}
+
+/// Catalogue entries of the `Worker.*` natives.
+pub(crate) fn catalogue() -> &'static [Entry] {
+    &[
+        ("", "Worker.Spawn", &["function: Function", "args: Array", "permissions?: Object"], "Int", None, "Runs function(args) in a fresh engine on its own thread. permissions {network, fs_read, fs_write} may only remove rights. Returns the worker id."),
+        ("", "Worker.Send", &["id: Int", "value: Any"], "Bool", None, "Sends a copy of value to worker id, or to the parent when id is 0 inside a worker. False once the other side has finished."),
+        ("", "Worker.Recv", &["id: Int", "timeout_ms?: Int"], "Any", None, "Next message from worker id (0: the parent), waiting up to timeout_ms (default 0). Void if none."),
+        ("", "Worker.Join", &["id: Int"], "Any", None, "Waits for a worker to finish and returns its result; faults if the worker faulted."),
+    ]
+}
+
+impl ExecutionEngine {
+    /// `Worker.*` natives.
+    pub(crate) fn worker_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Worker.Spawn" => self.worker_spawn(name, args),
+            "Worker.Send" => self.worker_send(name, args),
+            "Worker.Recv" => self.worker_recv(name, args),
+            "Worker.Join" => check_arity(name, args, 1).and_then(|_| arg::<i64>(name, args, 0)).and_then(|id| self.worker_join(name, id)),
+            _ => return None,
+        };
+        Some(res.unwrap_or_else(|fault| fault))
+    }
+
+    fn worker_spawn(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        if !(2..=3).contains(&args.len()) {
+            return Err(fault(name, format!("expected 2 or 3 args, got {}", args.len())));
+        }
+        let (function, params, body) = match &args[0] {
+            RelType::FnDef(fname, params, body) => (fname.clone(), params.clone(), body.clone()),
+            RelType::Str(fname) => match self.get_var(fname) {
+                Some(RelType::FnDef(_, params, body)) => (fname.clone(), params, body),
+                _ => return Err(fault(name, format!("'{}' is not a function", fname))),
+            },
+            other => return Err(fault(name, format!("function must be a Function, got {}", describe(other)))),
+        };
+        let call_args: Vec<RelType> = arg(name, args, 1)?;
+        if call_args.len() != params.len() {
+            return Err(fault(name, format!("'{}' expects {} args, got {}", function, params.len(), call_args.len())));
+        }
+        let permissions = match args.get(2) {
+            Some(requested) => narrow(name, &self.permissions, &arg::<ObjectMap>(name, args, 2).map_err(|_| {
+                fault(name, format!("permissions must be an Object, got {}", describe(requested)))
+            })?)?,
+            None => self.permissions.clone(),
+        };
+        if self.workers.children.len() >= MAX_WORKERS {
+            return Err(fault(name, format!("more than {} workers; Join finished ones first", MAX_WORKERS)));
+        }
+        // Everything handed to the thread is encoded or cloned here.
+        let encoded_args = call_args.iter().map(|a| encode(name, a)).collect::<Result<Vec<_>, _>>()?;
+        let functions: Vec<(String, RelType)> = self
+            .memory
+            .iter()
+            .filter(|(_, v)| matches!(v, RelType::FnDef(..)))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let overflow_mode = self.overflow_mode;
+        let (to_worker, from_parent) = channel();
+        let (to_parent, from_worker) = channel();
+        let thread = std::thread::Builder::new()
+            .name(format!("knoten-worker-{}", function))
+            .stack_size(WORKER_STACK_SIZE)
+            .spawn(move || {
+                let mut engine = ExecutionEngine::new();
+                engine.permissions = permissions;
+                engine.overflow_mode = overflow_mode;
+                engine.memory.extend(functions);
+                engine.workers.parent = Some(ParentLink { to_parent, from_parent });
+                let res = encoded_args
+                    .iter()
+                    .map(|a| decode("Worker.Spawn", a))
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|args| match engine.invoke_function(&function, &params, &body, args) {
+                        ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => encode("Worker.Join", &v),
+                        fault => Err(fault),
+                    });
+                res.map_err(|fault| match fault {
+                    ExecResult::Fault { msg, node } => format!("{} (at {})", msg, node),
+                    other => other.to_string(),
+                })
+            })
+            .map_err(|e| fault(name, format!("cannot start thread: {}", e)))?;
+        let workers = &mut self.workers;
+        workers.next_id += 1;
+        workers.children.insert(workers.next_id, Child { to_worker, from_worker, thread });
+        Ok(ExecResult::Value(RelType::Int(workers.next_id)))
+    }
+
+    /// `Worker.Send(id, value)`; false once the receiving side has finished.
+    fn worker_send(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        check_arity(name, args, 2)?;
+        let id: i64 = arg(name, args, 0)?;
+        let message = encode(name, &args[1])?;
+        let sender = if id == PARENT {
+            self.workers.parent.as_ref().map(|p| &p.to_parent).ok_or_else(|| fault(name, "id 0 (the parent) only exists inside a worker".into()))?
+        } else {
+            self.workers.children.get(&id).map(|c| &c.to_worker).ok_or_else(|| fault(name, format!("no worker with id {}", id)))?
+        };
+        Ok(ExecResult::Value(RelType::Bool(sender.send(message).is_ok())))
+    }
+
+    /// `Worker.Recv(id[, timeout_ms])`: the next message, or Void if none
+    /// arrives within the timeout (0, the default, does not wait) or the
+    /// other side has finished.
+    fn worker_recv(&mut self, name: &str, args: &[RelType]) -> Result<ExecResult, ExecResult> {
+        if !(1..=2).contains(&args.len()) {
+            return Err(fault(name, format!("expected 1 or 2 args, got {}", args.len())));
+        }
+        let id: i64 = arg(name, args, 0)?;
+        let timeout: i64 = if args.len() == 2 { arg(name, args, 1)? } else { 0 };
+        let timeout = u64::try_from(timeout).map_err(|_| fault(name, format!("timeout must not be negative, got {}", timeout)))?;
+        let receiver = if id == PARENT {
+            self.workers.parent.as_ref().map(|p| &p.from_parent).ok_or_else(|| fault(name, "id 0 (the parent) only exists inside a worker".into()))?
+        } else {
+            self.workers.children.get(&id).map(|c| &c.from_worker).ok_or_else(|| fault(name, format!("no worker with id {}", id)))?
+        };
+        let message = match receiver.recv_timeout(Duration::from_millis(timeout)) {
+            Ok(text) => decode(name, &text)?,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => RelType::Void,
+        };
+        Ok(ExecResult::Value(message))
+    }
+
+    /// Waits for the worker to finish and returns its function's result.
+    /// Messages it sent that were not received are dropped.
+    fn worker_join(&mut self, name: &str, id: i64) -> Result<ExecResult, ExecResult> {
+        let child = self.workers.children.remove(&id).ok_or_else(|| fault(name, format!("no worker with id {}", id)))?;
+        drop(child.to_worker);
+        match child.thread.join() {
+            Ok(Ok(result)) => decode(name, &result).map(ExecResult::Value),
+            Ok(Err(msg)) => Err(fault(name, format!("worker {} failed: {}", id, msg))),
+            Err(_) => Err(fault(name, format!("worker {} panicked", id))),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use knoten_core::natives::registry;
+
+fn main() {
+    let mut a = knoten_core::arith::AotArith::mul(6, 7);
+    println!("{}", a);
+    let mut b = 9223372036854775807;
+    let mut c = knoten_core::arith::AotArith::add(b, 1);
+    println!("{}", c);
+}
@@ -629,3 +629,1656 @@ knoten_test!(
     ),
     "Return: \"hello world\" (String)"
 );
+
+// ------------------------------------------------------------------
+// Tests 55-57: Step Debugger (breakpoints, stepping, inspection)
+// ------------------------------------------------------------------
+use knoten_core::debugger::{Breakpoints, DebugCommand, DebugFrontend, Debugger, StopEvent, StopReason};
+use std::sync::{Arc, Mutex};
+
+/// Replays a fixed list of commands and records every stop it sees.
+struct ScriptedFrontend {
+    commands: Vec<DebugCommand>,
+    stops: Arc<Mutex<Vec<(StopReason, usize, String)>>>,
+}
+
+impl DebugFrontend for ScriptedFrontend {
+    fn paused(&mut self, engine: &mut ExecutionEngine, _bps: &mut Breakpoints, stop: &StopEvent) -> DebugCommand {
+        let vars: Vec<String> = engine
+            .frame_variables(engine.call_stack.len() - 1)
+            .iter()
+            .filter(|(_, v)| !matches!(v, knoten_core::executor::RelType::FnDef(..)))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        self.stops.lock().unwrap().push((stop.reason.clone(), stop.line, vars.join(",")));
+        if self.commands.is_empty() { DebugCommand::Continue } else { self.commands.remove(0) }
+    }
+}
+
+fn debug_run(src: &str, commands: Vec<DebugCommand>, setup: impl FnOnce(&mut Debugger)) -> Vec<(StopReason, usize, String)> {
+    let ast = knoten_core::parser::Parser::with_locations(src).parse();
+    let stops = Arc::new(Mutex::new(Vec::new()));
+    let frontend = ScriptedFrontend { commands, stops: stops.clone() };
+    let mut debugger = Debugger::new(Box::new(frontend), false);
+    setup(&mut debugger);
+    let mut engine = ExecutionEngine::new();
+    engine.debugger = Some(Box::new(debugger));
+    engine.execute(&ast);
+    stops.lock().unwrap().clone()
+}
+
+const DEBUG_SRC: &str = "fn double(x) {\n    let y = x + x;\n    return y;\n}\nlet a = 1;\nlet b = double(a);\nlet c = b + 1;\n";
+
+#[test]
+fn test_55_debugger_line_breakpoint_and_variables() {
+    let stops = debug_run(DEBUG_SRC, vec![], |d| { d.breakpoints.lines.insert(7); });
+    // `y` is a global: new bindings inside functions land in `memory` (FINDING-09).
+    assert_eq!(stops, vec![(StopReason::Breakpoint, 7, "a=1,b=2,y=2".to_string())]);
+
+    // The console `p` command reports a bad expression and keeps the session alive.
+    let out = SharedBuf(Arc::new(Mutex::new(Vec::new())));
+    let input = std::io::Cursor::new(b"p b + 1\np b + $\np b\nc\n".to_vec());
+    let mut debugger = Debugger::new(Box::new(knoten_core::debugger::ConsoleFrontend::new(Box::new(input), Box::new(out.clone()), DEBUG_SRC)), false);
+    debugger.breakpoints.lines.insert(7);
+    let mut engine = ExecutionEngine::new();
+    engine.debugger = Some(Box::new(debugger));
+    engine.execute(&knoten_core::parser::Parser::with_locations(DEBUG_SRC).parse());
+    let printed = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    let answers: Vec<&str> = printed.split("(knc-dbg) ").skip(1).map(|s| s.trim_end()).collect();
+    assert_eq!(answers[0], "3");
+    assert!(answers[1].starts_with("Parse error"), "{}", printed);
+    assert_eq!(answers[2], "2");
+    assert_eq!(engine.get_var("c").unwrap().to_string(), "3");
+}
+
+#[test]
+fn test_56_debugger_function_breakpoint_and_step_over() {
+    let stops = debug_run(DEBUG_SRC, vec![DebugCommand::StepOver, DebugCommand::StepOver, DebugCommand::StepOver], |d| {
+        d.breakpoints.functions.insert("double".into());
+    });
+    let lines: Vec<usize> = stops.iter().map(|s| s.1).collect();
+    assert_eq!(stops[0].0, StopReason::FunctionBreakpoint("double".into()));
+    assert_eq!(stops[0].2, "x=1");
+    assert_eq!(lines, vec![2, 3, 7]);
+}
+
+#[test]
+fn test_57_debugger_step_in_and_out() {
+    let stops = debug_run(DEBUG_SRC, vec![DebugCommand::StepIn, DebugCommand::StepIn, DebugCommand::StepOut, DebugCommand::Terminate], |d| {
+        d.breakpoints.lines.insert(6);
+    });
+    let lines: Vec<usize> = stops.iter().map(|s| s.1).collect();
+    assert_eq!(lines, vec![6, 2, 3, 7]);
+}
+
+// ------------------------------------------------------------------
+// Test 58: Debug Adapter Protocol server
+// ------------------------------------------------------------------
+
+/// Collects everything the adapter writes so the test can decode it afterwards.
+#[derive(Clone)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn test_58_dap_breakpoint_inspect_and_evaluate() {
+    use knoten_core::dap::{read_message, serve, write_message};
+    use serde_json::json;
+
+    let path = std::env::temp_dir().join("knoten_dap_test_58.knoten");
+    std::fs::write(&path, format!("{}print(c);\n", DEBUG_SRC)).unwrap();
+    let program = path.to_str().unwrap();
+
+    let requests = [
+        json!({"command": "initialize", "arguments": {"adapterID": "knoten"}}),
+        json!({"command": "launch", "arguments": {"program": program}}),
+        // Nothing is executable past line 8, so the second breakpoint cannot be verified.
+        json!({"command": "setBreakpoints", "arguments": {"source": {"path": program}, "breakpoints": [{"line": 2}, {"line": 99}]}}),
+        json!({"command": "configurationDone"}),
+        json!({"command": "stackTrace", "arguments": {"threadId": 1}}),
+        json!({"command": "variables", "arguments": {"variablesReference": 2}}),
+        json!({"command": "evaluate", "arguments": {"expression": "x + 10", "frameId": 2}}),
+        // A mistyped watch expression fails the request, not the debuggee.
+        json!({"command": "evaluate", "arguments": {"expression": "x + $", "frameId": 2}}),
+        json!({"command": "continue", "arguments": {"threadId": 1}}),
+    ];
+    let mut input = Vec::new();
+    for (seq, mut req) in requests.into_iter().enumerate() {
+        req["seq"] = json!(seq + 1);
+        req["type"] = json!("request");
+        write_message(&mut input, &req).unwrap();
+    }
+
+    let out = SharedBuf(Arc::new(Mutex::new(Vec::new())));
+    serve(Box::new(std::io::Cursor::new(input)), Box::new(out.clone()));
+    let _ = std::fs::remove_file(&path);
+
+    let bytes = out.0.lock().unwrap().clone();
+    let mut reader = std::io::BufReader::new(&bytes[..]);
+    let mut messages = Vec::new();
+    while let Some(msg) = read_message(&mut reader) { messages.push(msg); }
+    let response = |cmd: &str| messages.iter().find(|m| m["type"] == "response" && m["command"] == cmd).unwrap().clone();
+    let events: Vec<String> = messages.iter().filter(|m| m["type"] == "event").map(|m| m["event"].as_str().unwrap().to_string()).collect();
+
+    let bps = response("setBreakpoints")["body"]["breakpoints"].clone();
+    assert_eq!(bps[0]["verified"], true);
+    assert_eq!(bps[1]["verified"], false);
+    let frames = response("stackTrace")["body"]["stackFrames"].clone();
+    assert_eq!(frames[0]["name"], "double");
+    assert_eq!(frames[0]["line"], 2);
+    assert_eq!(frames[1]["line"], 6);
+    assert_eq!(response("variables")["body"]["variables"][0]["name"], "x");
+    assert_eq!(response("variables")["body"]["variables"][0]["value"], "1");
+    assert_eq!(response("evaluate")["body"]["result"], "11");
+    let bad_eval = messages.iter().filter(|m| m["type"] == "response" && m["command"] == "evaluate").nth(1).unwrap();
+    assert_eq!(bad_eval["success"], false);
+    assert!(bad_eval["message"].as_str().unwrap().starts_with("Parse error"), "{}", bad_eval);
+
+    let output = messages.iter().find(|m| m["event"] == "output").unwrap();
+    assert_eq!(output["body"]["output"], "3\n");
+    assert_eq!(events, vec!["initialized", "stopped", "output", "exited", "terminated"]);
+}
+
+// ------------------------------------------------------------------
+// Test 59: Profiler (per-function, per-node and per-native stats)
+// ------------------------------------------------------------------
+#[test]
+fn test_59_profiler_counts_and_folded_stacks() {
+    let src = "fn fact(n) {\n    if (n < 2) { return 1; }\n    return n * fact(n - 1);\n}\nlet r = fact(5);\n";
+    let Node::Block(mut stmts) = knoten_core::parser::parse_checked(src, false).unwrap() else { panic!("expected block") };
+    stmts.push(Node::NativeCall("Math.Sin".into(), vec![Node::FloatLiteral(0.0)]));
+
+    let mut engine = ExecutionEngine::new();
+    engine.profiler = Some(Box::new(knoten_core::profiler::Profiler::new()));
+    assert!(matches!(engine.execute(&Node::Block(stmts)), knoten_core::executor::ExecResult::Value(_)));
+    assert_eq!(engine.get_var("r").unwrap().to_string(), "120");
+
+    let mut profiler = engine.profiler.take().unwrap();
+    assert_eq!(profiler.functions["fact"].calls, 5);
+    assert_eq!(profiler.natives["Math.Sin"].calls, 1);
+    assert_eq!(profiler.nodes["Call"].calls, 5);
+    // Recursion must not inflate inclusive time beyond the whole run.
+    let fact = profiler.functions["fact"];
+    assert!(fact.exclusive <= fact.inclusive);
+
+    let folded = profiler.folded();
+    assert!(folded.lines().any(|l| l.starts_with("<main>;fact;fact;fact ")), "{}", folded);
+    assert!(folded.lines().any(|l| l.starts_with("<main>;Math.Sin ")), "{}", folded);
+    let summary = profiler.summary_json();
+    assert!(summary["total_ms"].as_f64().unwrap() >= summary["functions"][0]["inclusive_ms"].as_f64().unwrap());
+}
+
+// ------------------------------------------------------------------
+// Test 60: Execution tracer (JSONL, function filter)
+// ------------------------------------------------------------------
+#[test]
+fn test_60_tracer_jsonl_with_function_filter() {
+    let trace = |ast: &Node, functions: Option<Vec<String>>| -> Vec<serde_json::Value> {
+        let out = SharedBuf(Arc::new(Mutex::new(Vec::new())));
+        let mut tracer = knoten_core::tracer::Tracer::new(Box::new(out.clone()));
+        if let Some(f) = functions { tracer = tracer.with_functions(f); }
+        let mut engine = ExecutionEngine::new();
+        engine.tracer = Some(Box::new(tracer));
+        engine.execute(ast);
+        drop(engine);
+        let bytes = out.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+    };
+
+    // Only events raised inside `double`.
+    let ast = knoten_core::parser::parse_checked(DEBUG_SRC, true).unwrap();
+    let entries = trace(&ast, Some(vec!["double".into()]));
+    let summary: Vec<String> = entries
+        .iter()
+        .map(|e| format!("{}:{}:{}", e["event"].as_str().unwrap(), e["line"], e["value"]))
+        .collect();
+    assert_eq!(summary, vec!["set:2:2", "stmt:2:2", "stmt:3:2"]);
+    assert!(entries.iter().all(|e| e["function"] == "double" && e["depth"] == 2));
+    assert_eq!(entries[1]["kind"], "Assign");
+    assert_eq!(entries[2]["returned"], true);
+
+    // `run_knc --trace` loads `.knoten` files with locations, so every statement has a line.
+    let ast = knoten_core::parser::parse_script("trace_me.knoten", DEBUG_SRC, true).unwrap();
+    let lines: Vec<String> = trace(&ast, None).iter().filter(|e| e["event"] == "stmt").map(|e| format!("{}:{}", e["line"], e["col"])).collect();
+    assert_eq!(lines, vec!["1:1", "5:1", "2:5", "3:5", "6:1", "7:1"]);
+
+    // Native calls record arguments, and a faulting statement records the fault.
+    let ast = Node::Block(vec![Node::NativeCall("Math.Sin".into(), vec![Node::StringLiteral("x".into())])]);
+    let entries = trace(&ast, None);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["event"], "native");
+    assert_eq!(entries[0]["args"], serde_json::json!(["x"]));
+    assert_eq!(entries[0]["fault"]["node"], "Native::Math.Sin");
+    assert_eq!(entries[1]["kind"], "NativeCall");
+    assert!(entries[1]["fault"]["msg"].as_str().unwrap().contains("expects a Number"));
+}
+
+// ------------------------------------------------------------------
+// Test 61: Deterministic mode (seeded RNG, virtual clock)
+// ------------------------------------------------------------------
+#[test]
+fn test_61_deterministic_rng_and_virtual_clock() {
+    let native = |name: &str, args: Vec<Node>| Node::NativeCall(name.into(), args);
+    let randoms = |seed: u64| -> Vec<String> {
+        let mut engine = ExecutionEngine::new();
+        engine.enable_deterministic(seed);
+        (0..3).map(|_| engine.execute(&native("Math.Random", vec![])).to_string()).collect()
+    };
+    assert_eq!(randoms(42), randoms(42));
+    assert_ne!(randoms(42), randoms(7));
+
+    let mut engine = ExecutionEngine::new();
+    engine.enable_deterministic(42);
+    assert_eq!(engine.execute(&Node::Time).to_string(), "0.0");
+    let stamp = engine.execute(&native("registry_now", vec![]));
+    let knoten_core::executor::ExecResult::Value(stamp) = stamp else { panic!("registry_now failed") };
+    engine.memory.insert("t0".into(), stamp);
+
+    engine.advance_time(std::time::Duration::from_millis(1500));
+    assert_eq!(engine.execute(&Node::Time).to_string(), "1.5");
+    let elapsed = Node::ExternCall { module: "registry".into(), function: "registry_elapsed_ms".into(), args: vec![Node::Identifier("t0".into())] };
+    assert_eq!(engine.execute(&elapsed).to_string(), "1500");
+
+    // Perlin noise follows the engine seed instead of the fixed default.
+    let perlin = native("Math.Perlin2D", vec![Node::FloatLiteral(0.3), Node::FloatLiteral(0.7)]);
+    let seeded = engine.execute(&perlin).to_string();
+    assert_eq!(seeded, engine.execute(&perlin).to_string());
+    assert_ne!(seeded, ExecutionEngine::new().execute(&perlin).to_string());
+}
+
+// ------------------------------------------------------------------
+// Test 62: Engine snapshot / restore
+// ------------------------------------------------------------------
+#[test]
+fn test_62_snapshot_roundtrip_into_fresh_engine() {
+    let reg = |f: &str, args: Vec<Node>| Node::ExternCall { module: "registry".into(), function: f.into(), args };
+    let counter = || vec![Node::Identifier("counter".into())];
+
+    let mut engine = ExecutionEngine::new();
+    engine.enable_deterministic(9);
+    let setup = knoten_core::parser::parse_checked("let score = 42;\nlet items = [1, 2, 3];\n", false).unwrap();
+    engine.execute(&setup);
+    engine.execute(&Node::Assign("counter".into(), Box::new(reg("registry_create_counter", vec![]))));
+    engine.execute(&reg("registry_increment", counter()));
+    engine.execute(&reg("registry_increment", counter()));
+    engine.voxel_map.insert([1, 2, 3], 7);
+    engine.world_aabbs.push(knoten_core::math::AABB::new([0.0; 3], [1.0; 3]));
+    engine.camera_pos = [4.0, 5.0, 6.0];
+    engine.advance_time(std::time::Duration::from_secs(2));
+    let next_random = {
+        let mut probe = ExecutionEngine::new();
+        probe.restore_snapshot(&engine.snapshot().to_json().unwrap()).unwrap();
+        probe.execute(&Node::NativeCall("Math.Random".into(), vec![])).to_string()
+    };
+
+    let path = std::env::temp_dir().join("knoten_snapshot_test_62.json");
+    engine.save_snapshot(path.to_str().unwrap()).unwrap();
+    drop(engine);
+
+    let mut restored = ExecutionEngine::new();
+    restored.load_snapshot(path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(restored.get_var("score").unwrap().to_string(), "42");
+    assert_eq!(restored.get_var("items").unwrap().to_string(), "[1, 2, 3]");
+    assert_eq!(restored.voxel_map.get(&[1, 2, 3]), Some(&7));
+    assert_eq!(restored.world_aabbs.len(), 1);
+    assert_eq!(restored.camera_pos, [4.0, 5.0, 6.0]);
+    assert_eq!(restored.execute(&Node::Time).to_string(), "2.0");
+    assert_eq!(restored.execute(&Node::NativeCall("Math.Random".into(), vec![])).to_string(), next_random);
+
+    // The counter lives on under a new registry id and keeps counting.
+    assert_eq!(restored.execute(&reg("registry_get_value", counter())).to_string(), "2");
+    restored.execute(&reg("registry_increment", counter()));
+    assert_eq!(restored.execute(&reg("registry_get_value", counter())).to_string(), "3");
+
+    assert!(restored.restore_snapshot("{\"version\": 99}").unwrap_err().contains("Unsupported snapshot version"));
+}
+
+// ------------------------------------------------------------------
+// Test 63: Hot reload (function swap between loop iterations)
+// ------------------------------------------------------------------
+#[test]
+fn test_63_hot_reload_swaps_functions_keeps_globals() {
+    use knoten_core::hot_reload::{prepare_reload, spawn_watcher};
+    let path = std::env::temp_dir().join("knoten_hot_reload_test_63.knoten");
+    let path_str = path.to_str().unwrap().to_string();
+    fs::write(&path, "fn f() { return 1; }\n").unwrap();
+
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&knoten_core::parser::parse_checked("fn f() { return 1; }\nlet kept = 7;\n", false).unwrap());
+    let tx = engine.action_tx.clone().unwrap();
+
+    let natives = engine.native_catalogue().to_vec();
+
+    // Broken edits are rejected up front and never reach the engine.
+    assert!(prepare_reload(&path_str, "fn f( { return 2; }", true, &natives).is_err());
+    assert!(prepare_reload(&path_str, "fn f() { return 2 $ 1; }", true, &natives).unwrap_err().contains("Unexpected char '$'"));
+    // So are edits the startup validator would refuse, such as unknown natives.
+    let unknown = r#"{ "Block": [ { "FnDef": ["f", [], { "Return": { "NativeCall": ["Nope.Missing", []] } }] } ] }"#;
+    let err = prepare_reload("reload.json", unknown, true, &natives).unwrap_err();
+    assert!(err.contains("Unknown native 'Nope.Missing'"), "{}", err);
+
+    // Only definitions are taken over; top-level statements are not re-run.
+    let defs = prepare_reload(&path_str, "fn f() { return 2; }\nlet kept = 0;\n", true, &natives).unwrap();
+    assert_eq!(defs.len(), 1);
+    tx.send(knoten_core::executor::Action::ReloadFunctions(defs)).unwrap();
+    let lp = knoten_core::parser::parse_checked("let i = 0;\nwhile (i < 1) { let r = f(); let i = i + 1; }\n", false).unwrap();
+    engine.execute(&lp);
+    assert_eq!(engine.get_var("r").unwrap().to_string(), "2");
+    assert_eq!(engine.get_var("kept").unwrap().to_string(), "7");
+
+    // The watcher picks up a file change on its own.
+    spawn_watcher(path_str.clone(), tx, true, natives, std::time::Duration::from_millis(10));
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    fs::write(&path, "fn f() { return 3; }\n").unwrap();
+    let call = Node::Call("f".into(), vec![]);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while engine.execute(&call).to_string() != "3" && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        engine.poll_actions();
+    }
+    let _ = fs::remove_file(&path);
+    assert_eq!(engine.execute(&call).to_string(), "3");
+}
+
+// ------------------------------------------------------------------
+// Test 64: REPL (multi-line input, meta-commands)
+// ------------------------------------------------------------------
+#[test]
+fn test_64_repl_session() {
+    let mut repl = knoten_core::repl::Repl::new();
+
+    // A block stays pending until its braces close.
+    assert_eq!(repl.feed("fn double(n) {"), None);
+    assert!(repl.is_continuation());
+    assert_eq!(repl.feed("    return n * 2;"), None);
+    assert_eq!(repl.feed("}").as_deref(), Some(""));
+    assert!(!repl.is_continuation());
+
+    assert_eq!(repl.feed("let x = double(21);").as_deref(), Some("42"));
+    assert_eq!(repl.feed("x + 1").as_deref(), Some("43"));
+    assert_eq!(repl.feed("\"a{b\"").as_deref(), Some("\"a{b\""));
+
+    assert!(repl.feed(":vars").unwrap().contains("x = 42"));
+    assert_eq!(repl.feed(":type 1 + 2").as_deref(), Some("Int"));
+    assert!(repl.feed(":ast 1 + 2").unwrap().contains("\"Add\""));
+    assert!(repl.feed("let = ;").unwrap().starts_with("Parse error"));
+    // Typos the parser used to panic on leave the session intact.
+    for line in ["let y = 1 $ 2;", "Print();", "99999999999999999999", ":type Concat(\"a\")", ":ast (1 +"] {
+        assert!(repl.feed(line).unwrap().starts_with("Parse error"), "{}", line);
+    }
+    assert_eq!(repl.feed("x").as_deref(), Some("42"));
+    assert!(repl.feed(":bogus").unwrap().contains("Unknown command"));
+
+    let path = std::env::temp_dir().join("knoten_repl_test_64.knoten");
+    fs::write(&path, "let loaded = double(5);\n").unwrap();
+    assert_eq!(repl.feed(&format!(":load {}", path.display())).as_deref(), Some("10"));
+    let _ = fs::remove_file(&path);
+    assert_eq!(repl.feed("loaded").as_deref(), Some("10"));
+}
+
+// ------------------------------------------------------------------
+// Test 65: Host action injection (data, function calls, events)
+// ------------------------------------------------------------------
+#[test]
+fn test_65_host_action_injection() {
+    use knoten_core::executor::{Action, RelType};
+    let mut engine = ExecutionEngine::new();
+    let tx = engine.action_sender();
+
+    // Queued before the script runs; applied on the first loop iteration.
+    tx.send(Action::UpdateData("sensor".into(), RelType::Int(5))).unwrap();
+    tx.send(Action::Event("hit".into(), RelType::Int(3))).unwrap();
+    tx.send(Action::Event("spawn".into(), RelType::Str("orc".into()))).unwrap();
+    let src = "let total = 0;\nfn on_hit(dmg) { let total = total + dmg; }\nlet i = 0;\nwhile (i < 2) { let i = i + 1; }\n";
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+
+    assert_eq!(engine.get_var("sensor").unwrap().to_string(), "5");
+    assert_eq!(engine.get_var("total").unwrap().to_string(), "3");
+    // Unhandled events are queued for the script to drain.
+    let events = engine.get_var("events").unwrap().to_string();
+    assert!(events.contains("name: spawn") && events.contains("payload: orc"), "{}", events);
+
+    // Function calls reply with their result; the host can also poll between runs.
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    tx.send(Action::CallFunction("on_hit".into(), vec![RelType::Int(4)], None)).unwrap();
+    tx.send(Action::CallFunction("missing".into(), vec![], Some(reply_tx.clone()))).unwrap();
+    engine.poll_actions();
+    assert_eq!(engine.get_var("total").unwrap().to_string(), "7");
+    assert!(reply_rx.try_recv().unwrap().to_string().contains("not found"));
+
+    engine.execute(&knoten_core::parser::parse_checked("fn add(a, b) { return a + b; }", false).unwrap());
+    assert_eq!(engine.call_function("add", vec![RelType::Int(2), RelType::Int(3)]).to_string(), "5");
+}
+
+// ------------------------------------------------------------------
+// Test 66: Typed native registration (closures, argument conversion)
+// ------------------------------------------------------------------
+#[test]
+fn test_66_typed_native_registration() {
+    use knoten_core::executor::RelType;
+    let mut engine = ExecutionEngine::new();
+    engine.register("Math.Lerp", |a: f64, b: f64, t: f64| a + (b - a) * t);
+    engine.register("Text.Join", |parts: Vec<String>, sep: String| parts.join(&sep));
+    engine.register("Host.Check", |ok: bool| if ok { Ok(1i64) } else { Err("sensor offline") });
+
+    let call = |name: &str, args: Vec<Node>| Node::NativeCall(name.into(), args);
+    let strs = |items: &[&str]| Node::ArrayCreate(items.iter().map(|s| Node::StringLiteral(s.to_string())).collect());
+
+    // Ints are accepted where a Number is expected.
+    let lerp = call("Math.Lerp", vec![Node::IntLiteral(0), Node::FloatLiteral(10.0), Node::FloatLiteral(0.25)]);
+    assert_eq!(engine.execute(&lerp).to_string(), "2.5");
+    assert_eq!(engine.execute(&call("Text.Join", vec![strs(&["a", "b"]), Node::StringLiteral("-".into())])).to_string(), "a-b");
+    assert_eq!(engine.execute(&call("Host.Check", vec![Node::BoolLiteral(true)])).to_string(), "1");
+
+    // Arity, type and closure errors become Faults at Native::<name>.
+    let arity = engine.execute(&call("Math.Lerp", vec![Node::IntLiteral(1)]));
+    assert_eq!(arity.to_string(), "Fault: Math.Lerp expects 3 arguments, got 1 (at Native::Math.Lerp)");
+    let typed = engine.execute(&call("Text.Join", vec![strs(&["a"]), Node::IntLiteral(1)]));
+    assert_eq!(typed.to_string(), "Fault: Text.Join expects a String for argument 2, got an Int (at Native::Text.Join)");
+    let failed = engine.execute(&call("Host.Check", vec![Node::BoolLiteral(false)]));
+    assert!(failed.to_string().contains("Host.Check: sensor offline"));
+
+    // Built-ins ported to the typed helpers keep their behaviour.
+    assert_eq!(engine.execute(&call("Math.Sin", vec![Node::IntLiteral(0)])).to_string(), "0.0");
+    let v = knoten_core::natives::typed::call_typed("Demo", &[RelType::Int(2)], |n: i64| vec![n; 2]);
+    assert_eq!(v.to_string(), "[2, 2]");
+
+    // Objects keep their key order through arguments and return values.
+    engine.register("Obj.Tag", |mut obj: knoten_core::executor::ObjectMap, tag: String| {
+        obj.insert(tag, RelType::Bool(true));
+        obj
+    });
+    let obj = Node::EvalJSONNative(Box::new(Node::StringLiteral(r#"{"z": 1, "a": 2, "m": 3}"#.into())));
+    let tagged = engine.execute(&call("Obj.Tag", vec![obj, Node::StringLiteral("b".into())]));
+    assert_eq!(tagged.to_string(), "{z: 1, a: 2, m: 3, b: true}");
+    let keys = (0..20).map(|i| (format!("k{}", i), i)).collect::<indexmap::IndexMap<String, i64>>();
+    engine.register("Obj.Make", move || keys.clone());
+    let made = engine.execute(&call("Obj.Make", vec![])).to_string();
+    assert_eq!(made, format!("{{{}}}", (0..20).map(|i| format!("k{}: {}", i, i)).collect::<Vec<_>>().join(", ")));
+}
+
+// ------------------------------------------------------------------
+// Test 67: Native catalogue (Native.List, Validator rejects unknown natives)
+// ------------------------------------------------------------------
+#[test]
+fn test_67_native_catalogue() {
+    use knoten_core::validator::Validator;
+    let mut engine = ExecutionEngine::new();
+    engine.register("Math.Lerp", |a: f64, b: f64, t: f64| a + (b - a) * t).doc = "Linear interpolation".into();
+
+    let catalogue = engine.native_catalogue();
+    let lerp = catalogue.iter().find(|n| n.name == "Math.Lerp").unwrap();
+    assert_eq!(lerp.signature(), "Math.Lerp(arg1: Number, arg2: Number, arg3: Number) -> Float");
+    let read = catalogue.iter().find(|n| n.name == "IO.ReadFile").unwrap();
+    assert_eq!(read.permission.as_deref(), Some("FS_READ"));
+    assert!(catalogue.iter().any(|n| n.module.as_deref() == Some("fs") && n.name == "obj_get"));
+
+    // The same catalogue is visible to scripts.
+    let listed = engine.execute(&Node::NativeCall("Native.List".into(), vec![]));
+    let knoten_core::executor::ExecResult::Value(knoten_core::executor::RelType::Array(items)) = listed else { panic!("expected array") };
+    assert_eq!(items.len(), engine.native_catalogue().len());
+    assert!(items.iter().any(|i| i.to_string().contains("doc: Linear interpolation")));
+
+    // Unknown names are rejected before execution; registry functions work as NativeCall too.
+    let script = Node::Block(vec![
+        Node::NativeCall("Math.Sin".into(), vec![Node::IntLiteral(0)]),
+        Node::NativeCall("registry_now".into(), vec![]),
+        Node::NativeCall("Math.Lerp".into(), vec![Node::IntLiteral(0), Node::IntLiteral(1), Node::IntLiteral(0)]),
+        Node::NativeCall("Math.Tan".into(), vec![]),
+        Node::ExternCall { module: "fs".into(), function: "obj_get".into(), args: vec![] },
+        Node::ExternCall { module: "fs".into(), function: "fs_delete".into(), args: vec![] },
+    ]);
+    let errors = Validator::new().validate(&script).unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            "NativeCall: Unknown native 'Math.Lerp'".to_string(),
+            "NativeCall: Unknown native 'Math.Tan'".to_string(),
+            "ExternCall: Unknown native 'fs.fs_delete'".to_string(),
+        ]
+    );
+    let errors = Validator::with_natives(engine.native_catalogue()).validate(&script).unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
+
+// ------------------------------------------------------------------
+// Test 68: Map iteration and removal nodes
+// ------------------------------------------------------------------
+#[test]
+fn test_68_map_keys_values_entries_remove_len() {
+    let src = r#"
+let m = MapCreate();
+MapSet(m, "b", 2);
+MapSet(m, "a", 1);
+MapSet(m, "c", 3);
+let keys = MapKeys(m);
+let values = MapValues(m);
+let entries = MapEntries(m);
+let removed = MapRemove(m, "b");
+let missing = MapRemove(m, "zzz");
+let len = MapLen(m);
+"#;
+    let ast = knoten_core::parser::parse_checked(src, false).unwrap();
+    let mut typer = knoten_core::optimizer::TypeChecker::new();
+    let _ = typer.check(&ast);
+    assert!(typer.errors.is_empty(), "{:?}", typer.errors);
+
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&knoten_core::optimizer::optimize(ast.clone()));
+    let var = |name: &str| engine.get_var(name).unwrap().to_string();
+    assert_eq!(var("keys"), "[b, a, c]");
+    assert_eq!(var("values"), "[2, 1, 3]");
+    assert_eq!(var("entries"), "[[b, 2], [a, 1], [c, 3]]");
+    assert_eq!(var("removed"), "2");
+    assert_eq!(var("missing"), "");
+    assert_eq!(var("len"), "2");
+
+    // JSON objects of unknown shape can be walked.
+    let json = Node::EvalJSONNative(Box::new(Node::StringLiteral(r#"{"x": 1, "y": {"z": true}}"#.into())));
+    assert_eq!(engine.execute(&Node::MapKeys(Box::new(json))).to_string(), "[x, y]");
+
+    // Non-map targets fault at runtime and are flagged by the TypeChecker.
+    let bad = Node::MapLen(Box::new(Node::IntLiteral(1)));
+    assert_eq!(engine.execute(&bad).to_string(), "Fault: Target is not a map/object (at Node::MapLen)");
+    let mut typer = knoten_core::optimizer::TypeChecker::new();
+    let _ = typer.check(&bad);
+    assert_eq!(typer.errors, vec!["TypeError: MapLen expects a Map, found Int".to_string()]);
+
+    // The DSL emitter round-trips the new nodes and codegen lowers them.
+    let m = || Box::new(Node::Identifier("m".into()));
+    for node in [Node::MapKeys(m()), Node::MapEntries(m()), Node::MapRemove(m(), Box::new(Node::StringLiteral("b".into())))] {
+        let emitted = knoten_core::dsl_emitter::emit_dsl(&node, 0);
+        assert_eq!(knoten_core::parser::parse_checked(&emitted, false).unwrap(), Node::Block(vec![node]), "{}", emitted);
+    }
+    let rust = knoten_core::compiler::codegen::generate_rust_code(&ast);
+    // AOT maps keep insertion order too, so nothing is sorted.
+    assert!(rust.contains("knoten_core::compiler::AotMap::new()") && rust.contains(".shift_remove(&"), "{}", rust);
+    assert!(!rust.contains("sort"), "{}", rust);
+}
+
+// ------------------------------------------------------------------
+// Test 69: Objects keep insertion order
+// ------------------------------------------------------------------
+#[test]
+fn test_69_objects_keep_insertion_order() {
+    let src = r#"
+let m = MapCreate();
+MapSet(m, "zeta", 1);
+MapSet(m, "alpha", 2);
+MapSet(m, "mid", 3);
+MapSet(m, "zeta", 4);
+"#;
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    // Overwriting a key keeps its position.
+    assert_eq!(engine.get_var("m").unwrap().to_string(), "{zeta: 4, alpha: 2, mid: 3}");
+
+    // Removing a key keeps the order of the rest.
+    engine.execute(&knoten_core::parser::parse_checked(r#"MapRemove(m, "zeta"); MapSet(m, "zeta", 5);"#, false).unwrap());
+    assert_eq!(engine.get_var("m").unwrap().to_string(), "{alpha: 2, mid: 3, zeta: 5}");
+
+    // JSON keeps document order in both directions.
+    let json = r#"{"b":1,"a":{"y":true,"x":[1,2]},"c":"s"}"#;
+    let parsed = knoten_core::natives::fs::fs_parse_json(json);
+    assert_eq!(parsed.to_string(), "{b: 1, a: {y: true, x: [1, 2]}, c: s}");
+    assert_eq!(knoten_core::natives::fs::reltype_to_json_value(&parsed).to_string(), json);
+    let serialized = serde_json::to_string(&parsed).unwrap();
+    let restored: knoten_core::executor::RelType = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(restored.to_string(), parsed.to_string());
+
+    // Object literals evaluate in source order, also after a JSON AST round trip.
+    let literal = Node::ObjectLiteral(
+        [("q", 1), ("b", 2), ("k", 3)].into_iter().map(|(k, v)| (k.to_string(), Node::IntLiteral(v))).collect(),
+    );
+    let literal: Node = serde_json::from_str(&serde_json::to_string(&literal).unwrap()).unwrap();
+    assert_eq!(engine.execute(&literal).to_string(), "{q: 1, b: 2, k: 3}");
+}
+
+// ------------------------------------------------------------------
+// Test 70: String natives
+// ------------------------------------------------------------------
+#[test]
+fn test_70_string_natives() {
+    let mut engine = ExecutionEngine::new();
+    let s = |v: &str| Node::StringLiteral(v.into());
+    let mut call = |name: &str, args: Vec<Node>| engine.execute(&Node::NativeCall(name.into(), args)).to_string();
+
+    // Lengths and indices count chars, not bytes.
+    assert_eq!(call("String.Len", vec![s("größe 🦀")]), "7");
+    assert_eq!(call("String.Substring", vec![s("größe 🦀"), Node::IntLiteral(2), Node::IntLiteral(4)]), "öß");
+    assert_eq!(call("String.Substring", vec![s("abc"), Node::IntLiteral(1), Node::IntLiteral(99)]), "bc");
+    assert_eq!(call("String.Substring", vec![s("abc"), Node::IntLiteral(2), Node::IntLiteral(1)]), "");
+    assert_eq!(call("String.Find", vec![s("🦀 crab 🦀"), s("crab")]), "2");
+    assert_eq!(call("String.Find", vec![s("abc"), s("x")]), "-1");
+    assert_eq!(call("String.Chars", vec![s("añ🦀")]), "[a, ñ, 🦀]");
+
+    assert_eq!(call("String.Split", vec![s("a,b,,c"), s(",")]), "[a, b, , c]");
+    assert_eq!(call("String.Split", vec![s("hé"), s("")]), "[h, é]");
+    let items = Node::ArrayCreate(vec![s("x"), Node::IntLiteral(1), Node::BoolLiteral(true)]);
+    assert_eq!(call("String.Join", vec![items, s("-")]), "x-1-true");
+    assert_eq!(call("String.Trim", vec![s("  \t pad \n")]), "pad");
+    assert_eq!(call("String.Replace", vec![s("a-b-c"), s("-"), s("+")]), "a+b+c");
+    assert_eq!(call("String.ToUpper", vec![s("straße")]), "STRASSE");
+    assert_eq!(call("String.ToLower", vec![s("ÄÖÜ")]), "äöü");
+    assert_eq!(call("String.StartsWith", vec![s("knoten"), s("kno")]), "true");
+    assert_eq!(call("String.EndsWith", vec![s("knoten"), s("kno")]), "false");
+    assert_eq!(call("String.Repeat", vec![s("ab"), Node::IntLiteral(3)]), "ababab");
+
+    // Bad input faults instead of panicking.
+    assert_eq!(call("String.Len", vec![Node::IntLiteral(3)]), "Fault: String.Len expects a String for argument 1, got an Int (at Native::String.Len)");
+    assert_eq!(call("String.Repeat", vec![s("ab"), Node::IntLiteral(-1)]), "Fault: String.Repeat: count must not be negative, got -1 (at Native::String.Repeat)");
+    assert_eq!(call("String.Repeat", vec![s("ab"), Node::IntLiteral(i64::MAX)]), "Fault: String.Repeat: result would exceed 64 MiB (at Native::String.Repeat)");
+    assert_eq!(call("String.Replace", vec![s("a"), s(""), s("b")]), "Fault: String.Replace: pattern must not be empty (at Native::String.Replace)");
+
+    // stdlib/string_utils.nod no longer applies ArrayLen to a string.
+    let mut engine = ExecutionEngine::new();
+    let lib: Node = serde_json::from_str(&fs::read_to_string("stdlib/string_utils.nod").unwrap()).unwrap();
+    engine.execute(&lib);
+    let is_not_empty = |engine: &mut ExecutionEngine, v: &str| engine.execute(&Node::Call("String.IsNotEmpty".into(), vec![Node::StringLiteral(v.into())])).to_string();
+    assert_eq!(is_not_empty(&mut engine, "ü"), "true");
+    assert_eq!(is_not_empty(&mut engine, ""), "false");
+
+    // Every String native is catalogued.
+    let names = ["Len", "Split", "Join", "Trim", "Replace", "Find", "Substring", "ToUpper", "ToLower", "StartsWith", "EndsWith", "Chars", "Repeat"];
+    let calls = Node::Block(names.iter().map(|n| Node::NativeCall(format!("String.{}", n), vec![])).collect());
+    let mut validator = knoten_core::validator::Validator::with_natives(engine.native_catalogue());
+    assert_eq!(validator.validate(&calls), Ok(()));
+}
+
+// ------------------------------------------------------------------
+// Test 71: Regex natives
+// ------------------------------------------------------------------
+#[test]
+fn test_71_regex_natives() {
+    let mut engine = ExecutionEngine::new();
+    let s = |v: &str| Node::StringLiteral(v.into());
+    let mut call = |name: &str, args: Vec<Node>| engine.execute(&Node::NativeCall(name.into(), args)).to_string();
+
+    let log = "ünï 12:04 WARN disk\n12:05 ERROR net";
+    let line = r"(?m)^(?:\S+ )?(?P<time>\d+:\d+) (?P<level>[A-Z]+)(x)?";
+    assert_eq!(
+        call("Regex.Match", vec![s(line), s(log)]),
+        "{text: ünï 12:04 WARN, index: 0, groups: [12:04, WARN, ], named: {time: 12:04, level: WARN}}"
+    );
+    assert_eq!(call("Regex.Match", vec![s(r"\d+"), s("none")]), "");
+    assert_eq!(
+        call("Regex.FindAll", vec![s(r"(\w+)=(\d+)"), s("ä=1 b=22")]),
+        "[{text: ä=1, index: 0, groups: [ä, 1], named: {}}, {text: b=22, index: 4, groups: [b, 22], named: {}}]"
+    );
+    // Char indices stay right across many matches after multi-byte text.
+    let found = call("Regex.FindAll", vec![s(r"\d"), s("é1ü22ö333")]);
+    let indices: Vec<&str> = found.match_indices("index: ").map(|(i, _)| &found[i + 7..i + 8]).collect();
+    assert_eq!(indices, ["1", "3", "4", "6", "7", "8"]);
+    assert_eq!(call("Regex.Replace", vec![s(r"(?P<k>\w+)=(\d+)"), s("a=1, b=2"), s("$2:${k}")]), "1:a, 2:b");
+    assert_eq!(call("Regex.Split", vec![s(r"\s*[,;]\s*"), s("a , b;c")]), "[a, b, c]");
+
+    // Bad patterns and oversized patterns fault; the linear-time engine rejects backreferences.
+    assert!(call("Regex.Match", vec![s("("), s("x")]).starts_with("Fault: Regex.Match: invalid pattern"));
+    assert!(call("Regex.Match", vec![s(r"(a)\1"), s("aa")]).starts_with("Fault: Regex.Match: invalid pattern"));
+    assert_eq!(
+        call("Regex.Split", vec![s(&"a".repeat(5000)), s("x")]),
+        "Fault: Regex.Split: pattern is longer than 4096 bytes (at Native::Regex.Split)"
+    );
+    assert!(call("Regex.Match", vec![s(r"\w{1000}{1000}"), s("x")]).starts_with("Fault: Regex.Match: invalid pattern"));
+    assert_eq!(
+        call("Regex.Match", vec![Node::IntLiteral(1), s("x")]),
+        "Fault: Regex.Match expects a String for argument 1, got an Int (at Native::Regex.Match)"
+    );
+
+    // Results are capped.
+    let many = "a".repeat(100_001);
+    assert_eq!(call("Regex.FindAll", vec![s("a"), s(&many)]), "Fault: Regex.FindAll: more than 100000 matches (at Native::Regex.FindAll)");
+    // An empty pattern matches between every char, so the replacement is inserted once per char.
+    assert_eq!(call("Regex.Replace", vec![s(""), s("ab"), s("-")]), "-a-b-");
+    assert_eq!(
+        call("Regex.Replace", vec![s(""), s(&"a".repeat(2000)), s(&"x".repeat(64 * 1024))]),
+        "Fault: Regex.Replace: result would exceed 64 MiB (at Native::Regex.Replace)"
+    );
+}
+
+// ------------------------------------------------------------------
+// Test 72: Higher-order Array natives
+// ------------------------------------------------------------------
+#[test]
+fn test_72_array_natives_with_callbacks() {
+    let src = r#"
+fn double(x) { return x * 2; }
+fn is_even(x) { return ((x / 2) * 2 == x); }
+fn add(acc, x) { return acc + x; }
+fn weighted(x, i) { return x * i; }
+fn neg(x) { return 0 - x; }
+fn len_of(s) { return 0; }
+fn boom(x) { return x / 0; }
+fn same(x) { return x; }
+fn four(a, b, c, d) { return a; }
+let xs = [3, 1, 4, 1, 5];
+"#;
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    let id = |v: &str| Node::Identifier(v.into());
+    let name = |v: &str| Node::StringLiteral(v.into());
+    let mut call = |native: &str, args: Vec<Node>| engine.execute(&Node::NativeCall(native.into(), args)).to_string();
+
+    assert_eq!(call("Array.Map", vec![id("xs"), id("double")]), "[6, 2, 8, 2, 10]");
+    // Callbacks can be passed by name and may take the index.
+    assert_eq!(call("Array.Map", vec![id("xs"), name("weighted")]), "[0, 1, 8, 3, 20]");
+    assert_eq!(call("Array.Filter", vec![id("xs"), id("is_even")]), "[4]");
+    assert_eq!(call("Array.Reduce", vec![id("xs"), id("add"), Node::IntLiteral(100)]), "114");
+    assert_eq!(call("Array.SortBy", vec![id("xs"), id("neg")]), "[5, 4, 3, 1, 1]");
+    assert_eq!(call("Array.Find", vec![id("xs"), id("is_even")]), "4");
+    assert_eq!(call("Array.Find", vec![Node::ArrayCreate(vec![Node::IntLiteral(1)]), id("is_even")]), "");
+    assert_eq!(call("Array.Any", vec![id("xs"), id("is_even")]), "true");
+    assert_eq!(call("Array.All", vec![id("xs"), id("is_even")]), "false");
+    assert_eq!(call("Array.All", vec![Node::ArrayCreate(vec![]), id("is_even")]), "true");
+    assert_eq!(call("Array.Slice", vec![id("xs"), Node::IntLiteral(1), Node::IntLiteral(3)]), "[1, 4]");
+    assert_eq!(call("Array.Slice", vec![id("xs"), Node::IntLiteral(4), Node::IntLiteral(99)]), "[5]");
+    assert_eq!(call("Array.Concat", vec![id("xs"), Node::ArrayCreate(vec![Node::IntLiteral(9)])]), "[3, 1, 4, 1, 5, 9]");
+    assert_eq!(call("Array.Range", vec![Node::IntLiteral(2), Node::IntLiteral(5)]), "[2, 3, 4]");
+    assert_eq!(call("Array.Range", vec![Node::IntLiteral(5), Node::IntLiteral(2)]), "[]");
+
+    // Sorting is stable.
+    assert_eq!(call("Array.SortBy", vec![id("xs"), id("len_of")]), "[3, 1, 4, 1, 5]");
+
+    // Callback faults propagate unchanged; bad callbacks fault with the native's name.
+    assert_eq!(call("Array.Map", vec![id("xs"), id("boom")]), "Fault: Div by zero (at Node::MathDiv)");
+    assert_eq!(
+        call("Array.Filter", vec![id("xs"), id("double")]),
+        "Fault: Array.Filter: callback must return a Bool, got an Int (at Native::Array.Filter)"
+    );
+    assert_eq!(call("Array.Map", vec![id("xs"), name("nope")]), "Fault: Array.Map: 'nope' is not a function (at Native::Array.Map)");
+    assert_eq!(
+        call("Array.Map", vec![id("xs"), Node::IntLiteral(1)]),
+        "Fault: Array.Map: callback must be a Function, got an Int (at Native::Array.Map)"
+    );
+    assert_eq!(
+        call("Array.Map", vec![id("xs"), id("add"), Node::IntLiteral(1)]),
+        "Fault: Array.Map expects 2 arguments, got 3 (at Native::Array.Map)"
+    );
+    assert_eq!(
+        call("Array.Map", vec![id("xs"), id("four")]),
+        "Fault: Array.Map: callback 'four' takes at most 2 parameters, has 4 (at Native::Array.Map)"
+    );
+    let mixed = Node::ArrayCreate(vec![Node::IntLiteral(1), Node::StringLiteral("a".into())]);
+    assert!(call("Array.SortBy", vec![mixed, id("same")]).starts_with("Fault: Array.SortBy: cannot compare keys"));
+    assert_eq!(
+        call("Array.Range", vec![Node::IntLiteral(0), Node::IntLiteral(i64::MAX)]),
+        "Fault: Array.Range: range is longer than 10000000 items (at Native::Array.Range)"
+    );
+}
+
+// The program `generate_rust_code` emits for AOT_ARITH_SRC, compiled as part of
+// this test crate so the generated calls must type-check against knoten_core.
+#[allow(unused_imports, unused_mut)]
+mod aot_checked_arith {
+    include!("aot/checked_arith.rs");
+    pub fn run() { main() }
+}
+const AOT_ARITH_SRC: &str = "let a = 6 * 7;\nprint(a);\nlet b = 9223372036854775807;\nlet c = b + 1;\nprint(c);\n";
+
+// ------------------------------------------------------------------
+// Test 73: Checked integer arithmetic
+// ------------------------------------------------------------------
+#[test]
+fn test_73_integer_overflow_faults() {
+    use knoten_core::arith::OverflowMode;
+    let int = |v: i64| Box::new(Node::IntLiteral(v));
+    let max_plus_one = Node::Add(int(i64::MAX), int(1));
+    let cases = [
+        (max_plus_one.clone(), "Fault: Integer overflow: 9223372036854775807 + 1 (at Node::Add)"),
+        (Node::Sub(int(i64::MIN), int(1)), "Fault: Integer overflow: -9223372036854775808 - 1 (at Node::Sub)"),
+        (Node::Mul(int(i64::MAX), int(2)), "Fault: Integer overflow: 9223372036854775807 * 2 (at Node::Mul)"),
+        (Node::Div(int(i64::MIN), int(-1)), "Fault: Integer overflow: -9223372036854775808 / -1 (at Node::Div)"),
+        (Node::Div(int(1), int(0)), "Fault: Div by zero (at Node::MathDiv)"),
+        (Node::BitShiftLeft(int(1), int(64)), "Fault: Integer overflow: 1 << 64 (at Node::BitShiftLeft)"),
+        (Node::BitShiftLeft(int(1), int(-1)), "Fault: Integer overflow: 1 << -1 (at Node::BitShiftLeft)"),
+        (Node::BitShiftRight(int(-8), int(64)), "Fault: Integer overflow: -8 >> 64 (at Node::BitShiftRight)"),
+        // Left shifts fault once a set bit or the sign is shifted out.
+        (Node::BitShiftLeft(int(i64::MAX), int(1)), "Fault: Integer overflow: 9223372036854775807 << 1 (at Node::BitShiftLeft)"),
+        (Node::BitShiftLeft(int(1), int(63)), "Fault: Integer overflow: 1 << 63 (at Node::BitShiftLeft)"),
+        (Node::BitShiftLeft(int(-3), int(62)), "Fault: Integer overflow: -3 << 62 (at Node::BitShiftLeft)"),
+    ];
+    let mut engine = ExecutionEngine::new();
+    for (node, expected) in &cases {
+        assert_eq!(engine.execute(node).to_string(), *expected);
+        // Constant folding leaves overflowing expressions to the runtime.
+        assert_eq!(&knoten_core::optimizer::optimize(node.clone()), node);
+    }
+    assert_eq!(knoten_core::optimizer::optimize(Node::Mul(int(6), int(7))), Node::IntLiteral(42));
+    assert_eq!(knoten_core::optimizer::optimize(Node::BitShiftLeft(int(-1), int(63))), Node::IntLiteral(i64::MIN));
+    assert_eq!(knoten_core::optimizer::optimize(Node::BitShiftLeft(int(1), int(62))), Node::IntLiteral(1 << 62));
+    assert_eq!(knoten_core::optimizer::optimize(Node::BitShiftRight(int(-8), int(1))), Node::IntLiteral(-4));
+
+    // Faults stop the script like any other fault.
+    let src = "let x = 9223372036854775807;\nlet y = x + 1;\nlet z = 1;\n";
+    let res = engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    assert!(res.to_string().starts_with("Fault: Integer overflow"), "{}", res);
+    assert!(engine.get_var("z").is_none());
+
+    engine.overflow_mode = OverflowMode::Wrapping;
+    assert_eq!(engine.execute(&max_plus_one).to_string(), i64::MIN.to_string());
+    assert_eq!(engine.execute(&Node::Div(int(i64::MIN), int(-1))).to_string(), i64::MIN.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(1), int(65))).to_string(), "2");
+    assert_eq!(engine.execute(&Node::BitShiftRight(int(-8), int(-63))).to_string(), "-4");
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(i64::MAX), int(1))).to_string(), "-2");
+    engine.overflow_mode = OverflowMode::Saturating;
+    assert_eq!(engine.execute(&max_plus_one).to_string(), i64::MAX.to_string());
+    assert_eq!(engine.execute(&Node::Sub(int(i64::MIN), int(5))).to_string(), i64::MIN.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(i64::MAX), int(1))).to_string(), i64::MAX.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(1), int(64))).to_string(), i64::MAX.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(-3), int(100))).to_string(), i64::MIN.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(0), int(100))).to_string(), "0");
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(-1), int(63))).to_string(), i64::MIN.to_string());
+    assert_eq!(engine.execute(&Node::BitShiftRight(int(-8), int(100))).to_string(), "-1");
+    // Negative amounts shift the other way.
+    assert_eq!(engine.execute(&Node::BitShiftLeft(int(40), int(-3))).to_string(), "5");
+    assert_eq!(engine.execute(&Node::BitShiftRight(int(5), int(-3))).to_string(), "40");
+    assert_eq!(engine.execute(&Node::BitShiftRight(int(1), int(i64::MIN))).to_string(), i64::MAX.to_string());
+    assert_eq!("wrap".parse::<OverflowMode>(), Ok(OverflowMode::Wrapping));
+    assert!("bogus".parse::<OverflowMode>().is_err());
+
+    // The bytecode VM follows the same rules.
+    let mut compiler = knoten_core::vm::VMCompiler::new();
+    assert!(compiler.compile(&max_plus_one));
+    let mut vm = knoten_core::vm::VM::new();
+    assert_eq!(vm.execute(&compiler.code), Err("Integer overflow: 9223372036854775807 + 1".to_string()));
+    vm.overflow_mode = OverflowMode::Saturating;
+    assert_eq!(vm.execute(&compiler.code), Ok(knoten_core::executor::RelType::Int(i64::MAX)));
+
+    // AOT code calls the checked helpers, which panic instead of wrapping.
+    let rust = knoten_core::compiler::codegen::generate_rust_code(&max_plus_one);
+    assert!(rust.contains("knoten_core::arith::AotArith::add(9223372036854775807, 1)"), "{}", rust);
+    let program = knoten_core::parser::parse_checked(AOT_ARITH_SRC, false).unwrap();
+    assert_eq!(knoten_core::compiler::codegen::generate_rust_code(&program), include_str!("aot/checked_arith.rs"));
+    let panic = std::panic::catch_unwind(aot_checked_arith::run).unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>().map(String::as_str), Some("Integer overflow: 9223372036854775807 + 1"));
+    use knoten_core::arith::AotArith;
+    assert_eq!(AotArith::mul(6_i64, 7), 42);
+    assert_eq!(AotArith::add(0.5_f64, 0.25), 0.75);
+    let panic = std::panic::catch_unwind(|| AotArith::add(i64::MAX, 1)).unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>().map(String::as_str), Some("Integer overflow: 9223372036854775807 + 1"));
+}
+
+// ------------------------------------------------------------------
+// Test 74: JSON.Stringify / JSON.Parse
+// ------------------------------------------------------------------
+#[test]
+fn test_74_json_natives_round_trip() {
+    use knoten_core::executor::{ExecResult, ObjectMap, RelType};
+    let mut engine = ExecutionEngine::new();
+    let mut native = |name: &str, args: Vec<RelType>| {
+        engine.set_var("arg0".into(), args.first().cloned().unwrap_or(RelType::Void));
+        engine.set_var("arg1".into(), args.get(1).cloned().unwrap_or(RelType::Void));
+        let nodes = (0..args.len()).map(|i| Node::Identifier(format!("arg{}", i))).collect();
+        engine.execute(&Node::NativeCall(name.into(), nodes))
+    };
+
+    let mut inner = ObjectMap::new();
+    inner.insert("z".into(), RelType::Float(3.0));
+    inner.insert("a".into(), RelType::Void);
+    let mut obj = ObjectMap::new();
+    obj.insert("int".into(), RelType::Int(i64::MIN));
+    obj.insert("float".into(), RelType::Float(0.1));
+    obj.insert("big".into(), RelType::Float(1e300));
+    obj.insert("text".into(), RelType::Str("\"q\" ü\n".into()));
+    obj.insert("list".into(), RelType::Array(vec![RelType::Bool(true), RelType::Array(vec![]), RelType::Object(inner)]));
+    let value = RelType::Object(obj);
+
+    let ExecResult::Value(RelType::Str(json)) = native("JSON.Stringify", vec![value.clone()]) else { panic!() };
+    assert_eq!(
+        json,
+        r#"{"int":-9223372036854775808,"float":0.1,"big":1e+300,"text":"\"q\" ü\n","list":[true,[],{"z":3.0,"a":null}]}"#
+    );
+    // Parsing gives back the same value, Int/Float distinction and key order included.
+    assert!(matches!(native("JSON.Parse", vec![RelType::Str(json)]), ExecResult::Value(v) if v == value));
+
+    let ExecResult::Value(RelType::Str(pretty)) = native("JSON.Stringify", vec![value.clone(), RelType::Bool(true)]) else { panic!() };
+    assert!(pretty.starts_with("{\n  \"int\": -9223372036854775808,\n"), "{}", pretty);
+    assert!(matches!(native("JSON.Parse", vec![RelType::Str(pretty)]), ExecResult::Value(v) if v == value));
+    assert_eq!(native("JSON.Stringify", vec![RelType::Void]).to_string(), "null");
+    assert!(matches!(native("JSON.Parse", vec![RelType::Str("null".into())]), ExecResult::Value(RelType::Void)));
+
+    // Invalid input and unrepresentable values fault.
+    assert_eq!(
+        native("JSON.Parse", vec![RelType::Str("{\"a\": }".into())]).to_string(),
+        "Fault: JSON.Parse: expected value at line 1 column 7 (at Native::JSON.Parse)"
+    );
+    assert_eq!(
+        native("JSON.Stringify", vec![RelType::Array(vec![RelType::Float(f64::NAN)])]).to_string(),
+        "Fault: JSON.Stringify: NaN cannot be represented in JSON (at Native::JSON.Stringify)"
+    );
+    assert_eq!(
+        native("JSON.Stringify", vec![RelType::FnDef("f".into(), vec![], Box::new(Node::Block(vec![])))]).to_string(),
+        "Fault: JSON.Stringify: a Function cannot be represented in JSON (at Native::JSON.Stringify)"
+    );
+    assert_eq!(
+        native("JSON.Stringify", vec![RelType::Int(1), RelType::Int(1)]).to_string(),
+        "Fault: JSON.Stringify expects a Bool for argument 2, got an Int (at Native::JSON.Stringify)"
+    );
+}
+
+// ------------------------------------------------------------------
+// Test 75: Extract with JSONPath and JSON Pointer
+// ------------------------------------------------------------------
+#[test]
+fn test_75_extract_json_path_and_pointer() {
+    let doc = r#"{"store": {"books": [
+        {"title": "A", "price": 8, "tags": ["x"]},
+        {"title": "B", "price": 12.5},
+        {"title": "C", "price": 3, "isbn": "0-1"}
+    ], "owner": {"name": "Kim", "id": 7}}, "a/b": {"m~n": true}, "id": 1}"#;
+    let mut engine = ExecutionEngine::new();
+    engine.set_var("body".into(), knoten_core::executor::RelType::Str(doc.into()));
+    let mut extract = |path: &str| {
+        let node = Node::Extract { source: Box::new(Node::Identifier("body".into())), path: Box::new(Node::StringLiteral(path.into())) };
+        engine.execute(&node).to_string()
+    };
+
+    // Definite paths return one value.
+    assert_eq!(extract("$.store.books[1].title"), "B");
+    assert_eq!(extract("$['store']['owner'][\"name\"]"), "Kim");
+    assert_eq!(extract("$.store.books[-1].price"), "3");
+    assert_eq!(extract("$.store.books[9].title"), "");
+    assert_eq!(extract("$.id"), "1");
+    // Everything else returns all matches.
+    assert_eq!(extract("$.store.books[*].title"), "[A, B, C]");
+    assert_eq!(extract("$..id"), "[1, 7]");
+    assert_eq!(extract("$.store.books[0:2].price"), "[8, 12.5]");
+    assert_eq!(extract("$.store.books[::-1].title"), "[C, B, A]");
+    assert_eq!(extract("$.store.books[1::9223372036854775807].title"), "[B]");
+    assert_eq!(extract("$.store.books[1::-9223372036854775808].title"), "[B]");
+    assert_eq!(extract("$.store.books[0,2]['title','isbn']"), "[A, C, 0-1]");
+    assert_eq!(extract("$.store.owner.*"), "[Kim, 7]");
+    assert_eq!(extract("$..tags[0]"), "[x]");
+    assert_eq!(extract("$.missing[*]"), "[]");
+
+    // JSON Pointer.
+    assert_eq!(extract("/store/books/2/isbn"), "0-1");
+    assert_eq!(extract("/a~1b/m~0n"), "true");
+    assert_eq!(extract("/store/books/5"), "");
+    assert_eq!(extract("/store/owner"), "{name: Kim, id: 7}");
+
+    // Bad paths fault.
+    assert_eq!(extract("$.store["), "Fault: Extract: unclosed '[' in path '$.store[' (at Node::Extract)");
+    assert_eq!(extract("store.books"), "Fault: Extract: path 'store.books' must start with '$' (JSONPath) or '/' (JSON Pointer) (at Node::Extract)");
+    assert_eq!(extract("$.a[?(@.x)]"), "Fault: Extract: filter expressions are not supported in path '$.a[?(@.x)]' (at Node::Extract)");
+    assert_eq!(extract("$.a[::0]"), "Fault: Extract: slice step must not be 0 in path '$.a[::0]' (at Node::Extract)");
+    assert_eq!(extract("/store/books/01"), "Fault: Extract: invalid array index '01' in pointer (at Node::Extract)");
+    assert_eq!(extract("/a~2"), "Fault: Extract: invalid escape in pointer token 'a~2' (use ~0 or ~1) (at Node::Extract)");
+
+    // Parsed values work as sources too, and the DSL builtin round-trips through the emitter.
+    engine.set_var("data".into(), knoten_core::natives::fs::fs_parse_json(r#"{"xs": [1, 2, 3]}"#));
+    let src = "let last = Extract(data, \"$.xs[-1]\");\nlet bad = Extract(\"{\", \"$\");\n";
+    let ast = knoten_core::parser::parse_checked(src, false).unwrap();
+    let res = engine.execute(&ast);
+    assert_eq!(engine.get_var("last").unwrap().to_string(), "3");
+    assert!(res.to_string().starts_with("Fault: Extract source is not valid JSON"), "{}", res);
+    let node = Node::Extract { source: Box::new(Node::Identifier("data".into())), path: Box::new(Node::StringLiteral("$.xs".into())) };
+    let emitted = knoten_core::dsl_emitter::emit_dsl(&node, 0);
+    assert_eq!(knoten_core::parser::parse_checked(&emitted, false).unwrap(), Node::Block(vec![node]), "{}", emitted);
+}
+
+// ------------------------------------------------------------------
+// Test 76: DateTime natives and the deterministic clock
+// ------------------------------------------------------------------
+#[test]
+fn test_76_datetime_natives() {
+    use knoten_core::executor::RelType;
+    let mut engine = ExecutionEngine::new();
+    let mut call = |name: &str, args: Vec<RelType>| {
+        let args = args
+            .into_iter()
+            .map(|a| match a {
+                RelType::Int(i) => Node::IntLiteral(i),
+                RelType::Str(s) => Node::StringLiteral(s),
+                other => panic!("unsupported arg {:?}", other),
+            })
+            .collect();
+        engine.execute(&Node::NativeCall(name.into(), args)).to_string()
+    };
+    let s = |v: &str| RelType::Str(v.into());
+    let ts = 1_792_308_615_250;
+
+    assert_eq!(call("DateTime.Parse", vec![s("2026-10-18T09:30:15.250+02:00")]), ts.to_string());
+    assert_eq!(call("DateTime.Parse", vec![s("2026-10-18T07:30:15.25")]), ts.to_string());
+    assert_eq!(call("DateTime.Parse", vec![s("2026-10-18")]), "1792281600000");
+    assert_eq!(call("DateTime.ToIso", vec![RelType::Int(ts)]), "2026-10-18T07:30:15.250Z");
+    assert_eq!(call("DateTime.Format", vec![RelType::Int(ts), s("%d.%m.%Y %H:%M")]), "18.10.2026 07:30");
+    assert_eq!(
+        call("DateTime.Components", vec![RelType::Int(ts)]),
+        "{year: 2026, month: 10, day: 18, hour: 7, minute: 30, second: 15, millisecond: 250, weekday: 7, yearday: 291, offset_minutes: 0}"
+    );
+    let date = [2026, 10, 18, 7, 30, 15].map(RelType::Int).to_vec();
+    assert_eq!(call("DateTime.FromComponents", date), (ts - 250).to_string());
+
+    // Arithmetic: fixed units, calendar months with clamping, and differences.
+    let jan31 = call("DateTime.Parse", vec![s("2024-01-31T12:00:00Z")]).parse::<i64>().unwrap();
+    let feb = call("DateTime.Add", vec![RelType::Int(jan31), RelType::Int(1), s("month")]).parse::<i64>().unwrap();
+    assert_eq!(call("DateTime.ToIso", vec![RelType::Int(feb)]), "2024-02-29T12:00:00.000Z");
+    assert_eq!(call("DateTime.Add", vec![RelType::Int(0), RelType::Int(-2), s("h")]), "-7200000");
+    assert_eq!(call("DateTime.Diff", vec![RelType::Int(feb), RelType::Int(jan31), s("d")]), "29");
+    assert_eq!(call("DateTime.ParseDuration", vec![s("P1DT2H30M")]), "95400000");
+    assert_eq!(call("DateTime.ParseDuration", vec![s("-PT1.5S")]), "-1500");
+    assert_eq!(call("DateTime.FormatDuration", vec![RelType::Int(95_401_500)]), "P1DT2H30M1.5S");
+    assert_eq!(call("DateTime.FormatDuration", vec![RelType::Int(0)]), "PT0S");
+
+    // Bad input faults.
+    assert!(call("DateTime.Parse", vec![s("yesterday")]).starts_with("Fault: DateTime.Parse: 'yesterday' is not an ISO-8601"));
+    assert!(call("DateTime.FromComponents", [2026, 2, 30, 0, 0, 0].map(RelType::Int).to_vec()).contains("not a valid date"));
+    assert!(call("DateTime.Add", vec![RelType::Int(0), RelType::Int(1), s("fortnight")]).contains("unknown unit 'fortnight'"));
+    assert!(call("DateTime.Diff", vec![RelType::Int(0), RelType::Int(0), s("month")]).contains("no fixed length"));
+    assert!(call("DateTime.ParseDuration", vec![s("P1M")]).contains("years and months"));
+    assert!(call("DateTime.Format", vec![RelType::Int(0), s("%Q")]).contains("invalid format pattern"));
+    let now: i64 = call("DateTime.Now", vec![]).parse().unwrap();
+    assert!(now > ts - 365 * 86_400_000, "{}", now);
+
+    // Deterministic engines report the configured epoch plus the virtual clock, with local time as UTC.
+    let mut det = ExecutionEngine::new();
+    det.enable_deterministic(7);
+    det.determinism.as_mut().unwrap().epoch_ms = ts;
+    det.advance_time(std::time::Duration::from_millis(1_750));
+    let now_iso = Node::NativeCall("DateTime.NowLocalIso".into(), vec![]);
+    assert_eq!(det.execute(&now_iso).to_string(), "2026-10-18T07:30:17.000Z");
+    assert_eq!(det.execute(&Node::NativeCall("DateTime.Now".into(), vec![])).to_string(), (ts + 1_750).to_string());
+}
+
+// ------------------------------------------------------------------
+// Test 77: Timers fired by the host pump on the virtual clock
+// ------------------------------------------------------------------
+#[test]
+fn test_77_timers_on_virtual_clock() {
+    use knoten_core::executor::RelType;
+    use std::time::Duration;
+    let src = r#"
+        let log = [];
+        fn note(id) { ArrayPush(log, id); }
+        fn tick() { ArrayPush(log, "tick"); }
+    "#;
+    let mut engine = ExecutionEngine::new();
+    engine.enable_deterministic(1);
+    let ast = knoten_core::parser::parse_checked(src, false).unwrap();
+    engine.execute(&ast);
+    let native = |name: &str, args: Vec<Node>| Node::NativeCall(name.into(), args);
+    let once = engine.execute(&native("Timer.SetTimeout", vec![Node::StringLiteral("note".into()), Node::IntLiteral(100)])).to_string();
+    let every = engine.execute(&native("Timer.SetInterval", vec![Node::StringLiteral("tick".into()), Node::IntLiteral(40)])).to_string();
+    assert_eq!((once.as_str(), every.as_str()), ("1", "2"));
+    let cancelled = engine.execute(&native("Timer.SetTimeout", vec![Node::StringLiteral("note".into()), Node::IntLiteral(10)]));
+    assert_eq!(engine.execute(&native("Timer.Cancel", vec![Node::IntLiteral(3)])).to_string(), "true", "{}", cancelled);
+    assert_eq!(engine.execute(&native("Timer.Cancel", vec![Node::IntLiteral(3)])).to_string(), "false");
+    assert_eq!(engine.execute(&native("Timer.Pending", vec![])).to_string(), "2");
+
+    // Nothing fires until the host pumps, and only what is due by the virtual clock.
+    let log = |engine: &ExecutionEngine| engine.get_var("log").unwrap().to_string();
+    engine.poll_async_bridge();
+    assert_eq!(log(&engine), "[]");
+    for _ in 0..3 {
+        engine.advance_time(Duration::from_millis(40));
+        engine.poll_async_bridge();
+    }
+    assert_eq!(log(&engine), "[tick, tick, 1, tick]");
+    // A late pump fires an interval once, then it continues from now.
+    engine.advance_time(Duration::from_millis(200));
+    engine.poll_timers();
+    engine.advance_time(Duration::from_millis(39));
+    engine.poll_timers();
+    assert_eq!(log(&engine), "[tick, tick, 1, tick, tick]");
+    engine.execute(&native("Timer.Cancel", vec![Node::IntLiteral(2)]));
+    engine.advance_time(Duration::from_millis(1_000));
+    engine.poll_timers();
+    assert_eq!(engine.execute(&native("Timer.Pending", vec![])).to_string(), "0");
+
+    // A faulting callback cancels its interval; bad arguments fault up front.
+    engine.set_var("broken".into(), RelType::Str("missing_fn".into()));
+    engine.execute(&native("Timer.SetInterval", vec![Node::Identifier("broken".into()), Node::IntLiteral(0)]));
+    engine.poll_timers();
+    assert_eq!(engine.execute(&native("Timer.Pending", vec![])).to_string(), "0");
+    let neg = engine.execute(&native("Timer.SetTimeout", vec![Node::StringLiteral("note".into()), Node::IntLiteral(-1)]));
+    assert!(neg.to_string().starts_with("Fault: Timer.SetTimeout: delay must not be negative"), "{}", neg);
+    let bad = engine.execute(&native("Timer.SetInterval", vec![Node::IntLiteral(5), Node::IntLiteral(10)]));
+    assert!(bad.to_string().starts_with("Fault: Timer.SetInterval: callback must be a Function"), "{}", bad);
+}
+
+// ------------------------------------------------------------------
+// Test 78: Coroutines resumed by the per-frame pump
+// ------------------------------------------------------------------
+#[test]
+fn test_78_coroutines_yield_and_await() {
+    use knoten_core::executor::RelType;
+    let src = r#"
+        let log = [];
+        let x = 0;
+        fn mover(steps) {
+            while (steps > 0) { let x = x + 1; let steps = steps - 1; yield; }
+            return x * 10;
+        }
+        fn chatter(tag) {
+            ArrayPush(log, tag);
+            yield;
+            if (x > 1) { yield; ArrayPush(log, "late"); } else { ArrayPush(log, "early"); }
+        }
+        fn forever(n) { while (true) { yield; } }
+        fn bad(n) { yield; let y = nope; }
+    "#;
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    // `let r = Coroutine.Await(id); ArrayPush(log, r); return r;`, built directly since natives have no DSL syntax.
+    let waiter = Node::FnDef("waiter".into(), vec!["id".into()], Box::new(Node::Block(vec![
+        Node::Assign("r".into(), Box::new(Node::NativeCall("Coroutine.Await".into(), vec![Node::Identifier("id".into())]))),
+        Node::ArrayPush(Box::new(Node::Identifier("log".into())), Box::new(Node::Identifier("r".into()))),
+        Node::Return(Box::new(Node::Identifier("r".into()))),
+    ])));
+    engine.execute(&waiter);
+    let call = |engine: &mut ExecutionEngine, name: &str, args: Vec<RelType>| {
+        let args = args.into_iter().map(|a| match a {
+            RelType::Int(i) => Node::IntLiteral(i),
+            RelType::Str(s) => Node::StringLiteral(s),
+            other => panic!("unsupported arg {:?}", other),
+        });
+        let mut args: Vec<Node> = args.collect();
+        if name == "Coroutine.Start" && args.len() == 2 {
+            args[1] = Node::ArrayCreate(vec![args[1].clone()]);
+        }
+        engine.execute(&Node::NativeCall(name.into(), args)).to_string()
+    };
+    let s = |v: &str| RelType::Str(v.into());
+    let var = |engine: &ExecutionEngine, name: &str| engine.get_var(name).unwrap().to_string();
+
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("mover"), RelType::Int(3)]), "1");
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("chatter"), s("a")]), "2");
+    // Nothing runs until the pump.
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(1)]), "suspended");
+    assert_eq!((var(&engine, "x"), var(&engine, "log")), ("0".into(), "[]".into()));
+    engine.poll_async_bridge();
+    assert_eq!((var(&engine, "x"), var(&engine, "log")), ("1".into(), "[a]".into()));
+    engine.poll_async_bridge();
+    engine.poll_async_bridge();
+    // chatter resumed inside the `if` it yielded in.
+    assert_eq!((var(&engine, "x"), var(&engine, "log")), ("3".into(), "[a, late]".into()));
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(2)]), "done");
+    assert_eq!(call(&mut engine, "Coroutine.Pending", vec![]), "1");
+    engine.poll_coroutines();
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(1)]), "done");
+    assert_eq!(call(&mut engine, "Coroutine.Await", vec![RelType::Int(1)]), "30");
+
+    // A coroutine awaiting another suspends until it has finished.
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("mover"), RelType::Int(2)]), "3");
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("waiter"), RelType::Int(3)]), "4");
+    for _ in 0..2 {
+        engine.poll_coroutines();
+        assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(4)]), "suspended");
+    }
+    engine.poll_coroutines();
+    assert_eq!(var(&engine, "log"), "[a, late, 50]");
+    assert_eq!(call(&mut engine, "Coroutine.Await", vec![RelType::Int(4)]), "50");
+
+    // Cancelling, faults, and misuse.
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("forever"), RelType::Int(0)]), "5");
+    engine.poll_coroutines();
+    let pending = call(&mut engine, "Coroutine.Await", vec![RelType::Int(5)]);
+    assert!(pending.contains("coroutine 5 has not finished"), "{}", pending);
+    assert_eq!(call(&mut engine, "Coroutine.Cancel", vec![RelType::Int(5)]), "true");
+    assert_eq!(call(&mut engine, "Coroutine.Cancel", vec![RelType::Int(5)]), "false");
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(5)]), "cancelled");
+    assert!(call(&mut engine, "Coroutine.Await", vec![RelType::Int(5)]).contains("coroutine 5 was cancelled"));
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("bad"), RelType::Int(0)]), "6");
+    engine.poll_coroutines();
+    engine.poll_coroutines();
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(6)]), "faulted");
+    assert!(call(&mut engine, "Coroutine.Await", vec![RelType::Int(6)]).contains("coroutine 6 faulted: Variable 'nope' not found"));
+    assert_eq!(call(&mut engine, "Coroutine.Pending", vec![]), "0");
+    assert!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(99)]).contains("no coroutine with id 99"));
+    assert!(call(&mut engine, "Coroutine.Start", vec![s("mover")]).contains("'mover' expects 1 args, got 0"));
+    let direct = engine.execute(&knoten_core::parser::parse_checked("mover(1);", false).unwrap()).to_string();
+    assert!(direct.starts_with("Fault: yield is only allowed as a statement in a function started with Coroutine.Start"), "{}", direct);
+
+    // Handles produced by the statements of a coroutine body are released like in functions.
+    use knoten_core::natives::registry::{HandleSnapshot, registry_create_counter, registry_free, registry_snapshot_handle};
+    let ident = |n: &str| Box::new(Node::Identifier(n.into()));
+    let int = |v: i64| Box::new(Node::IntLiteral(v));
+    // `fn make(c, k) { c = registry_create_counter(); k = 0; while (k < 781) { registry_increment(c); k = k + 1; } return c; }`
+    // (parameters, because new variables are globals).
+    engine.execute(&Node::FnDef("make".into(), vec!["c".into(), "k".into()], Box::new(Node::Block(vec![
+        Node::Assign("c".into(), Box::new(Node::NativeCall("registry_create_counter".into(), vec![]))),
+        Node::Assign("k".into(), int(0)),
+        Node::While(Box::new(Node::Lt(ident("k"), int(781))), Box::new(Node::Block(vec![
+            Node::NativeCall("registry_increment".into(), vec![Node::Identifier("c".into())]),
+            Node::Assign("k".into(), Box::new(Node::Add(ident("k"), int(1)))),
+        ]))),
+        Node::Return(ident("c")),
+    ]))));
+    // `fn churn(n) { make(0, 0); yield; while (0 < n) { n = n - 1; make(0, 0); } yield; }`
+    engine.execute(&Node::FnDef("churn".into(), vec!["n".into()], Box::new(Node::Block(vec![
+        Node::Call("make".into(), vec![Node::IntLiteral(0), Node::IntLiteral(0)]),
+        Node::Yield,
+        Node::While(Box::new(Node::Lt(int(0), ident("n"))), Box::new(Node::Block(vec![
+            Node::Assign("n".into(), Box::new(Node::Sub(ident("n"), int(1)))),
+            Node::Call("make".into(), vec![Node::IntLiteral(0), Node::IntLiteral(0)]),
+        ]))),
+        Node::Yield,
+    ]))));
+    // Counters made by `make`; other tests share the registry.
+    let made = |from: i64, to: i64| (from..to).filter(|id| registry_snapshot_handle(*id) == Some(HandleSnapshot::Counter(781))).count();
+    let before = registry_create_counter();
+    engine.execute(&Node::Assign("kept".into(), Box::new(Node::Call("make".into(), vec![Node::IntLiteral(0), Node::IntLiteral(0)]))));
+    assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("churn"), RelType::Int(2)]), "7");
+    engine.poll_coroutines();
+    let suspended = registry_create_counter();
+    assert_eq!(made(before, suspended), 1);
+    engine.poll_coroutines();
+    assert_eq!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(7)]), "suspended");
+    let after = registry_create_counter();
+    assert_eq!(made(before, after), 1);
+    [before, suspended, after].into_iter().for_each(registry_free);
+
+    // Only the most recent outcomes are kept.
+    engine.execute(&knoten_core::parser::parse_checked("fn quick(n) { return n; }", false).unwrap());
+    for n in 8..8 + 1030 {
+        assert_eq!(call(&mut engine, "Coroutine.Start", vec![s("quick"), RelType::Int(n)]), n.to_string());
+    }
+    engine.poll_coroutines();
+    assert!(call(&mut engine, "Coroutine.Status", vec![RelType::Int(8)]).contains("no coroutine with id 8"));
+    assert_eq!(call(&mut engine, "Coroutine.Await", vec![RelType::Int(1037)]), "1037");
+
+    let def = Node::FnDef("tick".into(), vec![], Box::new(Node::Block(vec![Node::Yield])));
+    let emitted = knoten_core::dsl_emitter::emit_dsl(&def, 0);
+    assert_eq!(knoten_core::parser::parse_checked(&emitted, false).unwrap(), Node::Block(vec![def]), "{}", emitted);
+}
+
+// ------------------------------------------------------------------
+// Test 79: Worker engines with message passing
+// ------------------------------------------------------------------
+#[test]
+fn test_79_worker_engines() {
+    use knoten_core::executor::RelType;
+    let src = r#"
+        fn sq(x) { return x * x; }
+        fn square_sum(n) {
+            let total = 0;
+            while (n > 0) { let total = total + sq(n); let n = n - 1; }
+            return total;
+        }
+        fn peek(path) { return FileRead(path); }
+        fn broken(n) { return nope; }
+        fn nest(n) {
+            let x = [];
+            while (n > 0) { let x = [x]; let n = n - 1; }
+            return x;
+        }
+    "#;
+    let mut engine = ExecutionEngine::new();
+    engine.permissions.allow_fs_read = true;
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    // `while (times > 0) { let m = Worker.Recv(0, 5000); Worker.Send(0, [m, "back"]); let times = times - 1; } return "bye";`
+    let id = |v: i64| Node::IntLiteral(v);
+    let ident = |v: &str| Node::Identifier(v.into());
+    let native = |name: &str, args: Vec<Node>| Node::NativeCall(name.into(), args);
+    let echo = Node::FnDef("echo".into(), vec!["times".into()], Box::new(Node::Block(vec![
+        Node::While(Box::new(Node::Gt(Box::new(ident("times")), Box::new(id(0)))), Box::new(Node::Block(vec![
+            Node::Assign("m".into(), Box::new(native("Worker.Recv", vec![id(0), id(5000)]))),
+            native("Worker.Send", vec![id(0), Node::ArrayCreate(vec![ident("m"), Node::StringLiteral("back".into())])]),
+            Node::Assign("times".into(), Box::new(Node::Sub(Box::new(ident("times")), Box::new(id(1))))),
+        ]))),
+        Node::Return(Box::new(Node::StringLiteral("bye".into()))),
+    ])));
+    engine.execute(&echo);
+    let run = |engine: &mut ExecutionEngine, node: Node| engine.execute(&node).to_string();
+    let spawn = |f: &str, args: Vec<Node>| native("Worker.Spawn", vec![Node::StringLiteral(f.into()), Node::ArrayCreate(args)]);
+
+    // Functions the worker calls are copied over; the result comes back through Join.
+    assert_eq!(run(&mut engine, spawn("square_sum", vec![id(10)])), "1");
+    assert_eq!(run(&mut engine, native("Worker.Join", vec![id(1)])), "385");
+    assert!(run(&mut engine, native("Worker.Join", vec![id(1)])).contains("no worker with id 1"));
+
+    // Messages in both directions, as copies; Recv without a timeout does not wait.
+    assert_eq!(run(&mut engine, spawn("echo", vec![id(2)])), "2");
+    assert_eq!(run(&mut engine, native("Worker.Send", vec![id(2), Node::FloatLiteral(1.0)])), "true");
+    assert_eq!(run(&mut engine, native("Worker.Recv", vec![id(2), id(5000)])), "[1.0, back]");
+    assert_eq!(run(&mut engine, native("Worker.Recv", vec![id(2)])), "");
+    assert_eq!(run(&mut engine, native("Worker.Send", vec![id(2), Node::ArrayCreate(vec![id(7), Node::StringLiteral("x".into())])])), "true");
+    assert_eq!(run(&mut engine, native("Worker.Recv", vec![id(2), id(5000)])), "[[7, x], back]");
+    assert_eq!(run(&mut engine, native("Worker.Join", vec![id(2)])), "bye");
+
+    // Workers get the parent's permissions or fewer, never more.
+    assert_eq!(run(&mut engine, spawn("peek", vec![Node::StringLiteral("Cargo.toml".into())])), "3");
+    assert!(run(&mut engine, native("Worker.Join", vec![id(3)])).contains("[package]"));
+    let denied = native("Worker.Spawn", vec![Node::StringLiteral("peek".into()), Node::ArrayCreate(vec![Node::StringLiteral("Cargo.toml".into())]), ident("perms")]);
+    let mut perms = knoten_core::executor::ObjectMap::new();
+    perms.insert("fs_read".into(), RelType::Bool(false));
+    engine.set_var("perms".into(), RelType::Object(perms.clone()));
+    assert_eq!(run(&mut engine, denied.clone()), "4");
+    let res = run(&mut engine, native("Worker.Join", vec![id(4)]));
+    assert!(res.contains("worker 4 failed: Permission Denied: allow_fs_read is false"), "{}", res);
+    perms.insert("network".into(), RelType::Bool(true));
+    engine.set_var("perms".into(), RelType::Object(perms));
+    let res = run(&mut engine, denied.clone());
+    assert!(res.contains("cannot grant 'network'"), "{}", res);
+
+    // Faults in the worker surface at Join; non-data values cannot be sent.
+    assert_eq!(run(&mut engine, spawn("broken", vec![id(0)])), "5");
+    let res = run(&mut engine, native("Worker.Join", vec![id(5)]));
+    assert!(res.contains("worker 5 failed: Variable 'nope' not found"), "{}", res);
+    assert_eq!(run(&mut engine, spawn("echo", vec![id(1)])), "6");
+    let res = run(&mut engine, native("Worker.Send", vec![id(6), ident("sq")]));
+    assert!(res.starts_with("Fault: Worker.Send: cannot send value"), "{}", res);
+    run(&mut engine, native("Worker.Send", vec![id(6), id(0)]));
+    assert_eq!(run(&mut engine, native("Worker.Join", vec![id(6)])), "bye");
+    let res = run(&mut engine, native("Worker.Recv", vec![id(0)]));
+    assert!(res.contains("only exists inside a worker"), "{}", res);
+
+    // Values nested deeper than JSON parsing allows fault instead of panicking,
+    // whichever side receives them.
+    let too_deep = || Node::Call("nest".into(), vec![id(200)]);
+    assert_eq!(run(&mut engine, spawn("nest", vec![id(200)])), "7");
+    let res = run(&mut engine, native("Worker.Join", vec![id(7)]));
+    assert!(res.starts_with("Fault: Worker.Join: cannot receive value"), "{}", res);
+    assert_eq!(run(&mut engine, spawn("sq", vec![too_deep()])), "8");
+    let res = run(&mut engine, native("Worker.Join", vec![id(8)]));
+    assert!(res.contains("worker 8 failed: Worker.Spawn: cannot receive value"), "{}", res);
+    assert_eq!(run(&mut engine, spawn("echo", vec![id(1)])), "9");
+    assert_eq!(run(&mut engine, native("Worker.Send", vec![id(9), too_deep()])), "true");
+    let res = run(&mut engine, native("Worker.Join", vec![id(9)]));
+    assert!(res.contains("worker 9 failed: Worker.Recv: cannot receive value"), "{}", res);
+    let send_deep = Node::FnDef("send_deep".into(), vec![], Box::new(Node::Block(vec![
+        native("Worker.Send", vec![id(0), too_deep()]),
+        Node::Return(Box::new(id(0))),
+    ])));
+    engine.execute(&send_deep);
+    assert_eq!(run(&mut engine, spawn("send_deep", vec![])), "10");
+    let res = run(&mut engine, native("Worker.Recv", vec![id(10), id(5000)]));
+    assert!(res.starts_with("Fault: Worker.Recv: cannot receive value"), "{}", res);
+    assert_eq!(run(&mut engine, native("Worker.Join", vec![id(10)])), "0");
+}
+
+// ------------------------------------------------------------------
+// Test 80: allowed_domains limits Fetch to listed hosts
+// ------------------------------------------------------------------
+#[test]
+fn test_80_allowed_domains() {
+    use knoten_core::executor::check_domains;
+    let fetch = |url: &str| Node::Fetch { method: "GET".into(), url: url.into(), callback: Box::new(Node::Block(vec![])) };
+    let mut engine = ExecutionEngine::new();
+    let res = engine.execute(&fetch("https://api.example.org/v1")).to_string();
+    assert!(res.contains("Permission Denied: allow_network is false"), "{}", res);
+
+    engine.permissions.allow_network = true;
+    engine.permissions.allowed_domains = vec!["API.example.org".into(), "*.cdn.example.org".into()];
+    for url in ["https://evil.com/x", "https://api.example.org.evil.com/", "https://api.example.org@evil.com/", "http://cdn.example.org/"] {
+        let res = engine.execute(&fetch(url)).to_string();
+        assert!(res.starts_with("Fault: Permission Denied: host '") && res.contains("is not in allowed_domains"), "{}: {}", url, res);
+    }
+    let res = engine.execute(&fetch("not a url")).to_string();
+    assert!(res.contains("Permission Denied: cannot check the host of 'not a url'"), "{}", res);
+    assert!(engine.permissions.check_url("https://api.example.org:8443/v1?q=1").is_ok());
+    assert!(engine.permissions.check_url("https://img.eu.cdn.example.org/a.png").is_ok());
+
+    let allowed = vec!["example.org".to_string(), "10.0.0.1".to_string()];
+    assert!(check_domains(&allowed, "http://EXAMPLE.org./").is_ok());
+    assert!(check_domains(&allowed, "http://10.0.0.1:8080/").is_ok());
+    assert!(check_domains(&allowed, "http://www.example.org/").is_err());
+    assert!(check_domains(&allowed, "http://notexample.org/").is_err());
+    assert!(check_domains(&[], "http://anything.test/").is_ok());
+}
+
+// ------------------------------------------------------------------
+// Test 81: Read and write grants scoped to directories
+// ------------------------------------------------------------------
+#[test]
+fn test_81_scoped_fs_permissions() {
+    let root = "target/tests_scoped_fs";
+    for dir in ["assets", "out", "secret"] {
+        fs::create_dir_all(format!("{}/{}", root, dir)).unwrap();
+    }
+    fs::write(format!("{}/assets/a.txt", root), "asset").unwrap();
+    fs::write(format!("{}/secret/key.txt", root), "hidden").unwrap();
+    let s = |v: &str| Node::StringLiteral(v.to_string());
+    let path = |rel: &str| s(&format!("{}/{}", root, rel));
+    let native = |name: &str, args: Vec<Node>| Node::NativeCall(name.to_string(), args);
+    let extern_call = |function: &str, args: Vec<Node>| Node::ExternCall { module: "registry".into(), function: function.into(), args };
+
+    let mut engine = ExecutionEngine::new();
+    assert!(engine.permissions.grant_read("no/such/dir").is_err());
+    assert!(engine.permissions.grant_read("/").unwrap_err().contains("outside the working directory"));
+    assert!(engine.permissions.grant_read(&format!("{}/assets/a.txt", root)).unwrap_err().contains("is not a directory"));
+    assert!(!engine.permissions.allow_fs_read);
+    engine.permissions.grant_read(&format!("{}/assets", root)).unwrap();
+    engine.permissions.grant_write(&format!("{}/out/", root)).unwrap();
+    assert!(engine.permissions.allow_fs_read && engine.permissions.allow_fs_write);
+
+    // Reads inside the grant work through every entry point.
+    assert_eq!(engine.execute(&Node::FileRead(Box::new(path("assets/a.txt")))).to_string(), "asset");
+    assert_eq!(engine.execute(&Node::FSRead(Box::new(path("out/../assets/a.txt")))).to_string(), "asset");
+    assert_eq!(engine.execute(&native("IO.ReadFile", vec![path("assets/a.txt")])).to_string(), "asset");
+    assert_eq!(engine.execute(&extern_call("registry_read_file", vec![path("assets/a.txt")])).to_string(), "asset");
+    assert_eq!(engine.execute(&native("IO.FileExists", vec![path("assets/missing.txt")])).to_string(), "false");
+    // A missing file in a granted directory reads as empty, as the catalogue documents.
+    assert_eq!(engine.execute(&native("IO.ReadFile", vec![path("assets/missing.txt")])).to_string(), "");
+
+    // Anything else in the working directory is denied.
+    let denied_reads = [
+        Node::FileRead(Box::new(path("secret/key.txt"))),
+        Node::FSRead(Box::new(path("assets/../secret/key.txt"))),
+        native("IO.ReadFile", vec![path("secret/key.txt")]),
+        native("IO.ReadFile", vec![path("secret/missing.txt")]),
+        native("IO.FileExists", vec![path("secret/key.txt")]),
+        extern_call("registry_read_file", vec![path("secret/key.txt")]),
+        extern_call("registry_texture_load", vec![path("secret/key.txt")]),
+    ];
+    for node in &denied_reads {
+        let res = engine.execute(node).to_string();
+        assert!(res.contains("is outside the allowed read directories"), "{}", res);
+    }
+    // A link inside the grant cannot lead out of it.
+    #[cfg(unix)]
+    {
+        let link = format!("{}/assets/key_link.txt", root);
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink("../secret/key.txt", &link).unwrap();
+        let res = engine.execute(&native("IO.ReadFile", vec![path("assets/key_link.txt")])).to_string();
+        assert!(res.contains("is outside the allowed read directories"), "{}", res);
+    }
+
+    // Writes are scoped separately: the read grant does not allow writing.
+    assert_eq!(engine.execute(&Node::FileWrite(Box::new(path("out/b.txt")), Box::new(s("b")))).to_string(), "");
+    assert_eq!(engine.execute(&native("IO.AppendFile", vec![path("out/b.txt"), s("!")])).to_string(), "true");
+    assert_eq!(fs::read_to_string(format!("{}/out/b.txt", root)).unwrap(), "b!");
+    let denied_writes = [
+        Node::FSWrite(Box::new(path("assets/a.txt")), Box::new(s("x"))),
+        native("IO.WriteFile", vec![path("secret/key.txt"), s("x")]),
+        extern_call("registry_write_file", vec![path("out/../secret/key.txt"), s("x")]),
+        extern_call("registry_file_create", vec![path("new.txt")]),
+    ];
+    for node in &denied_writes {
+        let res = engine.execute(node).to_string();
+        assert!(res.contains("is outside the allowed write directories"), "{}", res);
+    }
+    assert_eq!(fs::read_to_string(format!("{}/assets/a.txt", root)).unwrap(), "asset");
+    assert_eq!(fs::read_to_string(format!("{}/secret/key.txt", root)).unwrap(), "hidden");
+    assert!(!std::path::Path::new(&format!("{}/new.txt", root)).exists());
+}
+
+// ------------------------------------------------------------------
+// Test 82: Syntax errors come back as values, never as panics
+// ------------------------------------------------------------------
+#[test]
+fn test_82_parse_errors_are_returned() {
+    use knoten_core::parser::{parse_checked, ParseError};
+
+    let err = parse_checked("let x = 1;\nlet y = ;", false).unwrap_err();
+    assert_eq!(err, ParseError { line: 2, col: 9, hint: "Unexpected token in expression: Semi".to_string() });
+    assert_eq!(err.to_string(), r#"{"diagnostic": {"line": 2, "col": 9, "hint": "Unexpected token in expression: Semi"}}"#);
+
+    // Lexer errors, out-of-range literals and missing built-in arguments were panics before.
+    for src in ["let a = 1 $ 2;", "let big = 99999999999999999999;", "Print();", "Concat(\"a\");", "fn f( { }", "(1 + 2"] {
+        assert!(parse_checked(src, true).is_err(), "{}", src);
+    }
+
+    // The panicking entry point still reports the same diagnostic.
+    let payload = std::panic::catch_unwind(|| knoten_core::parser::Parser::new("let = 2;").parse()).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().unwrap(), &parse_checked("let = 2;", false).unwrap_err().to_string());
+}
+
+// ------------------------------------------------------------------
+// Test 83: Timers and coroutines advance from the script's frame loop
+// ------------------------------------------------------------------
+#[test]
+fn test_83_frame_loop_pumps_timers_and_coroutines() {
+    use knoten_core::executor::{NativeHandle, RelType};
+    let src = r#"
+        let log = [];
+        let frame = 0;
+        fn tick() { ArrayPush(log, "tick"); }
+        fn walk() {
+            ArrayPush(log, "step1");
+            yield;
+            ArrayPush(log, "step2");
+            yield;
+            ArrayPush(log, "step3");
+        }
+    "#;
+    let mut engine = ExecutionEngine::new();
+    engine.enable_deterministic(1);
+    engine.execute(&knoten_core::parser::parse_checked(src, false).unwrap());
+    let native = |name: &str, args: Vec<Node>| Node::NativeCall(name.into(), args);
+    let ident = |v: &str| Box::new(Node::Identifier(v.into()));
+    engine.execute(&native("Timer.SetInterval", vec![Node::StringLiteral("tick".into()), Node::IntLiteral(0)]));
+    engine.execute(&native("Coroutine.Start", vec![Node::StringLiteral("walk".into())]));
+    let frame_loop = |frames: i64, frame_end: Node| Node::While(
+        Box::new(Node::Lt(ident("frame"), Box::new(Node::IntLiteral(frames)))),
+        Box::new(Node::Block(vec![
+            Node::Assign("frame".into(), Box::new(Node::Add(ident("frame"), Box::new(Node::IntLiteral(1))))),
+            frame_end,
+        ])),
+    );
+    let log = |engine: &ExecutionEngine| engine.get_var("log").unwrap().to_string();
+
+    // `PollEvents` pumps before its body; the host never calls the pump here.
+    engine.execute(&frame_loop(3, Node::PollEvents(Box::new(Node::Block(vec![])))));
+    assert_eq!(log(&engine), "[tick, step1, tick, step2, tick, step3]");
+
+    // So does presenting a frame, as `doom_mvp.nod`'s render loop does.
+    engine.set_var("win".into(), RelType::Handle(NativeHandle(-1)));
+    engine.execute(&frame_loop(5, native("registry_window_update", vec![Node::Identifier("win".into())])));
+    assert_eq!(log(&engine), "[tick, step1, tick, step2, tick, step3, tick, tick]");
+    assert_eq!(engine.execute(&native("Coroutine.Pending", vec![])).to_string(), "0");
+
+    // A callback that reaches a frame boundary itself does not pump again.
+    let nested = Node::FnDef("tick".into(), vec![], Box::new(Node::Block(vec![
+        Node::ArrayPush(ident("log"), Box::new(Node::StringLiteral("outer".into()))),
+        Node::PollEvents(Box::new(Node::Block(vec![]))),
+    ])));
+    engine.execute(&nested);
+    engine.set_var("log".into(), RelType::Array(vec![]));
+    engine.poll_async_bridge();
+    assert_eq!(log(&engine), "[outer]");
+}
+
+// ------------------------------------------------------------------
+// Test 84: Every catalogued native dispatches, every dispatched native is catalogued
+// ------------------------------------------------------------------
+#[test]
+fn test_84_catalogue_matches_dispatch() {
+    let mut engine = ExecutionEngine::new();
+    let catalogue = engine.native_catalogue().to_vec();
+
+    // Called without arguments: arity faults are fine, "not found" is not.
+    // registry_gpu_init takes no arguments and needs a GPU adapter.
+    for native in catalogue.iter().filter(|n| n.name != "registry_gpu_init") {
+        let mut calls = Vec::new();
+        if matches!(native.module.as_deref(), None | Some("registry")) {
+            calls.push(Node::NativeCall(native.name.clone(), vec![]));
+        }
+        if let Some(module) = &native.module {
+            calls.push(Node::ExternCall { module: module.clone(), function: native.name.clone(), args: vec![] });
+        }
+        for call in calls {
+            let res = engine.execute(&call).to_string();
+            assert!(!res.contains("' not found"), "{}: {}", native.signature(), res);
+        }
+    }
+
+    // Match arms naming a `Module.Name` native in the dispatch code.
+    let sources = [
+        include_str!("../src/natives/math.rs"),
+        include_str!("../src/natives/io.rs"),
+        include_str!("../src/natives/string.rs"),
+        include_str!("../src/natives/regex.rs"),
+        include_str!("../src/natives/json.rs"),
+        include_str!("../src/natives/datetime.rs"),
+        include_str!("../src/natives/array.rs"),
+        include_str!("../src/natives/catalogue.rs"),
+        include_str!("../src/timers.rs"),
+        include_str!("../src/coroutines.rs"),
+        include_str!("../src/workers.rs"),
+        include_str!("../src/determinism.rs"),
+    ];
+    let arm = regex::Regex::new(r#""([A-Z][A-Za-z]*)\.([A-Z][A-Za-z0-9]*)"(?: if [^=]*)? =>"#).unwrap();
+    let mut prefixes = std::collections::BTreeSet::new();
+    for source in sources {
+        for caps in arm.captures_iter(source) {
+            let name = format!("{}.{}", &caps[1], &caps[2]);
+            assert!(catalogue.iter().any(|n| n.module.is_none() && n.name == name), "{} is dispatched but not catalogued", name);
+            prefixes.insert(caps[1].to_string());
+        }
+    }
+    assert_eq!(
+        prefixes.into_iter().collect::<Vec<_>>(),
+        ["Array", "Coroutine", "DateTime", "IO", "JSON", "Math", "Native", "Regex", "String", "Timer", "Worker"]
+    );
+}
+
+// ------------------------------------------------------------------
+// Test 85: Restoring a snapshot neither leaks old nor orphaned registry entries
+// ------------------------------------------------------------------
+#[test]
+fn test_85_snapshot_restore_releases_handles() {
+    use knoten_core::executor::{NativeHandle, RelType};
+    use knoten_core::natives::registry::{HandleSnapshot, registry_create_counter, registry_free, registry_snapshot_handle};
+    let handle_id = |engine: &ExecutionEngine| match engine.get_var("counter") {
+        Some(RelType::Handle(NativeHandle(id))) => id,
+        other => panic!("expected a Handle, got {:?}", other.map(|v| v.to_string())),
+    };
+    // Counters with this value created by the test; other tests share the registry.
+    let marked_ids = |from: i64, to: i64, count: i64| {
+        (from..to).filter(|id| registry_snapshot_handle(*id) == Some(HandleSnapshot::Counter(count))).count()
+    };
+
+    let mut engine = ExecutionEngine::new();
+    engine.execute(&Node::Assign(
+        "counter".into(),
+        Box::new(Node::ExternCall { module: "registry".into(), function: "registry_create_counter".into(), args: vec![] }),
+    ));
+    let mut json: serde_json::Value = serde_json::from_str(&engine.snapshot().to_json().unwrap()).unwrap();
+    let old = handle_id(&engine);
+    json["handles"][old.to_string()] = serde_json::json!({ "Counter": 850_001 });
+    json["handles"]["999999"] = serde_json::json!({ "Counter": 850_002 });
+
+    // Over a live engine, the old entry is released; the unreferenced one is never created.
+    let before = registry_create_counter();
+    engine.restore_snapshot(&json.to_string()).unwrap();
+    let after = registry_create_counter();
+    assert!(registry_snapshot_handle(old).is_none());
+    let restored = handle_id(&engine);
+    assert_eq!(registry_snapshot_handle(restored), Some(HandleSnapshot::Counter(850_001)));
+    assert_eq!(marked_ids(before, after, 850_002), 0);
+
+    // A snapshot that fails to load creates no entries and keeps the engine's state.
+    json["handles"][old.to_string()] = serde_json::json!({ "Counter": 850_003 });
+    json["camera"] = serde_json::json!("broken");
+    let before_bad = registry_create_counter();
+    assert!(engine.restore_snapshot(&json.to_string()).unwrap_err().starts_with("Invalid snapshot"));
+    let after_bad = registry_create_counter();
+    assert_eq!(marked_ids(before_bad, after_bad, 850_003), 0);
+    assert_eq!(handle_id(&engine), restored);
+
+    drop(engine);
+    assert!(registry_snapshot_handle(restored).is_none());
+    [before, after, before_bad, after_bad].into_iter().for_each(registry_free);
+}